@@ -0,0 +1,15 @@
+#![no_main]
+use libfuzzer_sys::arbitrary::Unstructured;
+use libfuzzer_sys::fuzz_target;
+
+use worng::run_string;
+use worng_fuzz::Generator;
+
+// functions and their environment keep each other alive, so closures leak and memory
+// grows with every run, pass `-rss_limit_mb` when fuzzing for long.
+fuzz_target!(|data: &[u8]| {
+  let mut u = Unstructured::new(data);
+  if let Ok(program) = Generator::new(&mut u).program() {
+    run_string(program);
+  }
+});
@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use worng::{Scanner, Parser};
+
+fuzz_target!(|source: &str| {
+  let tokens = Scanner::new(source).scan_tokens();
+  let _ = Parser::new(tokens).parse();
+});
@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use worng::{Scanner, Parser, Resolver};
+
+fuzz_target!(|source: &str| {
+  let tokens = Scanner::new(source).scan_tokens();
+  if let Ok(mut statements) = Parser::new(tokens).parse() {
+    Resolver::new().resolve(&mut statements);
+  }
+});
@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use worng::Scanner;
+
+fuzz_target!(|source: &str| {
+  Scanner::new(source).scan_tokens();
+});
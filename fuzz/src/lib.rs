@@ -0,0 +1,230 @@
+//! turns fuzzer bytes into syntactically valid worng programs, so the interpreter
+//! gets exercised past the parser.
+//!
+//! the programs always terminate quickly: functions can only call functions declared
+//! before them, so nothing recurses, and there are only a few loops, calls and functions
+//! per program so they can't multiply into billions of steps.
+//! they don't use natives either, nothing touches the file system or sleeps.
+
+use libfuzzer_sys::arbitrary::{Result, Unstructured};
+
+const MAX_DEPTH: usize = 4;
+const MAX_STATEMENTS: usize = 64;
+const MAX_LOOP: u8 = 4;
+const MAX_PARAMS: usize = 3;
+const MAX_LOOPS: usize = 3;
+const MAX_FUNCTIONS: usize = 3;
+const MAX_CALLS: usize = 8;
+
+const BINARY_OPERATORS: [&str; 11] = ["+", "-", "*", "/", "<", "<=", ">", ">=", "==", "!=", "and"];
+const STRINGS: [&str; 4] = ["\"\"", "\"a\"", "\"worng\"", "\"1\""];
+
+struct Function {
+  name: String,
+  arity: usize,
+}
+
+#[derive(Default)]
+struct Scope {
+  variables: Vec<String>,
+  functions: Vec<Function>,
+}
+
+pub struct Generator<'a, 'b> {
+  u: &'a mut Unstructured<'b>,
+  out: String,
+  scopes: Vec<Scope>,
+  statements: usize,
+  loops: usize,
+  functions: usize,
+  calls: usize,
+  next_name: usize,
+}
+
+impl<'a, 'b> Generator<'a, 'b> {
+  pub fn new(u: &'a mut Unstructured<'b>) -> Self {
+    Generator { u, out: String::new(), scopes: vec![Scope::default()], statements: 0, loops: 0, functions: 0, calls: 0, next_name: 0 }
+  }
+
+  pub fn program(mut self) -> Result<String> {
+    while self.statements < MAX_STATEMENTS && !self.u.is_empty() {
+      self.statement(0)?;
+    }
+    Ok(self.out)
+  }
+
+  fn statement(&mut self, depth: usize) -> Result<()> {
+    self.statements += 1;
+    let kinds = if depth < MAX_DEPTH && self.statements < MAX_STATEMENTS { 8 } else { 4 };
+
+    match self.u.int_in_range(0..=kinds - 1)? {
+      0 => {
+        let name = self.fresh_name("v");
+        self.out.push_str(&format!("var {} = ", name));
+        self.expression(depth)?;
+        self.out.push_str(";\n");
+        self.scope().variables.push(name);
+      },
+      1 => {
+        self.out.push_str("print ");
+        self.expression(depth)?;
+        self.out.push_str(";\n");
+      },
+      2 => {
+        let variables: Vec<String> = self.scopes.iter().flat_map(|scope| scope.variables.clone()).collect();
+        if variables.is_empty() {
+          self.out.push_str("print nil;\n");
+        } else {
+          let name = self.u.choose(&variables)?.clone();
+          self.out.push_str(&format!("{} = ", name));
+          self.expression(depth)?;
+          self.out.push_str(";\n");
+        }
+      },
+      3 => {
+        self.expression(depth)?;
+        self.out.push_str(";\n");
+      },
+      4 => {
+        self.out.push_str("if (");
+        self.expression(depth)?;
+        self.out.push_str(") ");
+        self.block(depth)?;
+        if self.u.arbitrary()? {
+          self.out.push_str(" else ");
+          self.block(depth)?;
+        }
+        self.out.push('\n');
+      },
+      5 if self.loops < MAX_LOOPS => {
+        self.loops += 1;
+        // the counter isn't added to the scope, so nothing else can assign it.
+        let counter = self.fresh_name("i");
+        let times = self.u.int_in_range(0..=MAX_LOOP)?;
+        self.out.push_str(&format!("for (var {0} = 0; {0} < {1}; {0} = {0} + 1) ", counter, times));
+        self.block(depth)?;
+        self.out.push('\n');
+      },
+      6 if self.functions < MAX_FUNCTIONS => {
+        self.functions += 1;
+        self.function(depth)?;
+      },
+      _ => {
+        self.block(depth)?;
+        self.out.push('\n');
+      },
+    }
+    Ok(())
+  }
+
+  fn block(&mut self, depth: usize) -> Result<()> {
+    self.out.push_str("{\n");
+    self.scopes.push(Scope::default());
+    let count = self.u.int_in_range(0..=3)?;
+    for _ in 0..count {
+      if self.statements >= MAX_STATEMENTS {
+        break;
+      }
+      self.statement(depth + 1)?;
+    }
+    self.scopes.pop();
+    self.out.push('}');
+    Ok(())
+  }
+
+  // declared after its body, a function can't call itself.
+  fn function(&mut self, depth: usize) -> Result<()> {
+    let name = self.fresh_name("f");
+    let arity = self.u.int_in_range(0..=MAX_PARAMS)?;
+    let params: Vec<String> = (0..arity).map(|_| self.fresh_name("p")).collect();
+
+    self.out.push_str(&format!("fun {}({}) ", name, params.join(", ")));
+    self.scopes.push(Scope { variables: params, functions: Vec::new() });
+    self.out.push_str("{\n");
+    let count = self.u.int_in_range(0..=3)?;
+    for _ in 0..count {
+      if self.statements >= MAX_STATEMENTS {
+        break;
+      }
+      self.statement(depth + 1)?;
+    }
+    self.out.push_str("return ");
+    self.expression(depth + 1)?;
+    self.out.push_str(";\n}\n");
+    self.scopes.pop();
+
+    self.scope().functions.push(Function { name, arity });
+    Ok(())
+  }
+
+  fn expression(&mut self, depth: usize) -> Result<()> {
+    let kinds = if depth < MAX_DEPTH { 7 } else { 3 };
+
+    match self.u.int_in_range(0..=kinds - 1)? {
+      0 => {
+        let number = self.u.int_in_range(0..=100u8)?;
+        self.out.push_str(&number.to_string());
+      },
+      1 => {
+        let literal = *self.u.choose(&["true", "false", "nil", STRINGS[0], STRINGS[1], STRINGS[2], STRINGS[3]])?;
+        self.out.push_str(literal);
+      },
+      2 => {
+        let variables: Vec<String> = self.scopes.iter().flat_map(|scope| scope.variables.clone()).collect();
+        if variables.is_empty() {
+          self.out.push_str("nil");
+        } else {
+          let name = self.u.choose(&variables)?.clone();
+          self.out.push_str(&name);
+        }
+      },
+      3 => {
+        self.out.push_str(if self.u.arbitrary()? { "-" } else { "!" });
+        self.expression(depth + 1)?;
+      },
+      4 => {
+        self.out.push('(');
+        self.expression(depth + 1)?;
+        self.out.push(')');
+      },
+      5 => {
+        self.expression(depth + 1)?;
+        let operator = if self.u.ratio(1, 12)? { "or" } else { *self.u.choose(&BINARY_OPERATORS)? };
+        self.out.push_str(&format!(" {} ", operator));
+        self.expression(depth + 1)?;
+      },
+      _ => {
+        let functions: Vec<(String, usize)> = self.scopes
+          .iter()
+          .flat_map(|scope| scope.functions.iter().map(|function| (function.name.clone(), function.arity)))
+          .collect();
+        if functions.is_empty() || self.calls >= MAX_CALLS {
+          self.out.push_str("nil");
+          return Ok(());
+        }
+        self.calls += 1;
+
+        let (name, arity) = self.u.choose(&functions)?.clone();
+        self.out.push_str(&name);
+        self.out.push('(');
+        for i in 0..arity {
+          if i > 0 {
+            self.out.push_str(", ");
+          }
+          self.expression(depth + 1)?;
+        }
+        self.out.push(')');
+      },
+    }
+    Ok(())
+  }
+
+  fn scope(&mut self) -> &mut Scope {
+    self.scopes.last_mut().unwrap()
+  }
+
+  fn fresh_name(&mut self, prefix: &str) -> String {
+    self.next_name += 1;
+    format!("{}{}", prefix, self.next_name)
+  }
+}
@@ -1,4 +1,21 @@
 mod worng;
 
 pub use worng::worng_value::*;
-pub use worng::wasm::*;
\ No newline at end of file
+pub use worng::driver::*;
+pub use worng::wasm::*;
+pub use worng::native_fs::FsAccess;
+pub use worng::interpreter::with_stack;
+pub use worng::scanner::Scanner;
+pub use worng::parser::Parser;
+pub use worng::resolver::Resolver;
+pub use worng::formatter::format_source;
+pub use worng::lint::{lint, Rule, Warning};
+pub use worng::json::Json;
+pub use worng::ast_json::AstJson;
+pub use worng::ast_printer::{print_program, read_program, read_expr};
+pub use worng::expr::{Expr, ExprVisitor, ExprVisitorMut, walk_expr, walk_expr_mut, print_expr};
+pub use worng::statement::{Stmt, StmtVisitor, StmtVisitorMut, walk_stmt, walk_stmt_mut, print_stmt};
+pub use worng::visitor::VisitResult;
+pub use worng::token::{Token, Literal};
+pub use worng::profiler::{Profiler, FunctionProfile};
+pub use worng::coverage::{FileCoverage, Branch};
@@ -1,21 +1,206 @@
 extern crate worng;
 // pub mod bin; // not part of interpreter, only for debugging.
 
-use std::env;
-use worng::Worng;
+use std::io::{self, Write};
+use std::fs;
+use clap::Clap;
+use std::path::PathBuf;
+use worng::{Worng, FsAccess, Rule, FileCoverage, with_stack};
 
+/// dynamic programming language and tree-walk interpreter.
+/// without a command it starts the REPL.
+#[derive(Clap)]
+#[clap(name = "worng", version = "0.1.0")]
+struct Opts {
+  /// run the given code instead of a file.
+  #[clap(short = "e", long = "eval")]
+  eval: Option<String>,
+
+  /// confine the `fs` natives to this directory.
+  #[clap(long = "fs-root", global = true)]
+  fs_root: Option<String>,
+
+  /// disable the `fs` natives.
+  #[clap(long = "no-fs", global = true)]
+  no_fs: bool,
+
+  #[clap(subcommand)]
+  command: Option<Command>,
+}
+
+#[derive(Clap)]
+enum Command {
+  /// run a script, reads it from stdin when <file> is `-` or missing.
+  Run {
+    file: Option<String>,
+    /// passed to the script, after `--`.
+    #[clap(last = true)]
+    args: Vec<String>,
+    /// print the time and calls of each function and the hottest lines to stderr when the script ends.
+    #[clap(long = "profile")]
+    profile: bool,
+    /// where `--profile` writes the collapsed stacks, for flamegraph tools.
+    #[clap(long = "profile-stacks", default_value = "profile.folded")]
+    profile_stacks: String,
+    /// print the lines and branches the script ran to stderr when it ends, and write them as lcov.
+    #[clap(long = "coverage", conflicts_with = "profile")]
+    coverage: bool,
+    /// where `--coverage` writes the lcov tracefile.
+    #[clap(long = "lcov", default_value = "lcov.info")]
+    lcov: String,
+  },
+  /// start the interactive prompt.
+  Repl,
+  /// scan, parse and resolve without running.
+  Check {
+    files: Vec<String>,
+  },
+  /// run the `test_*` functions of the `.worng` files in a directory.
+  Test {
+    path: String,
+    /// print the lines and branches the tests ran after the report, and write them as lcov.
+    #[clap(long = "coverage")]
+    coverage: bool,
+    /// where `--coverage` writes the lcov tracefile.
+    #[clap(long = "lcov", default_value = "lcov.info")]
+    lcov: String,
+  },
+  /// rewrite the files in the canonical layout, formats stdin to stdout without files.
+  Fmt {
+    files: Vec<String>,
+    /// only list the files that aren't formatted, and fail if there are any.
+    #[clap(long = "check")]
+    check: bool,
+  },
+  /// warn about code that is legal but most likely a mistake, reads stdin without files.
+  Lint {
+    files: Vec<String>,
+    /// leave out a rule, eg. `--allow shadowing`.
+    #[clap(short = "A", long = "allow", number_of_values = 1)]
+    allow: Vec<String>,
+  },
+  /// serve the Language Server Protocol on stdin and stdout, for editors.
+  Lsp,
+  /// run a script in the debugger, it reads its commands from stdin.
+  Debug {
+    file: String,
+    /// passed to the script, after `--`.
+    #[clap(last = true)]
+    args: Vec<String>,
+  },
+  /// serve the Debug Adapter Protocol on stdin and stdout, for editors.
+  Dap,
+  /// print the tokens of a script.
+  Tokens {
+    file: Option<String>,
+    /// as a JSON array of tokens with their spans.
+    #[clap(long = "json")]
+    json: bool,
+  },
+  /// print the syntax tree of a script.
+  Ast {
+    file: Option<String>,
+    /// as JSON, with the spans of the tokens and the resolved scope depth of each name.
+    #[clap(long = "json")]
+    json: bool,
+  },
+}
+
+// deep recursion in a script is stopped by the interpreter,
+// which needs more stack than the main thread gets on most platforms.
 fn main() {
-  // let args: Vec<String> = env::args().collect();
-  let mut args = env::args();
-  args.next();
-  let args: Vec<String> = args.collect();
-
-  let v: Vec<&str> = args.iter().map(|x| &**x).collect(); // Vec<String> -> Vec<&str>
-  let mut l = Worng { 
-    had_error: false, 
-    had_runtime_error: false, 
+  std::process::exit(with_stack(run));
+}
+
+fn run() -> i32 {
+  let opts = Opts::parse();
+  let mut l = Worng::new();
+  let stdin = io::stdin();
+
+  if opts.no_fs {
+    l.fs_access = FsAccess::Denied;
+  } else if let Some(root) = opts.fs_root {
+    l.fs_access = FsAccess::Within(PathBuf::from(root));
+  }
+
+  let result = match (opts.eval, opts.command) {
+    (Some(code), _) => {
+      l.run_source(code, &mut stdin.lock(), &mut io::stdout());
+      Ok(())
+    },
+    (None, Some(Command::Run { file, args, profile: false, coverage: false, .. })) => {
+      l.args = args;
+      Worng::read_source(file.as_deref()).map(|source| l.run_source(source, &mut stdin.lock(), &mut io::stdout()))
+    },
+    (None, Some(Command::Run { file, args, profile: true, profile_stacks, .. })) => {
+      l.args = args;
+      Worng::read_source(file.as_deref()).and_then(|source| {
+        let profiler = l.profile_source(source.clone(), &mut stdin.lock(), &mut io::stdout());
+        eprint!("{}", profiler.summary(&source));
+        fs::write(&profile_stacks, profiler.folded())
+      })
+    },
+    (None, Some(Command::Run { file, args, coverage: true, lcov, .. })) => {
+      l.args = args;
+      let path = file.clone().unwrap_or_else(|| "-".to_string());
+      Worng::read_source(file.as_deref()).and_then(|source| {
+        let files: Vec<FileCoverage> = l.cover_source(&path, source, &mut stdin.lock(), &mut io::stdout()).into_iter().collect();
+        write_coverage(&files, &mut io::stderr(), &lcov)
+      })
+    },
+    (None, Some(Command::Check { files })) => {
+      if files.is_empty() {
+        Worng::read_source(None).map(|source| l.check_source(source))
+      } else {
+        files.iter().map(|file| Worng::read_source(Some(file)).map(|source| l.check_source(source))).collect()
+      }
+    },
+    (None, Some(Command::Test { path, coverage: false, .. })) => l.run_tests(&path, &mut io::stdout()),
+    (None, Some(Command::Test { path, coverage: true, lcov })) => {
+      l.cover_tests(&path, &mut io::stdout()).and_then(|files| {
+        println!();
+        write_coverage(&files, &mut io::stdout(), &lcov)
+      })
+    },
+    (None, Some(Command::Fmt { files, check })) => l.format_files(&files, check, &mut io::stdout()),
+    (None, Some(Command::Lint { files, allow })) => {
+      allowed_rules(&allow).and_then(|allowed| l.lint_files(&files, &allowed, &mut io::stdout()))
+    },
+    (None, Some(Command::Lsp)) => l.serve_lsp(&mut stdin.lock(), &mut io::stdout()),
+    (None, Some(Command::Debug { file, args })) => {
+      l.args = args;
+      Worng::read_source(Some(&file)).map(|source| l.debug_source(source, &mut stdin.lock(), &mut io::stdout()))
+    },
+    (None, Some(Command::Dap)) => l.serve_dap(&mut stdin.lock(), &mut io::stdout()),
+    (None, Some(Command::Tokens { file, json })) => {
+      Worng::read_source(file.as_deref()).and_then(|source| l.dump_tokens(source, json, &mut io::stdout()))
+    },
+    (None, Some(Command::Ast { file, json })) => {
+      Worng::read_source(file.as_deref()).and_then(|source| l.dump_ast(source, json, &mut io::stdout()))
+    },
+    (None, Some(Command::Repl)) | (None, None) => {
+      l.run_prompt(&mut stdin.lock(), &mut io::stdout()).map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))
+    },
   };
 
-  Worng::main(&mut l, v);
+  if let Err(err) = result {
+    eprintln!("worng: {}", err);
+    std::process::exit(66);
+  }
+
+  l.exit_code()
+}
+
+fn allowed_rules(names: &[String]) -> io::Result<Vec<Rule>> {
+  names
+    .iter()
+    .map(|name| {
+      Rule::from_name(name).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("unknown lint rule '{}'", name)))
+    })
+    .collect()
+}
 
+fn write_coverage(files: &[FileCoverage], summary: &mut dyn Write, lcov: &str) -> io::Result<()> {
+  write!(summary, "{}", FileCoverage::summary(files))?;
+  fs::write(lcov, FileCoverage::lcov(files))
 }
@@ -1,66 +1,437 @@
-use std::io::{ self, Read, Write};
-use std::io::LineWriter;
+//! writes `Expr` and `Stmt` from the spec in `main`: the enums and visitor traits, with a method
+//! for each kind of node, walk functions, for passes reading the tree and for those changing it,
+//! and `print_expr` and `print_stmt`, writing the S-expressions their `Display` impls give. only
+//! `read_program` in `ast_printer.rs`, reading them back, is written by hand and needs a case for a
+//! new node.
+//!
+//!     cargo run --bin generate_ast -- src/worng
+//!
+//! a node is `Name : Type field, ... = form`, a `// comment` after it documents it. fields holding
+//! an `Expr` or a `Stmt`, also in a `Box`, `Vec` or `Option`, are the ones walked. the form is how
+//! the node prints: `(head part ...)`, or a single field bare. a part naming a field prints it, a
+//! `Vec` of nodes spread and one of tokens as a list, an `Option` only when there is one, like the
+//! parts in `[...]` with it. other words print as they are, a token field as the head gives its
+//! lexeme.
+
 use std::env;
-use std::fs::File;
-use std::io::prelude::*;
+use std::fs;
+use std::io::{self, Write};
 use std::path::Path;
+use std::process;
 
-type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
-
-fn main() -> Result<()>{
+fn main() -> io::Result<()> {
   let args: Vec<String> = env::args().collect();
   let output = match args.len() {
-    2 => &args[1],
-    _ => { 
-      writeln!(io::stdout(), "Usage: generate_ast <output directory>")?;
-      std::process::exit(64);
+    2 => Path::new(&args[1]),
+    _ => {
+      writeln!(io::stderr(), "Usage: generate_ast <output directory>")?;
+      process::exit(64);
     }
   };
 
   define_ast(
-    output, 
-    String::from("Expr"), 
-    vec![
-      "Binary   : Expr left, Token operator, Expr right",
-      "Grouping : Expr expression",
-      "Literal  : Object value",
-      "Unary    : Token operator, Expr right"
-    ]
-  );
-
-  Ok(())
+    output,
+    "expr.rs",
+    "Expr",
+    "expression",
+    &[
+      "use std::fmt;",
+      "",
+      "use super::ast_printer::{list, print_literal};",
+      "use super::token::{Literal, Token};",
+      "use super::visitor::VisitResult;",
+    ],
+    &[
+      "Unary    : Token operator, Box<Expr> right = (operator right)",
+      "Literal  : Literal value = value",
+      "Binary   : Box<Expr> left, Token operator, Box<Expr> right = (operator left right)",
+      "Call     : Box<Expr> callee, Token paren, Vec<Expr> arguments = (call callee arguments) // the paren is the one closing the arguments.",
+      "Get      : Box<Expr> object, Token name = (get object name)",
+      "Grouping : Box<Expr> expression = (group expression)",
+      "Var      : Token name, Option<usize> depth = name // the depth is what the resolver found, none for globals.",
+      "Assign   : Token name, Box<Expr> value, Option<usize> depth = (assign name value)",
+      "Set      : Box<Expr> object, Token name, Box<Expr> value = (set object name value)",
+      "Logical  : Box<Expr> left, Token operator, Box<Expr> right = (operator left right)",
+      "This     : Token keyword, Option<usize> depth = keyword",
+      "Super    : Token keyword, Token method, Option<usize> depth = (super method)",
+    ],
+  )?;
+
+  define_ast(
+    output,
+    "statement.rs",
+    "Stmt",
+    "statement",
+    &[
+      "use std::fmt;",
+      "",
+      "use super::ast_printer::{list, print_names};",
+      "use super::expr::{print_expr, Expr, ExprVisitor, ExprVisitorMut};",
+      "use super::token::Token;",
+      "use super::visitor::VisitResult;",
+    ],
+    &[
+      "Expr   : Expr expression = (expr expression)",
+      "Print  : Token keyword, Expr expression = (print expression) // the keyword gives the statement a line, like those of `If` and `While`.",
+      "Var    : Token name, Expr initializer = (var name initializer) // nil without an initializer.",
+      "Block  : Vec<Stmt> statements = (block statements)",
+      "Class  : Token name, Option<Expr> superclass, Vec<Stmt> methods = (class name [< superclass] methods)",
+      "If     : Token keyword, Expr condition, Box<Stmt> then_branch, Box<Option<Stmt>> else_branch = (if condition then_branch else_branch)",
+      "While  : Token keyword, Expr condition, Box<Stmt> body = (keyword condition body) // a `for` loop is a `While` with the `for` keyword, in a `Block` after its initializer.",
+      "Func   : Token name, Vec<Token> params, Box<Stmt> body = (fun name params body)",
+      "Return : Token keyword, Box<Expr> value = (return value) // nil without a value.",
+    ],
+  )
+}
+
+struct Node<'a> {
+  name: &'a str,
+  fields: Vec<(&'a str, &'a str)>,
+  form: &'a str,
+  comment: Option<&'a str>,
 }
 
-fn define_ast(output_dir: &str, base_name: String, types: Vec<&str> ) -> Result<()> {
-  let road_not_taken = b"struct 
-  Somewhere ages and ages hence:";
-  
-  let path_name: String = format!("{}/{}.rs", output_dir , base_name);
-  let path = Path::new(&path_name);
-  let display = path.display();
-
-  let mut file = match File::create(&path) {
-    Err(why) => panic!("couldn't create {}: {}", display, why),
-    Ok(file) => file,
+fn parse_node(spec: &str) -> Node<'_> {
+  let (spec, comment) = match spec.split_once("//") {
+    Some((spec, comment)) => (spec, Some(comment.trim())),
+    None => (spec, None),
   };
+  let (spec, form) = spec.split_once('=').expect("a node is `Name : Type field, ... = form`");
+  let (name, fields) = spec.split_once(':').expect("a node is `Name : Type field, ... = form`");
+  let fields = fields
+    .split(',')
+    .map(|field| field.trim().rsplit_once(' ').expect("a field is `Type name`"))
+    .collect();
+  Node { name: name.trim(), fields, form: form.trim(), comment }
+}
+
+fn define_ast(output_dir: &Path, file_name: &str, base_name: &str, noun: &str, imports: &[&str], types: &[&str]) -> io::Result<()> {
+  let nodes: Vec<Node> = types.iter().map(|spec| parse_node(spec)).collect();
+  // the bases the nodes hold, the own one first.
+  let mut bases = vec![base_name];
+  for node in nodes.iter() {
+    for &(field_type, _) in node.fields.iter() {
+      let base = unwrap_type(field_type).1;
+      if (base == "Expr" || base == "Stmt") && !bases.contains(&base) {
+        bases.push(base);
+      }
+    }
+  }
+
+  let mut out = String::new();
+  out.push_str("// generated by `cargo run --bin generate_ast -- src/worng` from the spec in\n");
+  out.push_str("// src/tool/GenerateAst.rs, change that instead.\n\n");
+  for import in imports {
+    out.push_str(import);
+    out.push('\n');
+  }
+  out.push('\n');
+
+  define_enum(&mut out, base_name, &nodes);
+  define_visitor(&mut out, base_name, noun, &bases, &nodes, false);
+  define_visitor(&mut out, base_name, noun, &bases, &nodes, true);
+  define_printer(&mut out, base_name, &nodes);
+  define_display(&mut out, base_name);
+
+  fs::write(output_dir.join(file_name), out)
+}
+
+fn define_enum(out: &mut String, base_name: &str, nodes: &[Node]) {
+  out.push_str("#[derive(Debug, Clone)]\n");
+  out.push_str(&format!("pub enum {} {{\n", base_name));
+  for node in nodes {
+    let names: Vec<&str> = node.fields.iter().map(|&(_, name)| name).collect();
+    let types: Vec<&str> = node.fields.iter().map(|&(field_type, _)| field_type).collect();
+    match node.comment {
+      Some(comment) => out.push_str(&format!("  /// {}. {}\n", names.join(", "), comment)),
+      None => out.push_str(&format!("  /// {}.\n", names.join(", "))),
+    }
+    out.push_str(&format!("  {}({}),\n", node.name, types.join(", ")));
+  }
+  out.push_str("}\n\n");
+}
+
+// `mutable` gives the `Mut` trait and walk, visiting `&mut` nodes.
+fn define_visitor(out: &mut String, base_name: &str, noun: &str, bases: &[&str], nodes: &[Node], mutable: bool) {
+  let lower = base_name.to_lowercase();
+  let (suffix, reference, binding) = if mutable { ("_mut", "&mut ", "ref mut") } else { ("", "&", "ref") };
+  let visitor = |base: &str| format!("{}Visitor{}", base, if mutable { "Mut" } else { "" });
+  // one result type for each visitor: `R` for its own, named after their base for the others,
+  // `E` for the `ExprVisitor` a `StmtVisitor` builds on.
+  let params: Vec<String> = bases.iter().enumerate().map(|(i, base)| if i == 0 { "R".to_string() } else { base[..1].to_string() }).collect();
+  // the others break with the same errors, so a walk can give theirs.
+  let bounded: Vec<String> = params
+    .iter()
+    .enumerate()
+    .map(|(i, param)| if i == 0 { format!("{}: VisitResult", param) } else { format!("{}: VisitResult<Break = R::Break>", param) })
+    .collect();
+  let supertraits: Vec<String> = bases.iter().zip(params.iter()).skip(1).map(|(base, param)| format!("{}<{}>", visitor(base), param)).collect();
+
+  if mutable {
+    out.push_str(&format!("/// the `{}Visitor` of passes changing the {}s they visit, like the resolver\n", base_name, noun));
+    out.push_str("/// filling in depths, with the same methods taking `&mut` parts.\n");
+  } else {
+    out.push_str(&format!("/// a pass over {}s giving an `R` for each. `visit_{}` hands each kind to its own method,\n", noun, lower));
+    out.push_str("/// a pass overrides those of the kinds it looks at, the others visit the parts, giving the\n");
+    out.push_str("/// first break one of them gives, or `R::walked()`.\n");
+  }
+  let supertraits = if supertraits.is_empty() { String::new() } else { format!(": {}", supertraits.join(" + ")) };
+  out.push_str(&format!("pub trait {}<{}>{} {{\n", visitor(base_name), bounded.join(", "), supertraits));
+  out.push_str(&format!("  fn visit_{}{}(&mut self, {}: {}{}) -> R {{\n", lower, suffix, lower, reference, base_name));
+  out.push_str(&format!("    match *{} {{\n", lower));
+  for node in nodes {
+    let names: Vec<String> = node.fields.iter().map(|&(_, name)| format!("{} {}", binding, name)).collect();
+    let args: Vec<&str> = node.fields.iter().map(|&(_, name)| name).collect();
+    out.push_str(&format!(
+      "      {}::{}({}) => self.{}({}),\n",
+      base_name,
+      node.name,
+      names.join(", "),
+      method_name(node, base_name, suffix),
+      args.join(", ")
+    ));
+  }
+  out.push_str("    }\n");
+  out.push_str("  }\n");
+
+  for node in nodes {
+    let mut params = Vec::new();
+    let mut visits = Vec::new();
+    for &(field_type, name) in node.fields.iter() {
+      match walk_field(field_type, name, bases, mutable, true) {
+        Some(lines) => {
+          params.push(format!("{}: {}", name, param_type(field_type, mutable)));
+          visits.extend(lines);
+        },
+        None => params.push(format!("_{}: {}", name, param_type(field_type, mutable))),
+      }
+    }
+
+    out.push('\n');
+    out.push_str(&format!("  fn {}(&mut self, {}) -> R {{\n", method_name(node, base_name, suffix), params.join(", ")));
+    for line in visits {
+      out.push_str(&format!("    {}\n", line));
+    }
+    out.push_str("    R::walked()\n");
+    out.push_str("  }\n");
+  }
+  out.push_str("}\n\n");
+
+  out.push_str(&format!("/// visits the nodes directly in `{}`, in source order, giving the first break or `R::walked()`.\n", lower));
+  out.push_str(&format!(
+    "pub fn walk_{}{}<{}, V: {}<{}> + ?Sized>(visitor: &mut V, {}: {}{}) -> R {{\n",
+    lower,
+    suffix,
+    bounded.join(", "),
+    visitor(base_name),
+    params.join(", "),
+    lower,
+    reference,
+    base_name
+  ));
+  out.push_str(&format!("  match *{} {{\n", lower));
+  for node in nodes {
+    let mut patterns = Vec::new();
+    let mut visits = Vec::new();
+    for &(field_type, name) in node.fields.iter() {
+      match walk_field(field_type, name, bases, mutable, false) {
+        Some(lines) => {
+          patterns.push(format!("{} {}", binding, name));
+          visits.extend(lines);
+        },
+        None => patterns.push("_".to_string()),
+      }
+    }
 
-  let mut file = LineWriter::new(file);
+    let pattern = format!("{}::{}({})", base_name, node.name, patterns.join(", "));
+    if visits.is_empty() {
+      out.push_str(&format!("    {} => {{}},\n", pattern));
+    } else {
+      out.push_str(&format!("    {} => {{\n", pattern));
+      for line in visits {
+        out.push_str(&format!("      {}\n", line));
+      }
+      out.push_str("    },\n");
+    }
+  }
+  out.push_str("  }\n");
+  out.push_str("  R::walked()\n");
+  out.push_str("}\n\n");
+}
+
+// `visit_binary_expr` for `Binary` in `Expr`, apart from `visit_var_stmt` for `Var` in `Stmt`.
+fn method_name(node: &Node, base_name: &str, suffix: &str) -> String {
+  format!("visit_{}_{}{}", node.name.to_lowercase(), base_name.to_lowercase(), suffix)
+}
+
+// a field as a method takes it, out of its `Box`: `&[Expr]` for a `Vec<Expr>`, `&mut Vec<Expr>`
+// to change it.
+fn param_type(field_type: &str, mutable: bool) -> String {
+  let (_, base, wrapper) = unwrap_type(field_type);
+  let reference = if mutable { "&mut " } else { "&" };
+  match wrapper {
+    Some("Vec") if !mutable => format!("&[{}]", base),
+    Some(wrapper) => format!("{}{}<{}>", reference, wrapper, base),
+    None => format!("{}{}", reference, base),
+  }
+}
+
+fn define_printer(out: &mut String, base_name: &str, nodes: &[Node]) {
+  let lower = base_name.to_lowercase();
+  out.push_str(&format!("/// `{}` as an S-expression, see `print_program`.\n", lower));
+  out.push_str(&format!("pub fn print_{}({}: &{}) -> String {{\n", lower, lower, base_name));
+  out.push_str(&format!("  match *{} {{\n", lower));
+  for node in nodes {
+    let form = node.form.strip_prefix('(').and_then(|form| form.strip_suffix(')'));
+    let words: Vec<&str> = form.unwrap_or(node.form).split_whitespace().collect();
+    let named: Vec<&str> = words.iter().map(|word| word.trim_matches(|c| c == '[' || c == ']')).collect();
+    let patterns: Vec<String> = node
+      .fields
+      .iter()
+      .map(|&(_, name)| if named.contains(&name) { format!("ref {}", name) } else { "_".to_string() })
+      .collect();
+    let pattern = format!("{}::{}({})", base_name, node.name, patterns.join(", "));
+
+    let form = match form {
+      Some(_) => words,
+      None => {
+        let field = find_field(node, node.form).expect("a bare form is a single field");
+        out.push_str(&format!("    {} => {},\n", pattern, print_part(field, node.form).expect("a bare form is a single field")));
+        continue;
+      },
+    };
+    let head = match find_field(node, form[0]) {
+      Some(_) => format!("&{}.lexeme", form[0]),
+      None => format!("\"{}\"", form[0]),
+    };
+
+    // the parts up to the first that may print as many or none go in the `vec!`, the rest after it.
+    let mut printed = Vec::new();
+    let mut rest = Vec::new();
+    let mut words = form[1..].iter();
+    while let Some(&word) = words.next() {
+      let mut group = vec![word];
+      if let Some(first) = word.strip_prefix('[') {
+        group = vec![first];
+        while !group[group.len() - 1].ends_with(']') {
+          group.push(words.next().expect("a `[` is closed by a `]`"));
+        }
+        let last = group.len() - 1;
+        group[last] = group[last].trim_end_matches(']');
+      }
+
+      let optional = group.iter().find_map(|word| find_field(node, word).filter(|&field| unwrap_type(field).2 == Some("Option")).map(|_| *word));
+      let parts: Vec<String> = group.iter().filter_map(|word| print_part(find_field(node, word).unwrap_or(""), word)).collect();
+      match (optional, find_field(node, word).map(unwrap_type)) {
+        (Some(name), _) => {
+          let derefs = unwrap_type(find_field(node, name).unwrap_or("")).0 + 1;
+          rest.push(format!("if let Some(ref {}) = {}{} {{", name, "*".repeat(derefs), name));
+          rest.extend(parts.iter().map(|part| format!("  parts.push({});", part)));
+          rest.push("}".to_string());
+        },
+        (None, Some((_, base, Some("Vec")))) if base != "Token" => {
+          rest.push(format!("parts.extend({}.iter().map(print_{}));", word, base.to_lowercase()));
+        },
+        _ if rest.is_empty() => printed.extend(parts),
+        _ => rest.extend(parts.iter().map(|part| format!("parts.push({});", part))),
+      }
+    }
+
+    let single_token = form.len() == 2 && find_field(node, form[1]) == Some("Token");
+    if single_token && find_field(node, form[0]).is_none() {
+      // as `format!`, `list` would take a clone.
+      out.push_str(&format!("    {} => format!(\"({} {{}})\", {}.lexeme),\n", pattern, form[0], form[1]));
+    } else if rest.is_empty() {
+      out.push_str(&format!("    {} => list({}, &[{}]),\n", pattern, head, printed.join(", ")));
+    } else {
+      out.push_str(&format!("    {} => {{\n", pattern));
+      if printed.is_empty() {
+        out.push_str("      let mut parts = Vec::new();\n");
+      } else {
+        out.push_str(&format!("      let mut parts = vec![{}];\n", printed.join(", ")));
+      }
+      for line in rest {
+        out.push_str(&format!("      {}\n", line));
+      }
+      out.push_str(&format!("      list({}, &parts)\n", head));
+      out.push_str("    },\n");
+    }
+  }
+  out.push_str("  }\n");
+  out.push_str("}\n\n");
+}
+
+fn find_field<'a>(node: &Node<'a>, name: &str) -> Option<&'a str> {
+  node.fields.iter().find(|&&(_, field)| field == name).map(|&(field_type, _)| field_type)
+}
+
+// a part of a form printing a single string, the word itself when it names no field. none for
+// the fields that print as many.
+fn print_part(field_type: &str, word: &str) -> Option<String> {
+  if field_type.is_empty() {
+    return Some(format!("\"{}\".to_string()", word));
+  }
+  let (_, base, wrapper) = unwrap_type(field_type);
+  match (base, wrapper) {
+    ("Token", None) => Some(format!("{}.lexeme.clone()", word)),
+    ("Token", Some("Vec")) => Some(format!("print_names({})", word)),
+    ("Literal", None) => Some(format!("print_literal({})", word)),
+    (_, Some("Vec")) => None,
+    (base, _) => Some(format!("print_{}({})", base.to_lowercase(), word)),
+  }
+}
 
-  file.write_fmt(format_args!("struct {} {{\n", String::from("Test")))?;
-  file.write_fmt(format_args!(" fn test_function() {{\n"))?;
-  file.write_fmt(format_args!(" }}\n"))?;
-  file.write_fmt(format_args!("}} \n"))?;
+fn define_display(out: &mut String, base_name: &str) {
+  out.push_str(&format!("impl fmt::Display for {} {{\n", base_name));
+  out.push_str("  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {\n");
+  out.push_str(&format!("    f.write_str(&print_{}(self))\n", base_name.to_lowercase()));
+  out.push_str("  }\n");
+  out.push_str("}\n");
+}
 
-  Ok(())
+// the lines visiting a field, none when it holds no node. `method` is for the visitor's own
+// methods, which get the field out of its `Box`.
+fn walk_field(field_type: &str, name: &str, bases: &[&str], mutable: bool, method: bool) -> Option<Vec<String>> {
+  let (boxes, base, wrapper) = unwrap_type(field_type);
+  if !bases.contains(&base) {
+    return None;
+  }
 
-  // PrintWriter writer = new PrintWriter(path, "UTF-8");
+  let (suffix, binding) = if mutable { ("_mut", "ref mut") } else { ("", "ref") };
+  let visit = format!("{}.visit_{}{}", if method { "self" } else { "visitor" }, base.to_lowercase(), suffix);
+  let derefs = if method { 1 } else { boxes + 1 };
+  let (open, node) = match wrapper {
+    Some("Vec") => (Some(format!("for item in {}.iter{}() {{", name, suffix)), "item"),
+    Some(_) => (Some(format!("if let Some({} {}) = {}{} {{", binding, name, "*".repeat(derefs), name)), name),
+    None => (None, name),
+  };
+  let indent = if open.is_some() { "  " } else { "" };
+  let mut lines: Vec<String> = open.into_iter().collect();
+  lines.push(format!("{}if let Some(stop) = {}({}).into_break() {{", indent, visit, node));
+  lines.push(format!("{}  return R::from_break(stop);", indent));
+  lines.push(format!("{}}}", indent));
+  if !indent.is_empty() {
+    lines.push("}".to_string());
+  }
+  Some(lines)
+}
 
-  // writer.println("package com.craftinginterpreters.worng;");
-  // writer.println();
-  // writer.println("import java.util.List;");
-  // writer.println();
-  // writer.println("abstract class " + baseName + " {");
+// `Box<Option<Stmt>>` is 1 box around an `Option` of `Stmt`.
+fn unwrap_type(field_type: &str) -> (usize, &str, Option<&str>) {
+  let mut inner = field_type;
+  let mut boxes = 0;
+  while let Some(rest) = strip_wrapper(inner, "Box") {
+    inner = rest;
+    boxes += 1;
+  }
+  for wrapper in ["Vec", "Option"].iter() {
+    if let Some(rest) = strip_wrapper(inner, wrapper) {
+      return (boxes, rest, Some(wrapper));
+    }
+  }
+  (boxes, inner, None)
+}
 
-  // writer.println("}");
-  // writer.close();
-}
\ No newline at end of file
+fn strip_wrapper<'a>(field_type: &'a str, wrapper: &str) -> Option<&'a str> {
+  field_type.strip_prefix(wrapper)?.strip_prefix('<')?.strip_suffix('>')
+}
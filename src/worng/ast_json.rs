@@ -0,0 +1,148 @@
+use super::expr::Expr;
+use super::json::Json;
+use super::lsp::{object, string};
+use super::statement::Stmt;
+use super::token::{Literal, Token};
+
+/// `worng tokens --json` and `worng ast --json`: the tokens and the syntax tree of a program
+/// for other tools, the layout is described in the README and only grows new keys.
+/// a token is `{"type", "lexeme", "literal", "span"}`, a node `{"kind", ...}` with its parts
+/// under named keys. spans are on the tokens, the nodes get theirs from the tokens in them.
+pub struct AstJson {
+  // char offsets where each line starts.
+  lines: Vec<usize>,
+}
+
+impl AstJson {
+  pub fn new(source: &str) -> AstJson {
+    let mut lines = vec![0];
+    lines.extend(source.chars().enumerate().filter(|&(_, c)| c == '\n').map(|(offset, _)| offset + 1));
+    AstJson { lines }
+  }
+
+  pub fn tokens(&self, tokens: &[Token]) -> Json {
+    Json::Array(tokens.iter().map(|token| self.token(token)).collect())
+  }
+
+  pub fn statements(&self, statements: &[Stmt]) -> Json {
+    Json::Array(statements.iter().map(|statement| self.statement(statement)).collect())
+  }
+
+  /// the literal is the value of a string or number token, null for the others.
+  pub fn token(&self, token: &Token) -> Json {
+    object(vec![
+      ("type", Json::String(format!("{:?}", token.token_type))),
+      ("lexeme", string(&token.lexeme)),
+      ("literal", token.literal.as_ref().map(literal).unwrap_or(Json::Null)),
+      ("span", self.span(token)),
+    ])
+  }
+
+  // lines and columns count from 1, columns and offsets in chars, `end` is past the last char.
+  fn span(&self, token: &Token) -> Json {
+    let line = self.lines.partition_point(|&start| start <= token.offset) - 1;
+    let number = |value: usize| Json::Number(value as f64);
+    object(vec![
+      ("line", number(line + 1)),
+      ("column", number(token.offset - self.lines[line] + 1)),
+      ("start", number(token.offset)),
+      ("end", number(token.offset + token.lexeme.chars().count())),
+    ])
+  }
+
+  pub fn statement(&self, statement: &Stmt) -> Json {
+    let node = |kind: &str, mut members: Vec<(&str, Json)>| {
+      members.insert(0, ("kind", string(kind)));
+      object(members)
+    };
+
+    match *statement {
+      Stmt::Expr(ref expr) => node("Expr", vec![("expression", self.expression(expr))]),
+      Stmt::Print(ref keyword, ref expr) => node("Print", vec![("keyword", self.token(keyword)), ("expression", self.expression(expr))]),
+      Stmt::Var(ref name, ref initializer) => node("Var", vec![("name", self.token(name)), ("initializer", self.expression(initializer))]),
+      Stmt::Block(ref statements) => node("Block", vec![("statements", self.statements(statements))]),
+      Stmt::Class(ref name, ref superclass, ref methods) => node("Class", vec![
+        ("name", self.token(name)),
+        ("superclass", superclass.as_ref().map(|superclass| self.expression(superclass)).unwrap_or(Json::Null)),
+        ("methods", self.statements(methods)),
+      ]),
+      Stmt::If(ref keyword, ref condition, ref then_branch, ref else_branch) => node("If", vec![
+        ("keyword", self.token(keyword)),
+        ("condition", self.expression(condition)),
+        ("then", self.statement(then_branch)),
+        ("else", else_branch.as_ref().as_ref().map(|else_branch| self.statement(else_branch)).unwrap_or(Json::Null)),
+      ]),
+      Stmt::While(ref keyword, ref condition, ref body) => node("While", vec![
+        ("keyword", self.token(keyword)),
+        ("condition", self.expression(condition)),
+        ("body", self.statement(body)),
+      ]),
+      Stmt::Func(ref name, ref params, ref body) => node("Func", vec![
+        ("name", self.token(name)),
+        ("params", self.tokens(params)),
+        ("body", self.statement(body)),
+      ]),
+      Stmt::Return(ref keyword, ref value) => node("Return", vec![("keyword", self.token(keyword)), ("value", self.expression(value))]),
+    }
+  }
+
+  /// `depth` is the resolver's distance to the scope declaring a name, null for globals.
+  pub fn expression(&self, expression: &Expr) -> Json {
+    let node = |kind: &str, mut members: Vec<(&str, Json)>| {
+      members.insert(0, ("kind", string(kind)));
+      object(members)
+    };
+
+    match *expression {
+      Expr::Unary(ref operator, ref right) => node("Unary", vec![("operator", self.token(operator)), ("right", self.expression(right))]),
+      Expr::Literal(ref value) => node("Literal", vec![("value", literal(value))]),
+      Expr::Binary(ref left, ref operator, ref right) => node("Binary", vec![
+        ("left", self.expression(left)),
+        ("operator", self.token(operator)),
+        ("right", self.expression(right)),
+      ]),
+      Expr::Call(ref callee, ref paren, ref arguments) => node("Call", vec![
+        ("callee", self.expression(callee)),
+        ("paren", self.token(paren)),
+        ("arguments", Json::Array(arguments.iter().map(|argument| self.expression(argument)).collect())),
+      ]),
+      Expr::Get(ref object, ref name) => node("Get", vec![("object", self.expression(object)), ("name", self.token(name))]),
+      Expr::Grouping(ref inner) => node("Grouping", vec![("expression", self.expression(inner))]),
+      Expr::Var(ref name, distance) => node("Var", vec![("name", self.token(name)), ("depth", depth(distance))]),
+      Expr::Assign(ref name, ref value, distance) => node("Assign", vec![
+        ("name", self.token(name)),
+        ("value", self.expression(value)),
+        ("depth", depth(distance)),
+      ]),
+      Expr::Set(ref object, ref name, ref value) => node("Set", vec![
+        ("object", self.expression(object)),
+        ("name", self.token(name)),
+        ("value", self.expression(value)),
+      ]),
+      Expr::Logical(ref left, ref operator, ref right) => node("Logical", vec![
+        ("left", self.expression(left)),
+        ("operator", self.token(operator)),
+        ("right", self.expression(right)),
+      ]),
+      Expr::This(ref keyword, distance) => node("This", vec![("keyword", self.token(keyword)), ("depth", depth(distance))]),
+      Expr::Super(ref keyword, ref method, distance) => node("Super", vec![
+        ("keyword", self.token(keyword)),
+        ("method", self.token(method)),
+        ("depth", depth(distance)),
+      ]),
+    }
+  }
+}
+
+fn literal(value: &Literal) -> Json {
+  match *value {
+    Literal::String(ref value) => string(value),
+    Literal::Number(value) => Json::Number(value),
+    Literal::Bool(value) => Json::Bool(value),
+    Literal::Nil => Json::Null,
+  }
+}
+
+fn depth(distance: Option<usize>) -> Json {
+  distance.map(|distance| Json::Number(distance as f64)).unwrap_or(Json::Null)
+}
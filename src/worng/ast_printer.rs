@@ -1,17 +1,234 @@
+use super::error::{ParsingError, WorngError};
 use super::expr::Expr;
-use super::token::{Token,Literal};
+use super::scanner::Scanner;
+use super::statement::{print_stmt, Stmt};
+use super::token::{Literal, Token};
 use super::token_type::TokenType;
 
-pub fn main() {
-  let expression = Expr::Binary(
-    Box::new( Expr::Unary( 
-        Token::new(TokenType::Minus, String::from("-"), None, 1), 
-        Box::new(Expr::Literal(Literal::Number(123 as f64) ))
-      )
-    ), 
-    Token::new(TokenType::Star, String::from("*"), None, 1),
-    Box::new(Expr::Grouping( Box::new(Expr::Literal(Literal::Number(45.67))) ))
-  );
-
-  println!("expr = {}", expression);
-}
\ No newline at end of file
+/// the syntax tree as S-expressions, one statement per line: `(var a (+ 1 (group (* 2 3))))`.
+/// what `Display` of `Expr` and `Stmt` writes and `read_program` reads back, so parser tests can
+/// spell out the tree they expect. resolver depths aren't written, a tree read back has none.
+///
+/// statements: `(expr e)`, `(print e)`, `(var name e)`, `(block s...)`, `(class name methods...)`
+/// or `(class name < superclass methods...)`, `(if e then)`, `(if e then else)`, `(while e body)`
+/// (`for` for the `while` a `for` loop becomes), `(fun name (params...) body)` and `(return e)`.
+/// expressions: literals as in worng and negative numbers as `-2`, names bare, `this`, `(op e)`,
+/// `(op left right)` for the arithmetic, comparison and logical operators, `(group e)`,
+/// `(call callee args...)`, `(get object name)`, `(set object name value)`, `(assign name value)`
+/// and `(super method)`. `print_stmt` and `print_expr` are generated from the forms in the spec in
+/// `src/tool/GenerateAst.rs`, the reader below isn't.
+pub fn print_program(statements: &[Stmt]) -> String {
+  statements.iter().map(|statement| format!("{}\n", print_stmt(statement))).collect()
+}
+
+// a literal as worng writes it, strings in quotes.
+pub(crate) fn print_literal(value: &Literal) -> String {
+  match *value {
+    Literal::String(ref value) => format!("\"{}\"", value),
+    ref value => value.to_string(),
+  }
+}
+
+pub(crate) fn print_names(names: &[Token]) -> String {
+  let names: Vec<&str> = names.iter().map(|name| name.lexeme.as_str()).collect();
+  format!("({})", names.join(" "))
+}
+
+pub(crate) fn list(head: &str, parts: &[String]) -> String {
+  let mut out = format!("({}", head);
+  for part in parts {
+    out.push(' ');
+    out.push_str(part);
+  }
+  out.push(')');
+  out
+}
+
+/// the statements `print_program` writes, read back. the tokens come from scanning `source`,
+/// with its lines and offsets, and a call's paren is the `)` closing its list.
+pub fn read_program(source: &str) -> Result<Vec<Stmt>, Vec<WorngError>> {
+  let mut reader = Reader::new(source)?;
+  let mut statements = Vec::new();
+  while !reader.check(&TokenType::EOF) {
+    statements.push(reader.statement().map_err(|err| vec![WorngError::ParsingError(err)])?);
+  }
+  Ok(statements)
+}
+
+/// one expression as `print_expr` writes it.
+pub fn read_expr(source: &str) -> Result<Expr, Vec<WorngError>> {
+  let mut reader = Reader::new(source)?;
+  let expression = reader.expression().and_then(|expression| {
+    if reader.check(&TokenType::EOF) {
+      Ok(expression)
+    } else {
+      Err(ParsingError::UnexpectedTokenError(reader.peek().clone(), "Expect the end after the expression.".to_string()))
+    }
+  });
+  expression.map_err(|err| vec![WorngError::ParsingError(err)])
+}
+
+fn literal(token: &Token) -> Result<Literal, ParsingError> {
+  token.literal.clone().ok_or_else(|| ParsingError::UnexpectedTokenError(token.clone(), "Expect a literal.".to_string()))
+}
+
+struct Reader {
+  tokens: Vec<Token>,
+  current: usize,
+}
+
+impl Reader {
+  fn new(source: &str) -> Result<Reader, Vec<WorngError>> {
+    let mut scanner = Scanner::new(source);
+    let tokens = scanner.scan_tokens();
+    if !scanner.errors.is_empty() {
+      return Err(scanner.errors.into_iter().map(WorngError::ScanningError).collect());
+    }
+    Ok(Reader { tokens, current: 0 })
+  }
+
+  fn statement(&mut self) -> Result<Stmt, ParsingError> {
+    self.consume(TokenType::LeftParen, "Expect '(' before a statement.")?;
+    let head = self.advance()?;
+    let statement = match head.token_type {
+      TokenType::Identifier if head.lexeme == "expr" => Stmt::Expr(self.expression()?),
+      TokenType::Print => Stmt::Print(head, self.expression()?),
+      TokenType::Var => Stmt::Var(self.name()?, self.expression()?),
+      TokenType::Identifier if head.lexeme == "block" => Stmt::Block(self.statements()?),
+      TokenType::Class => {
+        let name = self.name()?;
+        let mut superclass = None;
+        if self.check(&TokenType::Less) {
+          self.advance()?;
+          superclass = Some(Expr::Var(self.name()?, None));
+        }
+        Stmt::Class(name, superclass, self.statements()?)
+      },
+      TokenType::If => {
+        let condition = self.expression()?;
+        let then_branch = self.statement()?;
+        let else_branch = if self.check(&TokenType::RightParen) { None } else { Some(self.statement()?) };
+        Stmt::If(head, condition, Box::new(then_branch), Box::new(else_branch))
+      },
+      TokenType::While | TokenType::For => {
+        let condition = self.expression()?;
+        Stmt::While(head, condition, Box::new(self.statement()?))
+      },
+      TokenType::Func => {
+        let name = self.name()?;
+        self.consume(TokenType::LeftParen, "Expect '(' before parameters.")?;
+        let mut params = Vec::new();
+        while !self.check(&TokenType::RightParen) {
+          params.push(self.name()?);
+        }
+        self.advance()?;
+        Stmt::Func(name, params, Box::new(self.statement()?))
+      },
+      TokenType::Return => Stmt::Return(head, Box::new(self.expression()?)),
+      _ => return Err(ParsingError::UnexpectedTokenError(head, "Expect a statement.".to_string())),
+    };
+    self.consume(TokenType::RightParen, "Expect ')' after a statement.")?;
+    Ok(statement)
+  }
+
+  // the statements up to the `)` closing the list, which is left for the caller.
+  fn statements(&mut self) -> Result<Vec<Stmt>, ParsingError> {
+    let mut statements = Vec::new();
+    while !self.check(&TokenType::RightParen) {
+      statements.push(self.statement()?);
+    }
+    Ok(statements)
+  }
+
+  fn expression(&mut self) -> Result<Expr, ParsingError> {
+    let token = self.advance()?;
+    match token.token_type {
+      TokenType::Number | TokenType::String => return Ok(Expr::Literal(literal(&token)?)),
+      // a negative number, which the parser only makes with a unary minus but a pass over the
+      // tree may put in a literal.
+      TokenType::Minus if self.check(&TokenType::Number) && self.peek().offset == token.offset + 1 => {
+        return match literal(&self.advance()?)? {
+          Literal::Number(value) => Ok(Expr::Literal(Literal::Number(-value))),
+          _ => Err(ParsingError::UnexpectedTokenError(token, "Expect a number.".to_string())),
+        };
+      },
+      TokenType::True => return Ok(Expr::Literal(Literal::Bool(true))),
+      TokenType::False => return Ok(Expr::Literal(Literal::Bool(false))),
+      TokenType::Nil => return Ok(Expr::Literal(Literal::Nil)),
+      TokenType::Identifier => return Ok(Expr::Var(token, None)),
+      TokenType::This => return Ok(Expr::This(token, None)),
+      TokenType::LeftParen => {},
+      _ => return Err(ParsingError::UnexpectedTokenError(token, "Expect an expression.".to_string())),
+    }
+
+    let head = self.advance()?;
+    let expression = match head.token_type {
+      TokenType::Minus | TokenType::Bang => {
+        let operand = self.expression()?;
+        if self.check(&TokenType::RightParen) {
+          Expr::Unary(head, Box::new(operand))
+        } else if head.token_type == TokenType::Minus {
+          Expr::Binary(Box::new(operand), head, Box::new(self.expression()?))
+        } else {
+          return Err(ParsingError::UnexpectedTokenError(self.peek().clone(), "Expect ')' after the operand.".to_string()));
+        }
+      },
+      TokenType::Plus
+      | TokenType::Slash
+      | TokenType::Star
+      | TokenType::BangEqual
+      | TokenType::EqualEqual
+      | TokenType::Greater
+      | TokenType::GreaterEqual
+      | TokenType::Less
+      | TokenType::LessEqual => Expr::Binary(Box::new(self.expression()?), head, Box::new(self.expression()?)),
+      TokenType::And | TokenType::Or => Expr::Logical(Box::new(self.expression()?), head, Box::new(self.expression()?)),
+      TokenType::Identifier if head.lexeme == "group" => Expr::Grouping(Box::new(self.expression()?)),
+      TokenType::Identifier if head.lexeme == "call" => {
+        let callee = self.expression()?;
+        let mut arguments = Vec::new();
+        while !self.check(&TokenType::RightParen) {
+          arguments.push(self.expression()?);
+        }
+        return Ok(Expr::Call(Box::new(callee), self.advance()?, arguments));
+      },
+      TokenType::Identifier if head.lexeme == "get" => Expr::Get(Box::new(self.expression()?), self.name()?),
+      TokenType::Identifier if head.lexeme == "set" => {
+        Expr::Set(Box::new(self.expression()?), self.name()?, Box::new(self.expression()?))
+      },
+      TokenType::Identifier if head.lexeme == "assign" => Expr::Assign(self.name()?, Box::new(self.expression()?), None),
+      TokenType::Super => Expr::Super(head, self.name()?, None),
+      _ => return Err(ParsingError::UnexpectedTokenError(head, "Expect an operator or a kind of expression.".to_string())),
+    };
+    self.consume(TokenType::RightParen, "Expect ')' after an expression.")?;
+    Ok(expression)
+  }
+
+  fn name(&mut self) -> Result<Token, ParsingError> {
+    self.consume(TokenType::Identifier, "Expect a name.")
+  }
+
+  fn consume(&mut self, token_type: TokenType, message: &str) -> Result<Token, ParsingError> {
+    if self.check(&token_type) {
+      self.advance()
+    } else {
+      Err(ParsingError::UnexpectedTokenError(self.peek().clone(), message.to_string()))
+    }
+  }
+
+  fn check(&self, token_type: &TokenType) -> bool {
+    self.peek().token_type == *token_type
+  }
+
+  fn advance(&mut self) -> Result<Token, ParsingError> {
+    if self.check(&TokenType::EOF) {
+      return Err(ParsingError::UnexpectedEofError);
+    }
+    self.current += 1;
+    Ok(self.tokens[self.current - 1].clone())
+  }
+
+  fn peek(&self) -> &Token {
+    &self.tokens[self.current]
+  }
+}
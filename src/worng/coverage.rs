@@ -0,0 +1,208 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::debugger::statement_lines;
+use super::statement::Stmt;
+use super::expr::Expr;
+use super::token::Token;
+
+/// what a program ran, the interpreter keeps it while `--coverage` is on.
+/// a branch is an `if`, `while` or `for` keyword or an `and` or `or` operator, known by the
+/// offset of its token. the first of its counts is how often the condition was true or the
+/// operator short-circuited, the second how often it was false or evaluated its right side.
+#[derive(Debug, Default)]
+pub struct Hits {
+  pub lines: HashMap<i32, usize>,
+  pub branches: HashMap<usize, [usize; 2]>,
+}
+
+impl Hits {
+  pub fn line(&mut self, line: i32) {
+    *self.lines.entry(line).or_default() += 1;
+  }
+
+  pub fn branch(&mut self, token: &Token, first: bool) {
+    self.branches.entry(token.offset).or_default()[if first { 0 } else { 1 }] += 1;
+  }
+}
+
+/// the two ways a branch can go and how often it went each of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branch {
+  pub line: i32,
+  pub taken: [usize; 2],
+}
+
+/// the coverage of one file: every line with a statement and every branch, run or not.
+#[derive(Debug, Clone)]
+pub struct FileCoverage {
+  pub path: String,
+  pub lines: BTreeMap<i32, usize>,
+  /// in source order.
+  pub branches: Vec<Branch>,
+}
+
+impl FileCoverage {
+  pub fn new(path: &str, statements: &[Stmt], hits: &Hits) -> FileCoverage {
+    let lines = statement_lines(statements)
+      .into_iter()
+      .map(|line| (line, hits.lines.get(&line).cloned().unwrap_or(0)))
+      .collect();
+
+    let mut tokens = Vec::new();
+    for statement in statements {
+      statement_branches(statement, &mut tokens);
+    }
+    tokens.sort_by_key(|token| token.offset);
+    let branches = tokens
+      .into_iter()
+      .map(|token| Branch { line: token.line, taken: hits.branches.get(&token.offset).cloned().unwrap_or([0, 0]) })
+      .collect();
+
+    FileCoverage { path: path.to_string(), lines, branches }
+  }
+
+  pub fn lines_hit(&self) -> usize {
+    self.lines.values().filter(|&&hits| hits > 0).count()
+  }
+
+  /// each branch counts twice, once for each way.
+  pub fn branches_found(&self) -> usize {
+    self.branches.len() * 2
+  }
+
+  pub fn branches_hit(&self) -> usize {
+    self.branches.iter().map(|branch| branch.taken.iter().filter(|&&taken| taken > 0).count()).sum()
+  }
+
+  /// the lines that never ran, runs of them as `first-last`.
+  pub fn missed_lines(&self) -> String {
+    let missed: Vec<i32> = self.lines.iter().filter(|(_, &hits)| hits == 0).map(|(&line, _)| line).collect();
+    let mut ranges: Vec<(i32, i32)> = Vec::new();
+    for line in missed {
+      // lines without statements in between don't end a run.
+      match ranges.last_mut() {
+        Some(range) if self.lines.range(range.1 + 1..line).next().is_none() => range.1 = line,
+        _ => ranges.push((line, line)),
+      }
+    }
+
+    let ranges: Vec<String> = ranges
+      .iter()
+      .map(|&(first, last)| if first == last { first.to_string() } else { format!("{}-{}", first, last) })
+      .collect();
+    ranges.join(", ")
+  }
+
+  /// a table of the lines and branches each file ran, with the lines it didn't.
+  pub fn summary(files: &[FileCoverage]) -> String {
+    let width = files.iter().map(|file| file.path.len()).max().unwrap_or(0).max("total".len());
+    let mut out = format!("{:<width$}  {:>15}  {:>15}  missed lines\n", "file", "lines", "branches", width = width);
+
+    let (mut lines_hit, mut lines_found, mut branches_hit, mut branches_found) = (0, 0, 0, 0);
+    for file in files {
+      let row = format!(
+        "{:<width$}  {:>15}  {:>15}  {}",
+        file.path,
+        ratio(file.lines_hit(), file.lines.len()),
+        ratio(file.branches_hit(), file.branches_found()),
+        file.missed_lines(),
+        width = width
+      );
+      out.push_str(row.trim_end());
+      out.push('\n');
+      lines_hit += file.lines_hit();
+      lines_found += file.lines.len();
+      branches_hit += file.branches_hit();
+      branches_found += file.branches_found();
+    }
+
+    if files.len() > 1 {
+      out.push_str(&format!(
+        "{:<width$}  {:>15}  {:>15}\n",
+        "total",
+        ratio(lines_hit, lines_found),
+        ratio(branches_hit, branches_found),
+        width = width
+      ));
+    }
+    out
+  }
+
+  /// the lcov tracefile format `genhtml` and coverage viewers read. the block of a branch is its
+  /// index in the file, a branch never reached is `-`.
+  pub fn lcov(files: &[FileCoverage]) -> String {
+    let mut out = String::new();
+    for file in files {
+      out.push_str(&format!("TN:\nSF:{}\n", file.path));
+      for (block, branch) in file.branches.iter().enumerate() {
+        let reached = branch.taken.iter().any(|&taken| taken > 0);
+        for (way, &taken) in branch.taken.iter().enumerate() {
+          let taken = if reached { taken.to_string() } else { "-".to_string() };
+          out.push_str(&format!("BRDA:{},{},{},{}\n", branch.line, block, way, taken));
+        }
+      }
+      out.push_str(&format!("BRF:{}\nBRH:{}\n", file.branches_found(), file.branches_hit()));
+      for (line, hits) in file.lines.iter() {
+        out.push_str(&format!("DA:{},{}\n", line, hits));
+      }
+      out.push_str(&format!("LF:{}\nLH:{}\nend_of_record\n", file.lines.len(), file.lines_hit()));
+    }
+    out
+  }
+}
+
+fn statement_branches<'a>(statement: &'a Stmt, tokens: &mut Vec<&'a Token>) {
+  match *statement {
+    Stmt::Expr(ref expr) | Stmt::Print(_, ref expr) | Stmt::Var(_, ref expr) => expression_branches(expr, tokens),
+    Stmt::Return(_, ref expr) => expression_branches(expr, tokens),
+    Stmt::Block(ref statements) | Stmt::Class(_, _, ref statements) => {
+      for statement in statements {
+        statement_branches(statement, tokens);
+      }
+    },
+    Stmt::If(ref keyword, ref condition, ref then_branch, ref else_branch) => {
+      tokens.push(keyword);
+      expression_branches(condition, tokens);
+      statement_branches(then_branch, tokens);
+      if let Some(ref else_branch) = **else_branch {
+        statement_branches(else_branch, tokens);
+      }
+    },
+    Stmt::While(ref keyword, ref condition, ref body) => {
+      tokens.push(keyword);
+      expression_branches(condition, tokens);
+      statement_branches(body, tokens);
+    },
+    Stmt::Func(_, _, ref body) => statement_branches(body, tokens),
+  }
+}
+
+fn expression_branches<'a>(expression: &'a Expr, tokens: &mut Vec<&'a Token>) {
+  match *expression {
+    Expr::Logical(ref left, ref operator, ref right) => {
+      tokens.push(operator);
+      expression_branches(left, tokens);
+      expression_branches(right, tokens);
+    },
+    Expr::Binary(ref left, _, ref right) | Expr::Set(ref left, _, ref right) => {
+      expression_branches(left, tokens);
+      expression_branches(right, tokens);
+    },
+    Expr::Unary(_, ref inner) | Expr::Grouping(ref inner) | Expr::Get(ref inner, _) | Expr::Assign(_, ref inner, _) => {
+      expression_branches(inner, tokens)
+    },
+    Expr::Call(ref callee, _, ref arguments) => {
+      expression_branches(callee, tokens);
+      for argument in arguments {
+        expression_branches(argument, tokens);
+      }
+    },
+    Expr::Literal(_) | Expr::Var(_, _) | Expr::This(_, _) | Expr::Super(_, _, _) => {},
+  }
+}
+
+// eg. `12/14  85.7%`, `0/0 100.0%` when there is nothing to cover.
+fn ratio(hit: usize, found: usize) -> String {
+  let percent = if found == 0 { 100.0 } else { hit as f64 * 100.0 / found as f64 };
+  format!("{}/{} {:>5.1}%", hit, found, percent)
+}
@@ -0,0 +1,441 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, BufRead, Cursor, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+use super::json::Json;
+use super::lsp::{object, string, read_message, write_message};
+use super::debugger::{self, DebugHook, Frame, Step, Stepper};
+use super::interpreter::Interpreter;
+use super::environment::Environment;
+use super::native_fs::FsAccess;
+use super::driver::compile;
+use super::worng_value::WorngValue;
+use super::statement::Stmt;
+use super::error::{WorngError, RuntimeError};
+
+// a worng program has one thread.
+const THREAD_ID: f64 = 1.0;
+
+/// `worng dap`: a Debug Adapter Protocol server on `reader` and `writer`, for editors.
+/// it runs the program given to `launch` once the client is done with `configurationDone`,
+/// pausing it at breakpoints and after steps. the program's output goes to the client as
+/// `output` events and it reads an empty stdin. a program that is running can't be paused,
+/// and a runtime error ends it like it ends `worng run`.
+pub fn serve(reader: &mut dyn BufRead, writer: &mut dyn Write, fs_access: FsAccess) -> io::Result<()> {
+  let client = Rc::new(RefCell::new(Client { reader, writer, seq: 0.0, disconnected: false }));
+  let mut program: Option<Program> = None;
+  let mut breakpoints: Vec<i32> = Vec::new();
+
+  loop {
+    let request = match client.borrow_mut().read()? {
+      Some(request) => request,
+      None => return Ok(()),
+    };
+    let arguments = request.get("arguments").cloned().unwrap_or(Json::Null);
+
+    let result = match command(&request) {
+      "initialize" => {
+        client.borrow_mut().respond(&request, Ok(capabilities()))?;
+        client.borrow_mut().event("initialized", Json::Object(Vec::new()))?;
+        continue;
+      },
+      "launch" => Program::launch(&arguments).map(|launched| {
+        program = Some(launched);
+        Json::Null
+      }),
+      "setBreakpoints" => {
+        breakpoints = requested_lines(&arguments);
+        Ok(breakpoints_body(&breakpoints, program.as_ref().map(|program| &program.lines)))
+      },
+      "setExceptionBreakpoints" => Ok(Json::Null),
+      "threads" => Ok(threads()),
+      "configurationDone" => {
+        client.borrow_mut().respond(&request, Ok(Json::Null))?;
+        if let Some(program) = program.take() {
+          run(&client, program, &breakpoints, fs_access.clone())?;
+        }
+        if client.borrow().disconnected {
+          return Ok(());
+        }
+        continue;
+      },
+      "disconnect" => {
+        client.borrow_mut().respond(&request, Ok(Json::Null))?;
+        return Ok(());
+      },
+      command => Err(format!("'{}' needs a paused program", command)),
+    };
+    client.borrow_mut().respond(&request, result)?;
+  }
+}
+
+// the two ends of the connection, the program's output goes through it as well.
+struct Client<'a> {
+  reader: &'a mut dyn BufRead,
+  writer: &'a mut dyn Write,
+  seq: f64,
+  disconnected: bool,
+}
+
+impl<'a> Client<'a> {
+  // `None` at the end of the input, messages that aren't JSON are skipped.
+  fn read(&mut self) -> io::Result<Option<Json>> {
+    while let Some(body) = read_message(self.reader)? {
+      if let Ok(message) = Json::parse(&body) {
+        return Ok(Some(message));
+      }
+    }
+    Ok(None)
+  }
+
+  fn send(&mut self, kind: &str, members: Vec<(&str, Json)>) -> io::Result<()> {
+    self.seq += 1.0;
+    let mut message = vec![("seq", Json::Number(self.seq)), ("type", string(kind))];
+    message.extend(members);
+    write_message(self.writer, &object(message))
+  }
+
+  // a failed request gets the error as its message.
+  fn respond(&mut self, request: &Json, result: Result<Json, String>) -> io::Result<()> {
+    let mut members = vec![
+      ("request_seq", request.get("seq").cloned().unwrap_or(Json::Null)),
+      ("command", string(command(request))),
+      ("success", Json::Bool(result.is_ok())),
+    ];
+    match result {
+      Ok(Json::Null) => {},
+      Ok(body) => members.push(("body", body)),
+      Err(message) => members.push(("message", Json::String(message))),
+    }
+    self.send("response", members)
+  }
+
+  fn event(&mut self, event: &str, body: Json) -> io::Result<()> {
+    self.send("event", vec![("event", string(event)), ("body", body)])
+  }
+
+  fn output(&mut self, category: &str, text: &str) -> io::Result<()> {
+    self.event("output", object(vec![("category", string(category)), ("output", string(text))]))
+  }
+}
+
+// what the program prints, one `output` event per write.
+struct Output<'a, 'b> {
+  client: Rc<RefCell<Client<'b>>>,
+  category: &'a str,
+}
+
+impl<'a, 'b> Write for Output<'a, 'b> {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    self.client.borrow_mut().output(self.category, &String::from_utf8_lossy(buf))?;
+    Ok(buf.len())
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+// a `launch` request's program, compiled.
+struct Program {
+  path: String,
+  statements: Vec<Stmt>,
+  lines: BTreeSet<i32>,
+  args: Vec<String>,
+  stop_on_entry: bool,
+}
+
+impl Program {
+  fn launch(arguments: &Json) -> Result<Program, String> {
+    let path = arguments.get("program").and_then(Json::as_str).ok_or("'launch' needs a 'program'")?;
+    let source = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    let statements = compile(&source).map_err(|errors| {
+      let messages: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+      messages.join("\n")
+    })?;
+    let args = match arguments.get("args") {
+      Some(Json::Array(args)) => args.iter().filter_map(Json::as_str).map(String::from).collect(),
+      _ => Vec::new(),
+    };
+
+    Ok(Program {
+      path: path.to_string(),
+      lines: debugger::statement_lines(&statements),
+      statements,
+      args,
+      stop_on_entry: arguments.get("stopOnEntry") == Some(&Json::Bool(true)),
+    })
+  }
+}
+
+fn run(client: &Rc<RefCell<Client>>, program: Program, breakpoints: &[i32], fs_access: FsAccess) -> io::Result<()> {
+  let mut output = Output { client: client.clone(), category: "stdout" };
+  let mut input = Cursor::new(Vec::new());
+  let writer = Rc::new(RefCell::new(&mut output as &mut dyn Write));
+  let mut stepper = Stepper::new(program.stop_on_entry);
+  stepper.breakpoints = breakpoints.iter().filter_map(|&line| debugger::breakpoint_line(&program.lines, line)).collect();
+
+  let mut interpreter = Interpreter::new(writer, Rc::new(RefCell::new(&mut input as &mut dyn BufRead)));
+  interpreter.args = program.args;
+  interpreter.fs_access = fs_access;
+  interpreter.debug_hook = Some(Box::new(Adapter {
+    client: client.clone(),
+    stepper,
+    lines: program.lines,
+    path: program.path,
+    frames: Vec::new(),
+    references: Vec::new(),
+  }));
+
+  let exit_code = match interpreter.interpret(program.statements) {
+    None => 0,
+    Some(RuntimeError::Exit(code)) => code,
+    Some(err) => {
+      client.borrow_mut().output("stderr", &format!("{}\n", WorngError::RuntimeError(err)))?;
+      70
+    },
+  };
+
+  let mut client = client.borrow_mut();
+  if client.disconnected {
+    return Ok(());
+  }
+  client.event("exited", object(vec![("exitCode", Json::Number(exit_code as f64))]))?;
+  client.event("terminated", Json::Object(Vec::new()))
+}
+
+// what a `variablesReference` stands for, the reference is the index + 1.
+enum Reference {
+  Scope(Rc<RefCell<Environment>>),
+  Value(WorngValue),
+}
+
+// pauses the program and answers the client until it resumes.
+struct Adapter<'a> {
+  client: Rc<RefCell<Client<'a>>>,
+  stepper: Stepper,
+  lines: BTreeSet<i32>,
+  path: String,
+  // of the pause, frame ids are the index + 1.
+  frames: Vec<Frame>,
+  references: Vec<Reference>,
+}
+
+impl<'a> DebugHook for Adapter<'a> {
+  fn before(&mut self, interpreter: &mut Interpreter, statement: &Stmt) -> Result<(), RuntimeError> {
+    let line = match debugger::statement_line(statement) {
+      Some(line) => line,
+      None => return Ok(()),
+    };
+    let reason = match self.stepper.pause(line, interpreter.calls().len()) {
+      Some(reason) => reason,
+      None => return Ok(()),
+    };
+
+    self.frames = debugger::frames(interpreter, line);
+    let result = self.paused(interpreter, reason.name());
+    self.frames.clear();
+    self.references.clear();
+
+    match result {
+      Ok(true) => Ok(()),
+      // the client is gone, so is the program.
+      Ok(false) => {
+        self.client.borrow_mut().disconnected = true;
+        Err(RuntimeError::Exit(0))
+      },
+      Err(err) => Err(RuntimeError::InternalError(format!("debug adapter: {}", err))),
+    }
+  }
+}
+
+impl<'a> Adapter<'a> {
+  // answers requests until one resumes the program, false when the client disconnects.
+  fn paused(&mut self, interpreter: &mut Interpreter, reason: &str) -> io::Result<bool> {
+    let stopped = object(vec![("reason", string(reason)), ("threadId", Json::Number(THREAD_ID)), ("allThreadsStopped", Json::Bool(true))]);
+    self.client.borrow_mut().event("stopped", stopped)?;
+
+    loop {
+      // not borrowed while the request is handled, evaluating may print.
+      let request = self.client.borrow_mut().read()?;
+      let request = match request {
+        Some(request) => request,
+        None => return Ok(false),
+      };
+      let arguments = request.get("arguments").cloned().unwrap_or(Json::Null);
+
+      let step = match command(&request) {
+        "continue" => Some(Step::Continue),
+        "next" => Some(Step::Over),
+        "stepIn" => Some(Step::In),
+        "stepOut" => Some(Step::Out),
+        _ => None,
+      };
+      if let Some(step) = step {
+        self.stepper.resume(step);
+        let body = if step == Step::Continue { object(vec![("allThreadsContinued", Json::Bool(true))]) } else { Json::Null };
+        self.client.borrow_mut().respond(&request, Ok(body))?;
+        return Ok(true);
+      }
+
+      let result = match command(&request) {
+        "disconnect" => {
+          self.client.borrow_mut().respond(&request, Ok(Json::Null))?;
+          return Ok(false);
+        },
+        "threads" => Ok(threads()),
+        "pause" | "setExceptionBreakpoints" => Ok(Json::Null),
+        "setBreakpoints" => {
+          let requested = requested_lines(&arguments);
+          self.stepper.breakpoints = requested.iter().filter_map(|&line| debugger::breakpoint_line(&self.lines, line)).collect();
+          Ok(breakpoints_body(&requested, Some(&self.lines)))
+        },
+        "stackTrace" => Ok(self.stack_trace()),
+        "scopes" => self.scopes(&arguments),
+        "variables" => self.variables(&arguments),
+        "evaluate" => self.evaluate(interpreter, &arguments),
+        command => Err(format!("'{}' isn't supported", command)),
+      };
+      self.client.borrow_mut().respond(&request, result)?;
+    }
+  }
+
+  fn stack_trace(&self) -> Json {
+    let name = Path::new(&self.path).file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let frames = self
+      .frames
+      .iter()
+      .enumerate()
+      .map(|(index, frame)| {
+        object(vec![
+          ("id", Json::Number((index + 1) as f64)),
+          ("name", string(&frame.name)),
+          ("line", Json::Number(frame.line as f64)),
+          ("column", Json::Number(1.0)),
+          ("source", object(vec![("name", string(&name)), ("path", string(&self.path))])),
+        ])
+      })
+      .collect();
+    object(vec![("stackFrames", Json::Array(frames)), ("totalFrames", Json::Number(self.frames.len() as f64))])
+  }
+
+  fn frame(&self, arguments: &Json) -> Result<&Frame, String> {
+    match arguments.get("frameId").and_then(Json::as_f64) {
+      Some(id) => self.frames.get((id as usize).wrapping_sub(1)).ok_or_else(|| format!("no frame {}", id)),
+      None => Ok(&self.frames[0]),
+    }
+  }
+
+  fn reference(&mut self, reference: Reference) -> f64 {
+    self.references.push(reference);
+    self.references.len() as f64
+  }
+
+  fn scopes(&mut self, arguments: &Json) -> Result<Json, String> {
+    let scopes = debugger::scopes(&self.frame(arguments)?.environment);
+    let count = scopes.len();
+    let scopes = scopes
+      .into_iter()
+      .enumerate()
+      .map(|(index, scope)| {
+        object(vec![
+          ("name", string(&debugger::scope_name(index, count))),
+          ("variablesReference", Json::Number(self.reference(Reference::Scope(scope)))),
+          ("expensive", Json::Bool(index + 1 == count)),
+        ])
+      })
+      .collect();
+    Ok(object(vec![("scopes", Json::Array(scopes))]))
+  }
+
+  fn variables(&mut self, arguments: &Json) -> Result<Json, String> {
+    let id = arguments.get("variablesReference").and_then(Json::as_f64).unwrap_or(0.0);
+    let variables = match self.references.get((id as usize).wrapping_sub(1)) {
+      Some(Reference::Scope(scope)) => debugger::variables(scope),
+      Some(Reference::Value(value)) => children(value),
+      None => return Err(format!("no variables {}", id)),
+    };
+
+    let variables = variables
+      .into_iter()
+      .map(|(name, value)| {
+        let mut members = vec![("name", Json::String(name))];
+        members.extend(self.value("value", value));
+        object(members)
+      })
+      .collect();
+    Ok(object(vec![("variables", Json::Array(variables))]))
+  }
+
+  fn evaluate(&mut self, interpreter: &mut Interpreter, arguments: &Json) -> Result<Json, String> {
+    let expression = arguments.get("expression").and_then(Json::as_str).unwrap_or("");
+    let value = debugger::evaluate(interpreter, self.frame(arguments)?, expression)?;
+    Ok(object(self.value("result", value)))
+  }
+
+  // the value under `key`, its `type` and `variablesReference`, lists, maps and instances can be expanded.
+  fn value(&mut self, key: &'static str, value: WorngValue) -> Vec<(&'static str, Json)> {
+    let described = debugger::describe(&value);
+    let type_name = value.type_name();
+    let reference = match value {
+      WorngValue::List(_) | WorngValue::Map(_) | WorngValue::Instance(_) => self.reference(Reference::Value(value)),
+      _ => 0.0,
+    };
+    vec![(key, Json::String(described)), ("type", string(type_name)), ("variablesReference", Json::Number(reference))]
+  }
+}
+
+fn children(value: &WorngValue) -> Vec<(String, WorngValue)> {
+  match *value {
+    WorngValue::List(ref list) => list.borrow().iter().enumerate().map(|(index, item)| (format!("[{}]", index), item.clone())).collect(),
+    WorngValue::Map(ref map) => map.borrow().iter().map(|(key, value)| (Json::String(key.clone()).to_string(), value.clone())).collect(),
+    WorngValue::Instance(ref instance) => {
+      let instance = instance.borrow();
+      let mut names = instance.field_names();
+      names.sort();
+      names.into_iter().filter_map(|name| instance.field(&name).map(|value| (name, value))).collect()
+    },
+    _ => Vec::new(),
+  }
+}
+
+fn command(request: &Json) -> &str {
+  request.get("command").and_then(Json::as_str).unwrap_or("")
+}
+
+fn capabilities() -> Json {
+  object(vec![("supportsConfigurationDoneRequest", Json::Bool(true)), ("supportsEvaluateForHovers", Json::Bool(true))])
+}
+
+fn threads() -> Json {
+  object(vec![("threads", Json::Array(vec![object(vec![("id", Json::Number(THREAD_ID)), ("name", string("main"))])]))])
+}
+
+// the lines of a `setBreakpoints` request.
+fn requested_lines(arguments: &Json) -> Vec<i32> {
+  match arguments.get("breakpoints") {
+    Some(Json::Array(breakpoints)) => {
+      breakpoints.iter().filter_map(|breakpoint| breakpoint.get("line").and_then(Json::as_f64)).map(|line| line as i32).collect()
+    },
+    _ => Vec::new(),
+  }
+}
+
+// a breakpoint goes on the next line with a statement, one with none after it isn't verified.
+// before `launch` the lines aren't known and every breakpoint is taken as it is.
+fn breakpoints_body(requested: &[i32], lines: Option<&BTreeSet<i32>>) -> Json {
+  let breakpoints = requested
+    .iter()
+    .map(|&line| {
+      let moved = match lines {
+        Some(lines) => debugger::breakpoint_line(lines, line),
+        None => Some(line),
+      };
+      object(vec![("verified", Json::Bool(moved.is_some())), ("line", Json::Number(moved.unwrap_or(line) as f64))])
+    })
+    .collect();
+  object(vec![("breakpoints", Json::Array(breakpoints))])
+}
@@ -0,0 +1,503 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+
+use super::json::Json;
+use super::scanner::Scanner;
+use super::parser::Parser;
+use super::resolver::first_token;
+use super::interpreter::Interpreter;
+use super::environment::Environment;
+use super::worng_value::WorngValue;
+use super::worng_function::WorngFunction;
+use super::worng_class::WorngClass;
+use super::statement::Stmt;
+use super::expr::Expr;
+use super::error::{WorngError, RuntimeError};
+
+// lists, maps and instances nested deeper than this are shown as `...`, they may contain themselves.
+const MAX_NESTING: usize = 3;
+
+const HELP: &str = "\
+break LINE, b LINE    pause before the first statement on or after LINE
+delete LINE, d LINE   remove the breakpoint on LINE
+breakpoints           list the breakpoints
+continue, c           run until the next breakpoint
+step, s               run until the next line, going into calls
+next, n               run until the next line of this function
+out, o                run until this function returns
+backtrace, bt         list the calls leading here
+frame N, f N          look at the Nth call of the backtrace
+vars, v               the variables of each scope of the frame
+print EXPR, p EXPR    evaluate EXPR in the frame
+list, l               the source around the frame's line
+quit, q               stop the program
+";
+
+/// what `Interpreter::interpret_statement` asks before it runs a statement.
+/// the hook can pause the program in there, an error stops it.
+pub trait DebugHook {
+  fn before(&mut self, interpreter: &mut Interpreter, statement: &Stmt) -> Result<(), RuntimeError>;
+}
+
+/// how a paused program goes on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+  Continue,
+  In,
+  Over,
+  Out,
+}
+
+/// why the program paused.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reason {
+  Entry,
+  Breakpoint,
+  Step,
+}
+
+impl Reason {
+  /// as the Debug Adapter Protocol names it.
+  pub fn name(self) -> &'static str {
+    match self {
+      Reason::Entry => "entry",
+      Reason::Breakpoint => "breakpoint",
+      Reason::Step => "step",
+    }
+  }
+}
+
+/// decides where a program pauses, shared by `worng debug` and `worng dap`.
+/// it works in lines: statements on the line the program paused at don't pause it again,
+/// and a breakpoint pauses once each time the program gets to its line.
+pub struct Stepper {
+  pub breakpoints: BTreeSet<i32>,
+  step: Step,
+  entry: bool,
+  // line and number of calls where the program paused last.
+  from: (i32, usize),
+  // the same for the last statement.
+  last: Option<(i32, usize)>,
+}
+
+impl Stepper {
+  pub fn new(stop_on_entry: bool) -> Stepper {
+    Stepper {
+      breakpoints: BTreeSet::new(),
+      step: if stop_on_entry { Step::In } else { Step::Continue },
+      entry: stop_on_entry,
+      from: (0, 0),
+      last: None,
+    }
+  }
+
+  pub fn resume(&mut self, step: Step) {
+    self.step = step;
+  }
+
+  /// whether to pause before a statement on `line` run `depth` calls deep.
+  pub fn pause(&mut self, line: i32, depth: usize) -> Option<Reason> {
+    let arrived = self.last != Some((line, depth));
+    self.last = Some((line, depth));
+
+    let (from_line, from_depth) = self.from;
+    let stepped = match self.step {
+      Step::Continue => false,
+      Step::In => line != from_line || depth != from_depth,
+      Step::Over => depth < from_depth || (depth == from_depth && line != from_line),
+      Step::Out => depth < from_depth,
+    };
+
+    let reason = if arrived && self.breakpoints.contains(&line) {
+      Reason::Breakpoint
+    } else if stepped && self.entry {
+      Reason::Entry
+    } else if stepped {
+      Reason::Step
+    } else {
+      return None;
+    };
+
+    self.entry = false;
+    self.step = Step::Continue;
+    self.from = (line, depth);
+    Some(reason)
+  }
+}
+
+/// the line a statement pauses at, blocks don't pause but their statements do.
+pub fn statement_line(statement: &Stmt) -> Option<i32> {
+  match *statement {
+    Stmt::Block(_) => None,
+    _ => first_token(statement).map(|token| token.line),
+  }
+}
+
+/// every line a statement of the program pauses at.
+pub fn statement_lines(statements: &[Stmt]) -> BTreeSet<i32> {
+  let mut lines = BTreeSet::new();
+  for statement in statements {
+    add_lines(statement, &mut lines);
+  }
+  lines
+}
+
+fn add_lines(statement: &Stmt, lines: &mut BTreeSet<i32>) {
+  lines.extend(statement_line(statement));
+  match *statement {
+    Stmt::Block(ref statements) | Stmt::Class(_, _, ref statements) => {
+      for statement in statements {
+        add_lines(statement, lines);
+      }
+    },
+    Stmt::If(_, _, ref then_branch, ref else_branch) => {
+      add_lines(then_branch, lines);
+      if let Some(ref else_branch) = **else_branch {
+        add_lines(else_branch, lines);
+      }
+    },
+    Stmt::While(_, _, ref body) | Stmt::Func(_, _, ref body) => add_lines(body, lines),
+    Stmt::Expr(_) | Stmt::Print(_, _) | Stmt::Var(_, _) | Stmt::Return(_, _) => {},
+  }
+}
+
+/// a breakpoint asked for on `line` is moved to the next line with a statement.
+pub fn breakpoint_line(lines: &BTreeSet<i32>, line: i32) -> Option<i32> {
+  lines.range(line..).next().cloned()
+}
+
+/// a function being run or the script itself, and where in it the program is.
+pub struct Frame {
+  pub name: String,
+  pub line: i32,
+  pub environment: Rc<RefCell<Environment>>,
+}
+
+/// the frames of a program paused at `line`, innermost first.
+pub fn frames(interpreter: &Interpreter, line: i32) -> Vec<Frame> {
+  let mut frames = Vec::new();
+  let mut line = line;
+  let mut environment = interpreter.environment();
+  for call in interpreter.calls().iter().rev() {
+    frames.push(Frame { name: call.name.clone(), line, environment });
+    line = call.line;
+    environment = call.environment.clone();
+  }
+  frames.push(Frame { name: "<script>".to_string(), line, environment });
+  frames
+}
+
+/// the scopes seen from `environment`, walking `enclosing`: innermost first, the globals last.
+pub fn scopes(environment: &Rc<RefCell<Environment>>) -> Vec<Rc<RefCell<Environment>>> {
+  let mut scopes = vec![environment.clone()];
+  while let Some(enclosing) = scopes.last().and_then(|scope| scope.borrow().enclosing.clone()) {
+    scopes.push(enclosing);
+  }
+  scopes
+}
+
+/// "locals", "enclosing #1" and so on, "globals" for the last one.
+pub fn scope_name(index: usize, count: usize) -> String {
+  if index + 1 == count {
+    "globals".to_string()
+  } else if index == 0 {
+    "locals".to_string()
+  } else {
+    format!("enclosing #{}", index)
+  }
+}
+
+/// the variables of `scope`, without the natives when it's the globals.
+pub fn variables(scope: &Rc<RefCell<Environment>>) -> Vec<(String, WorngValue)> {
+  let scope = scope.borrow();
+  let globals = scope.enclosing.is_none();
+  scope.variables().into_iter().filter(|(_, value)| !(globals && is_native(value))).collect()
+}
+
+fn is_native(value: &WorngValue) -> bool {
+  match *value {
+    WorngValue::Func(ref function) => !function.as_any().is::<WorngFunction>() && !function.as_any().is::<WorngClass>(),
+    WorngValue::Module(_) => true,
+    _ => false,
+  }
+}
+
+/// a value as the debugger shows it, unlike `print` it quotes strings.
+pub fn describe(value: &WorngValue) -> String {
+  describe_nested(value, 0)
+}
+
+fn describe_nested(value: &WorngValue, nesting: usize) -> String {
+  match *value {
+    WorngValue::String(ref string) => Json::String(string.clone()).to_string(),
+    WorngValue::Func(ref function) => function.func_to_string(),
+    WorngValue::Class(ref class) => format!("<class {}>", class),
+    WorngValue::List(_) | WorngValue::Map(_) | WorngValue::Instance(_) if nesting == MAX_NESTING => "...".to_string(),
+    WorngValue::List(ref list) => {
+      let items: Vec<String> = list.borrow().iter().map(|item| describe_nested(item, nesting + 1)).collect();
+      format!("[{}]", items.join(", "))
+    },
+    WorngValue::Map(ref map) => {
+      let entries: Vec<String> = map
+        .borrow()
+        .iter()
+        .map(|(key, value)| format!("{}: {}", Json::String(key.clone()), describe_nested(value, nesting + 1)))
+        .collect();
+      format!("{{{}}}", entries.join(", "))
+    },
+    WorngValue::Instance(ref instance) => {
+      let instance = instance.borrow();
+      let mut names = instance.field_names();
+      names.sort();
+      let fields: Vec<String> = names
+        .iter()
+        .map(|name| format!("{}: {}", name, describe_nested(&instance.field(name).unwrap_or(WorngValue::Nil), nesting + 1)))
+        .collect();
+      format!("{} {{{}}}", instance.class(), fields.join(", "))
+    },
+    _ => value.to_string(),
+  }
+}
+
+/// evaluates the expression `source` in the scope of `frame`.
+/// functions it calls run to the end, breakpoints don't pause them.
+pub fn evaluate(interpreter: &mut Interpreter, frame: &Frame, source: &str) -> Result<WorngValue, String> {
+  let source = source.trim().trim_end_matches(';');
+  let mut scanner = Scanner::new(&format!("{};", source));
+  let tokens = scanner.scan_tokens();
+  if let Some(err) = scanner.errors.into_iter().next() {
+    return Err(WorngError::ScanningError(err).to_string());
+  }
+
+  let mut statements = Parser::new(tokens).parse().map_err(|errors| {
+    let messages: Vec<String> = errors.into_iter().map(|err| WorngError::ParsingError(err).to_string()).collect();
+    messages.join("\n")
+  })?;
+
+  let mut expression = match (statements.pop(), statements.is_empty()) {
+    (Some(Stmt::Expr(expression)), true) => expression,
+    _ => return Err(format!("'{}' is not an expression.", source)),
+  };
+  bind(&mut expression, &frame.environment);
+
+  interpreter
+    .evaluate_in(&expression, frame.environment.clone())
+    .map_err(|err| WorngError::RuntimeError(err).to_string())
+}
+
+// the resolver knows the scopes of a whole program, not those of a paused one, so the names
+// get distance 0: the lookup starts in the paused scope and goes out through `enclosing`.
+// only `super` has to be found at its real distance.
+fn bind(expression: &mut Expr, environment: &Rc<RefCell<Environment>>) {
+  match *expression {
+    Expr::Var(_, ref mut distance) | Expr::This(_, ref mut distance) => *distance = Some(0),
+    Expr::Assign(_, ref mut value, ref mut distance) => {
+      bind(value, environment);
+      *distance = Some(0);
+    },
+    Expr::Super(_, _, ref mut distance) => {
+      *distance = scopes(environment).iter().position(|scope| scope.borrow().defines("super"));
+    },
+    Expr::Unary(_, ref mut inner) | Expr::Grouping(ref mut inner) | Expr::Get(ref mut inner, _) => bind(inner, environment),
+    Expr::Binary(ref mut left, _, ref mut right)
+    | Expr::Logical(ref mut left, _, ref mut right)
+    | Expr::Set(ref mut left, _, ref mut right) => {
+      bind(left, environment);
+      bind(right, environment);
+    },
+    Expr::Call(ref mut callee, _, ref mut arguments) => {
+      bind(callee, environment);
+      for argument in arguments {
+        bind(argument, environment);
+      }
+    },
+    Expr::Literal(_) => {},
+  }
+}
+
+/// `worng debug`: pauses before the first statement, at breakpoints and after steps, and
+/// reads commands from `reader` until one resumes the program. it shares `reader` and
+/// `writer` with the program. once the commands run out the program runs to its end.
+pub struct Terminal<'a> {
+  source: Vec<String>,
+  lines: BTreeSet<i32>,
+  reader: Rc<RefCell<&'a mut dyn BufRead>>,
+  writer: Rc<RefCell<&'a mut dyn Write>>,
+  stepper: Stepper,
+}
+
+impl<'a> Terminal<'a> {
+  pub fn new(source: &str, statements: &[Stmt], reader: Rc<RefCell<&'a mut dyn BufRead>>, writer: Rc<RefCell<&'a mut dyn Write>>) -> Terminal<'a> {
+    Terminal {
+      source: source.lines().map(String::from).collect(),
+      lines: statement_lines(statements),
+      reader,
+      writer,
+      stepper: Stepper::new(true),
+    }
+  }
+
+  fn write(&self, text: &str) -> io::Result<()> {
+    let mut writer = self.writer.borrow_mut();
+    writer.write_all(text.as_bytes())?;
+    writer.flush()
+  }
+
+  // `None` at the end of the input.
+  fn read_command(&self) -> io::Result<Option<String>> {
+    self.write("(worng) ")?;
+    let mut line = String::new();
+    if self.reader.borrow_mut().read_line(&mut line)? == 0 {
+      return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+  }
+
+  fn source_line(&self, line: i32, current: bool) -> String {
+    let text = self.source.get((line - 1) as usize).map(String::as_str).unwrap_or("");
+    format!("{} {:>3} | {}\n", if current { "->" } else { "  " }, line, text)
+  }
+
+  // runs commands until one resumes the program.
+  fn prompt(&mut self, interpreter: &mut Interpreter, frames: &[Frame]) -> io::Result<Result<(), RuntimeError>> {
+    let mut selected = 0;
+    loop {
+      let command = match self.read_command()? {
+        Some(command) => command,
+        None => {
+          self.stepper.breakpoints.clear();
+          self.stepper.resume(Step::Continue);
+          return Ok(Ok(()));
+        },
+      };
+      let (name, argument) = match command.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (command.as_str(), ""),
+      };
+      let frame = &frames[selected];
+
+      let step = match name {
+        "" => continue,
+        "continue" | "c" => Step::Continue,
+        "step" | "s" => Step::In,
+        "next" | "n" => Step::Over,
+        "out" | "o" => Step::Out,
+        "quit" | "q" => return Ok(Err(RuntimeError::Exit(0))),
+        "help" | "h" => {
+          self.write(HELP)?;
+          continue;
+        },
+        "break" | "b" => {
+          let message = match argument.parse().ok().and_then(|line| breakpoint_line(&self.lines, line)) {
+            Some(line) => {
+              self.stepper.breakpoints.insert(line);
+              format!("breakpoint at line {}\n", line)
+            },
+            None => format!("no statement on or after line '{}'\n", argument),
+          };
+          self.write(&message)?;
+          continue;
+        },
+        "delete" | "d" => {
+          let message = match argument.parse() {
+            Ok(line) if self.stepper.breakpoints.remove(&line) => format!("deleted the breakpoint at line {}\n", line),
+            _ => format!("no breakpoint at line '{}'\n", argument),
+          };
+          self.write(&message)?;
+          continue;
+        },
+        "breakpoints" => {
+          let lines: Vec<String> = self.stepper.breakpoints.iter().map(|line| line.to_string()).collect();
+          let message = if lines.is_empty() { "no breakpoints\n".to_string() } else { format!("breakpoints at lines {}\n", lines.join(", ")) };
+          self.write(&message)?;
+          continue;
+        },
+        "backtrace" | "bt" => {
+          for (index, frame) in frames.iter().enumerate() {
+            let marker = if index == selected { "->" } else { "  " };
+            self.write(&format!("{} #{} {} at line {}\n", marker, index, frame.name, frame.line))?;
+          }
+          continue;
+        },
+        "frame" | "f" => {
+          match argument.parse::<usize>() {
+            Ok(index) if index < frames.len() => {
+              selected = index;
+              let frame = &frames[index];
+              self.write(&format!("#{} {} at line {}\n", index, frame.name, frame.line))?;
+              self.write(&self.source_line(frame.line, true))?;
+            },
+            _ => self.write(&format!("no frame '{}', there are {}\n", argument, frames.len()))?,
+          }
+          continue;
+        },
+        "vars" | "v" => {
+          let scopes = scopes(&frame.environment);
+          let mut any = false;
+          for (index, scope) in scopes.iter().enumerate() {
+            let variables = variables(scope);
+            if variables.is_empty() {
+              continue;
+            }
+            any = true;
+            self.write(&format!("{}:\n", scope_name(index, scopes.len())))?;
+            for (name, value) in variables {
+              self.write(&format!("  {} = {}\n", name, describe(&value)))?;
+            }
+          }
+          if !any {
+            self.write("no variables\n")?;
+          }
+          continue;
+        },
+        "print" | "p" => {
+          let message = match evaluate(interpreter, frame, argument) {
+            Ok(value) => describe(&value),
+            Err(err) => format!("error: {}", err),
+          };
+          self.write(&format!("{}\n", message))?;
+          continue;
+        },
+        "list" | "l" => {
+          let first = (frame.line - 2).max(1);
+          let last = (frame.line + 2).min(self.source.len() as i32);
+          for line in first..=last {
+            self.write(&self.source_line(line, line == frame.line))?;
+          }
+          continue;
+        },
+        _ => {
+          self.write(&format!("unknown command '{}', 'help' lists them\n", name))?;
+          continue;
+        },
+      };
+
+      self.stepper.resume(step);
+      return Ok(Ok(()));
+    }
+  }
+}
+
+impl<'a> DebugHook for Terminal<'a> {
+  fn before(&mut self, interpreter: &mut Interpreter, statement: &Stmt) -> Result<(), RuntimeError> {
+    let line = match statement_line(statement) {
+      Some(line) => line,
+      None => return Ok(()),
+    };
+    let reason = match self.stepper.pause(line, interpreter.calls().len()) {
+      Some(reason) => reason,
+      None => return Ok(()),
+    };
+
+    let frames = frames(interpreter, line);
+    let shown = self
+      .write(&format!("paused at line {} in {} ({})\n", line, frames[0].name, reason.name()))
+      .and_then(|_| self.write(&self.source_line(line, true)));
+    match shown.and_then(|_| self.prompt(interpreter, &frames)) {
+      Ok(result) => result,
+      Err(err) => Err(RuntimeError::InternalError(format!("debugger: {}", err))),
+    }
+  }
+}
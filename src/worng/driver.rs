@@ -0,0 +1,433 @@
+extern crate rustyline;
+
+use std::io::{ self, Read, Cursor};
+use std::fs::{self, File};
+use std::path::Path;
+use std::error::Error;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use self::rustyline::error::ReadlineError;
+use self::rustyline::Editor;
+
+use super::scanner::Scanner;
+use super::statement::Stmt;
+use super::token::Token;
+use super::token_type::TokenType;
+use super::parser::Parser;
+use super::interpreter::{Interpreter, with_stack};
+use super::resolver::Resolver;
+use super::repl::ReplHelper;
+use super::native_fs::FsAccess;
+use super::test_runner::TestRunner;
+use super::formatter::format_source;
+use super::lint::{lint, Rule};
+use super::lsp;
+use super::dap;
+use super::debugger::Terminal;
+use super::profiler::Profiler;
+use super::coverage::{FileCoverage, Hits};
+use super::ast_json::AstJson;
+use super::error::{WorngError, RuntimeError};
+
+/// what the `worng` binary does for each command, keeping what went wrong for its exit code.
+#[derive(Debug)]
+pub struct Worng {
+  pub had_error: bool,
+  pub had_runtime_error: bool,
+  pub exit_status: Option<i32>,
+  pub had_failed_test: bool,
+  pub had_unformatted: bool,
+  pub had_warning: bool,
+  pub args: Vec<String>,
+  pub fs_access: FsAccess,
+}
+
+impl Worng {
+
+  pub fn new() -> Worng {
+    Worng {
+      had_error: false,
+      had_runtime_error: false,
+      exit_status: None,
+      had_failed_test: false,
+      had_unformatted: false,
+      had_warning: false,
+      args: Vec::new(),
+      fs_access: FsAccess::Unrestricted,
+    }
+  }
+
+  /// `None` or `-` reads the program from stdin.
+  pub fn read_source(path: Option<&str>) -> io::Result<String> {
+    let mut contents = String::new();
+    match path {
+      None | Some("-") => { io::stdin().read_to_string(&mut contents)?; },
+      Some(path) => {
+        File::open(path)
+          .and_then(|mut file| file.read_to_string(&mut contents))
+          .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path, err)))?;
+      }
+    }
+    Ok(contents)
+  }
+
+  /// the status given to `os.exit()`, otherwise
+  /// sysexits-style status: 65 for compile errors, 70 for runtime errors, 1 for failed tests.
+  pub fn exit_code(&self) -> i32 {
+    if let Some(status) = self.exit_status {
+      status
+    } else if self.had_error {
+      65
+    } else if self.had_runtime_error {
+      70
+    } else if self.had_failed_test || self.had_unformatted || self.had_warning {
+      1
+    } else {
+      0
+    }
+  }
+
+  pub fn run_source<'a>(&mut self, source: String, reader: &'a mut dyn io::BufRead, writer: &'a mut dyn io::Write) {
+    let writer = Rc::new(RefCell::new(writer));
+    let mut interpreter = Interpreter::new(writer, Rc::new(RefCell::new(reader)));
+    interpreter.args = self.args.clone();
+    interpreter.fs_access = self.fs_access.clone();
+    self.run(&mut interpreter, source);
+  }
+
+  /// runs `source` like `run_source`, timing its functions and counting its lines.
+  pub fn profile_source<'a>(&mut self, source: String, reader: &'a mut dyn io::BufRead, writer: &'a mut dyn io::Write) -> Profiler {
+    let writer = Rc::new(RefCell::new(writer));
+    let mut interpreter = Interpreter::new(writer, Rc::new(RefCell::new(reader)));
+    interpreter.args = self.args.clone();
+    interpreter.fs_access = self.fs_access.clone();
+    interpreter.profiler = Some(Profiler::new());
+    self.run(&mut interpreter, source);
+
+    let mut profiler = interpreter.profiler.take().unwrap_or_default();
+    profiler.finish();
+    profiler
+  }
+
+  /// runs `source` like `run_source`, keeping what it ran under the name `path`.
+  /// `None` when it doesn't compile.
+  pub fn cover_source<'a>(&mut self, path: &str, source: String, reader: &'a mut dyn io::BufRead, writer: &'a mut dyn io::Write) -> Option<FileCoverage> {
+    let statements = match compile(&source) {
+      Ok(statements) => statements,
+      Err(errors) => {
+        self.report_errors(errors);
+        return None;
+      }
+    };
+
+    let writer = Rc::new(RefCell::new(writer));
+    let mut interpreter = Interpreter::new(writer, Rc::new(RefCell::new(reader)));
+    interpreter.args = self.args.clone();
+    interpreter.fs_access = self.fs_access.clone();
+    interpreter.coverage = Some(Hits::default());
+    if let Some(err) = interpreter.interpret(statements.clone()) {
+      self.report_errors(vec![WorngError::RuntimeError(err)]);
+    }
+
+    let hits = interpreter.coverage.take().unwrap_or_default();
+    Some(FileCoverage::new(path, &statements, &hits))
+  }
+
+  /// scan, parse and resolve only.
+  pub fn check_source(&mut self, source: String) {
+    if let Err(errors) = compile(&source) {
+      self.report_errors(errors);
+    }
+  }
+
+  /// runs the `test_*` functions of the `.worng` files under `path`.
+  pub fn run_tests(&mut self, path: &str, writer: &mut dyn io::Write) -> io::Result<()> {
+    let mut runner = TestRunner::new(self.fs_access.clone());
+    if !runner.run(Path::new(path), writer)? {
+      self.had_failed_test = true;
+    }
+    Ok(())
+  }
+
+  /// like `run_tests`, and gives back what the tests of each file ran.
+  pub fn cover_tests(&mut self, path: &str, writer: &mut dyn io::Write) -> io::Result<Vec<FileCoverage>> {
+    let mut runner = TestRunner::new(self.fs_access.clone());
+    runner.coverage = Some(Vec::new());
+    if !runner.run(Path::new(path), writer)? {
+      self.had_failed_test = true;
+    }
+    Ok(runner.coverage.unwrap_or_default())
+  }
+
+  /// rewrites the files in the canonical layout, or formats stdin to `writer` without files.
+  /// with `check` nothing is written but the names of the files that aren't formatted.
+  pub fn format_files(&mut self, files: &[String], check: bool, writer: &mut dyn io::Write) -> io::Result<()> {
+    if files.is_empty() {
+      let source = Worng::read_source(None)?;
+      match format_source(&source) {
+        Ok(ref formatted) if check => {
+          if *formatted != source {
+            writeln!(writer, "<stdin>")?;
+            self.had_unformatted = true;
+          }
+        },
+        Ok(formatted) => writer.write_all(formatted.as_bytes())?,
+        Err(errors) => self.report_errors(errors),
+      }
+      return Ok(());
+    }
+
+    for file in files {
+      let source = Worng::read_source(Some(file))?;
+      match format_source(&source) {
+        Ok(ref formatted) if *formatted == source => {},
+        Ok(_) if check => {
+          writeln!(writer, "{}", file)?;
+          self.had_unformatted = true;
+        },
+        Ok(formatted) => fs::write(file, formatted)?,
+        Err(errors) => self.report_errors(errors),
+      }
+    }
+    Ok(())
+  }
+
+  /// writes the warnings for the files, or stdin without files, leaving out the `allowed` rules.
+  pub fn lint_files(&mut self, files: &[String], allowed: &[Rule], writer: &mut dyn io::Write) -> io::Result<()> {
+    let stdin = [String::from("-")];
+    let files = if files.is_empty() { &stdin[..] } else { files };
+
+    for file in files {
+      let source = Worng::read_source(Some(file))?;
+      let name = if file == "-" { "<stdin>" } else { file };
+      match lint(&source, allowed) {
+        Ok(warnings) => {
+          for warning in warnings.iter() {
+            writeln!(
+              writer,
+              "{}:{}:{}: warning: {} [{}]",
+              name, warning.token.line, warning.column(&source), warning.message, warning.rule.name()
+            )?;
+            self.had_warning = true;
+          }
+        },
+        Err(errors) => self.report_errors(errors),
+      }
+    }
+    Ok(())
+  }
+
+  /// speaks the Language Server Protocol over `reader` and `writer` until the client exits,
+  /// exiting without a `shutdown` request first is a failure.
+  pub fn serve_lsp(&mut self, reader: &mut dyn io::BufRead, writer: &mut dyn io::Write) -> io::Result<()> {
+    if !lsp::serve(reader, writer)? {
+      self.exit_status = Some(1);
+    }
+    Ok(())
+  }
+
+  /// `worng debug`: runs `source` paused before its first statement, the debugger reads its
+  /// commands from `reader` and the program shares it for its input.
+  pub fn debug_source<'a>(&mut self, source: String, reader: &'a mut dyn io::BufRead, writer: &'a mut dyn io::Write) {
+    let statements = match compile(&source) {
+      Ok(statements) => statements,
+      Err(errors) => return self.report_errors(errors),
+    };
+
+    let writer = Rc::new(RefCell::new(writer));
+    let reader = Rc::new(RefCell::new(reader));
+    let mut interpreter = Interpreter::new(writer.clone(), reader.clone());
+    interpreter.args = self.args.clone();
+    interpreter.fs_access = self.fs_access.clone();
+    interpreter.debug_hook = Some(Box::new(Terminal::new(&source, &statements, reader, writer)));
+
+    if let Some(err) = interpreter.interpret(statements) {
+      self.report_errors(vec![WorngError::RuntimeError(err)]);
+    }
+  }
+
+  /// speaks the Debug Adapter Protocol over `reader` and `writer` until the client disconnects.
+  pub fn serve_dap(&mut self, reader: &mut dyn io::BufRead, writer: &mut dyn io::Write) -> io::Result<()> {
+    dap::serve(reader, writer, self.fs_access.clone())
+  }
+
+  /// `json` writes them as `AstJson` does, the tokens scanned before an error are still written.
+  pub fn dump_tokens(&mut self, source: String, json: bool, writer: &mut dyn io::Write) -> io::Result<()> {
+    let mut scanner = Scanner::new(&source);
+    let tokens = scanner.scan_tokens();
+
+    if json {
+      writeln!(writer, "{}", AstJson::new(&source).tokens(&tokens).pretty(2))?;
+    } else {
+      for token in tokens.iter() {
+        writeln!(writer, "[line {}] {:?} {:?} {:?}", token.line, token.token_type, token.lexeme, token.literal)?;
+      }
+    }
+
+    let errors = scanner.errors.into_iter().map(WorngError::ScanningError).collect();
+    self.report_errors(errors);
+    Ok(())
+  }
+
+  /// the tree after resolving, `json` writes it as `AstJson` does.
+  pub fn dump_ast(&mut self, source: String, json: bool, writer: &mut dyn io::Write) -> io::Result<()> {
+    match compile(&source) {
+      Ok(statements) if json => writeln!(writer, "{}", AstJson::new(&source).statements(&statements).pretty(2))?,
+      Ok(statements) => {
+        for stmt in statements.iter() {
+          writeln!(writer, "{}", stmt)?;
+        }
+      },
+      Err(errors) => self.report_errors(errors),
+    }
+    Ok(())
+  }
+
+  pub fn run_prompt<'a>(&mut self, reader: &'a mut dyn io::BufRead, writer: &'a mut dyn io::Write) -> Result<(), Box<dyn Error>>{
+    let writer = Rc::new(RefCell::new(writer));
+    let mut interpreter = Interpreter::new(writer.clone(), Rc::new(RefCell::new(reader)));
+    interpreter.fs_access = self.fs_access.clone();
+
+    let mut rl = Editor::<ReplHelper>::new();
+    rl.set_helper(Some(ReplHelper::new(interpreter.globals.clone())));
+    if rl.load_history("history.txt").is_err() {
+      println!("No previous history.");
+    }
+  
+    loop {
+      let readline = rl.readline(">> ");
+      let l = match readline {
+        Ok(line) => { rl.add_history_entry(line.as_str()); line },
+        Err(ReadlineError::Interrupted) => { println!("exit"); break },
+        Err(err) => { println!("Error: {:?}", err); break }
+      };
+      self.run(&mut interpreter, l);
+      if self.exit_status.is_some() {
+        break;
+      }
+
+      // a mistake in one line should not end the session.
+      self.had_error = false;
+      self.had_runtime_error = false;
+    }
+  
+    rl.save_history("history.txt")?;
+  
+    Ok(())
+  }
+
+  fn run(&mut self, interpreter: &mut Interpreter, source: String) {
+    if let Err(errors) = run(interpreter, source) {
+      self.report_errors(errors);
+    }
+  }
+
+  fn report_errors(&mut self, errors: Vec<WorngError>) {
+    for err in errors.iter() {
+      match *err {
+        WorngError::RuntimeError(RuntimeError::Exit(status)) => {
+          self.exit_status = Some(status);
+          continue;
+        },
+        WorngError::RuntimeError(_) => self.had_runtime_error = true,
+        _ => self.had_error = true,
+      }
+      eprintln!("{}", err);
+    }
+  }
+
+  fn report(&mut self, line: i32, pos: String, message: &str) {
+    eprintln!("[Line {}] Error {}: {}", line,pos,message);
+    self.had_error = true;
+  }
+  
+  pub fn error(&mut self, token: Token, message: &str) {
+    if token.token_type == TokenType::EOF {
+      self.report(token.line, String::from(" at end"), message);
+    } else {
+      self.report(token.line, format!(" at '{}'", token.lexeme), message);
+    }
+  }
+}
+
+impl Default for Worng {
+  fn default() -> Self {
+    Worng::new()
+  }
+}
+
+/// scanning, parsing and resolving, everything that happens before the program runs.
+/// errors of each stage are collected so they can be reported together.
+pub(crate) fn compile(source: &str) -> Result<Vec<Stmt>, Vec<WorngError>> {
+  let mut scanner = Scanner::new(source);
+  let tokens: Vec<Token> = scanner.scan_tokens();
+  let mut errors: Vec<WorngError> = scanner.errors.into_iter().map(WorngError::ScanningError).collect();
+
+  let mut statements = match Parser::new(tokens).parse() {
+    Ok(statements) => statements,
+    Err(parse_errors) => {
+      errors.extend(parse_errors.into_iter().map(WorngError::ParsingError));
+      return Err(errors);
+    }
+  };
+
+  let mut resolver = Resolver::new();
+  resolver.resolve(&mut statements);
+  errors.extend(resolver.errors.into_iter().map(WorngError::ParsingError));
+
+  if errors.is_empty() {
+    Ok(statements)
+  } else {
+    Err(errors)
+  }
+}
+
+fn run(interpreter: &mut Interpreter, source: String) -> Result<(), Vec<WorngError>> {
+  let statements = compile(&source)?;
+
+  match interpreter.interpret(statements) {
+    Some(err) => Err(vec![WorngError::RuntimeError(err)]),
+    None => Ok(()),
+  }
+}
+
+pub fn run_string(code: String) -> String {
+  run_string_with_input(code, String::new())
+}
+
+/// like `run_string` but `input` is what the program reads with `input()`, `read_line()` and `read_all()`.
+pub fn run_string_with_input(code: String, input: String) -> String {
+  match run_string_output(code, input) {
+    (_, Some(errors)) => errors,
+    (output, None) => output,
+  }
+}
+
+/// what the program wrote before it ended, and its errors, if any, one per line.
+/// a program ending with `os.exit()` has no errors.
+pub fn run_string_output(code: String, input: String) -> (String, Option<String>) {
+  with_stack(move || run_string_here(code, input))
+}
+
+fn run_string_here(code: String, input: String) -> (String, Option<String>) {
+  let output: Vec<u8> = Vec::new();
+  let mut cursor = Cursor::new(output);
+  let mut input = Cursor::new(input.into_bytes());
+
+  let result = {
+    let writer = Rc::new(RefCell::new(&mut cursor as &mut dyn io::Write));
+    let reader = Rc::new(RefCell::new(&mut input as &mut dyn io::BufRead));
+    let mut interpreter = Interpreter::new(writer, reader);
+    run(&mut interpreter, code)
+  };
+
+  let output = String::from_utf8(cursor.into_inner()).unwrap();
+  match result {
+    Err(ref errors) if !errors.iter().any(|err| matches!(err, WorngError::RuntimeError(RuntimeError::Exit(_)))) => {
+      let errors = errors.iter().map(|error| error.to_string()).collect::<Vec<String>>().join("\n");
+      (output, Some(errors))
+    },
+    _ => (output, None),
+  }
+}
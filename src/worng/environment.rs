@@ -3,7 +3,15 @@ use std::rc::Rc;
 use std::cell::RefCell;
 
 use super::expr::Expr;
-use super::native_function::NativeClock;
+use super::native_function::{NativeClock, NativeFunction};
+use super::native_io;
+use super::native_convert;
+use super::native_random;
+use super::native_time;
+use super::{native_list, native_map, native_json, native_regex, native_assert};
+use super::native_os;
+use super::native_fs;
+use super::native_math;
 use super::token::{ Token };
 use super::error::{ RuntimeError, EnvironmentError };
 use super::worng_value::{WorngValue};
@@ -26,9 +34,44 @@ impl Environment {
     self.values.insert(string, value); 
   }
 
+  pub fn names(&self) -> Vec<String> {
+    self.values.keys().cloned().collect()
+  }
+
+  /// whether `name` is defined in this scope itself, not an enclosing one.
+  pub fn defines(&self, name: &str) -> bool {
+    self.values.contains_key(name)
+  }
+
+  /// the names of this scope itself and their values, sorted by name.
+  pub fn variables(&self) -> Vec<(String, WorngValue)> {
+    let mut variables: Vec<(String, WorngValue)> = self.values.iter().map(|(name, value)| (name.clone(), value.clone())).collect();
+    variables.sort_by(|(left, _), (right, _)| left.cmp(right));
+    variables
+  }
+
   pub fn global() -> Environment {
     let mut env = Environment::new();
     env.define( "clock".to_string(), WorngValue::Func(Rc::new(NativeClock::new())));
+    env.define( "input".to_string(), WorngValue::Func(Rc::new(NativeFunction::new("input", 1, native_io::input))));
+    env.define( "read_line".to_string(), WorngValue::Func(Rc::new(NativeFunction::new("read_line", 0, native_io::read_line))));
+    env.define( "read_all".to_string(), WorngValue::Func(Rc::new(NativeFunction::new("read_all", 0, native_io::read_all))));
+    env.define( "str".to_string(), WorngValue::Func(Rc::new(NativeFunction::new("str", 1, native_convert::str))));
+    env.define( "num".to_string(), WorngValue::Func(Rc::new(NativeFunction::new("num", 1, native_convert::num))));
+    env.define( "bool".to_string(), WorngValue::Func(Rc::new(NativeFunction::new("bool", 1, native_convert::bool))));
+    env.define( "type".to_string(), WorngValue::Func(Rc::new(NativeFunction::new("type", 1, native_convert::type_of))));
+    env.define( "is_instance".to_string(), WorngValue::Func(Rc::new(NativeFunction::new("is_instance", 2, native_convert::is_instance))));
+    env.define( "assert".to_string(), WorngValue::Func(Rc::new(NativeFunction::new("assert", 2, native_assert::assert))));
+    env.define( "assert_eq".to_string(), WorngValue::Func(Rc::new(NativeFunction::new("assert_eq", 2, native_assert::assert_eq))));
+    env.define( "list".to_string(), WorngValue::Func(Rc::new(NativeFunction::new("list", 0, native_list::new))));
+    env.define( "map".to_string(), WorngValue::Func(Rc::new(NativeFunction::new("map", 0, native_map::new))));
+    env.define( "os".to_string(), WorngValue::Module(Rc::new(native_os::module())));
+    env.define( "fs".to_string(), WorngValue::Module(Rc::new(native_fs::module())));
+    env.define( "math".to_string(), WorngValue::Module(Rc::new(native_math::module())));
+    env.define( "random".to_string(), WorngValue::Module(Rc::new(native_random::module())));
+    env.define( "time".to_string(), WorngValue::Module(Rc::new(native_time::module())));
+    env.define( "json".to_string(), WorngValue::Module(Rc::new(native_json::module())));
+    env.define( "regex".to_string(), WorngValue::Module(Rc::new(native_regex::module())));
     env
   }
 
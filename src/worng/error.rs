@@ -2,6 +2,7 @@ use super::token::Token;
 
 pub enum WorngError{
   ValueError(ValueError),
+  ScanningError(ScanningError),
   ParsingError(ParsingError),
   RuntimeError(RuntimeError) 
 }
@@ -12,6 +13,12 @@ pub enum ValueError {
   // UndefinedMethod(String)
 }
 
+#[derive(Debug, Clone)]
+pub enum ScanningError {
+  UnexpectedCharacter(i32, char),
+  UnterminatedString(i32),
+}
+
 // TODO: add more error handling
 #[derive(Debug)]
 pub enum ParsingError {
@@ -21,6 +28,8 @@ pub enum ParsingError {
   InvalidAssignmentError(Token),
   TooManyArgumentsError,
   TooManyParametersError,
+  TooDeeplyNestedError(Token, usize),
+  ResolvingError(Token, String),
   InternalError(String)
 }
 
@@ -37,8 +46,53 @@ pub enum RuntimeError{
   InvalidGetTarget(Token),
   UndefinedVariable(Token),
   UndefinedProperty(Token),
-  ArityError(usize, usize),
-  CallOnNonCallable(Token)
+  ArityError(Token, usize, usize),
+  CallOnNonCallable(Token),
+  TypeError(Token, String),
+  NativeError(Token, String),
+  AssertionError(Token, String),
+  StackOverflow(Token, usize),
+  Exit(i32)
+}
+
+impl RuntimeError {
+  /// where it happened, when known.
+  pub fn token(&self) -> Option<&Token> {
+    match *self {
+      RuntimeError::RuntimeError(ref token)
+      | RuntimeError::SubtractNonNumbers(ref token)
+      | RuntimeError::AddNonNumbers(ref token)
+      | RuntimeError::DivideByZero(ref token)
+      | RuntimeError::InvalidSuperclass(ref token)
+      | RuntimeError::InvalidGetTarget(ref token)
+      | RuntimeError::UndefinedVariable(ref token)
+      | RuntimeError::UndefinedProperty(ref token)
+      | RuntimeError::ArityError(ref token, _, _)
+      | RuntimeError::CallOnNonCallable(ref token)
+      | RuntimeError::TypeError(ref token, _)
+      | RuntimeError::NativeError(ref token, _)
+      | RuntimeError::AssertionError(ref token, _)
+      | RuntimeError::StackOverflow(ref token, _) => Some(token),
+      RuntimeError::DivideInvalidType | RuntimeError::InternalError(_) | RuntimeError::Exit(_) => None,
+    }
+  }
+}
+
+impl ParsingError {
+  /// where it happened, when known.
+  pub fn token(&self) -> Option<&Token> {
+    match *self {
+      ParsingError::UnexpectedTokenError(ref token, _)
+      | ParsingError::InvalidAssignmentError(ref token)
+      | ParsingError::TooDeeplyNestedError(ref token, _)
+      | ParsingError::ResolvingError(ref token, _) => Some(token),
+      ParsingError::ParsingError
+      | ParsingError::UnexpectedEofError
+      | ParsingError::TooManyArgumentsError
+      | ParsingError::TooManyParametersError
+      | ParsingError::InternalError(_) => None,
+    }
+  }
 }
 
 #[derive(Debug)]
@@ -105,15 +159,41 @@ impl std::fmt::Display for RuntimeError {
       RuntimeError::UndefinedProperty(ref token) => {
         write!(f,  "[line {}] Undefined property -> {}", token.line, token.lexeme)
       },
-      RuntimeError::ArityError(ref expected, ref size ) => {
-        write!(f,  "Expected {} arguments but got {}.", expected, size )
+      RuntimeError::ArityError(ref token, ref expected, ref size ) => {
+        write!(f,  "[line {}] Expected {} arguments but got {}.", token.line, expected, size )
       },
       RuntimeError::CallOnNonCallable(ref token ) => {
         write!(f,  "call on non-callable: {}.", token.lexeme )
+      },
+      RuntimeError::TypeError(ref token, ref message) => {
+        write!(f,  "[line {}] Type error: {}", token.line, message)
+      },
+      RuntimeError::NativeError(ref token, ref message) => {
+        write!(f,  "[line {}] {}", token.line, message)
+      },
+      RuntimeError::AssertionError(ref token, ref message) => {
+        write!(f,  "[line {}] Assertion failed: {}", token.line, message)
+      },
+      RuntimeError::StackOverflow(ref token, ref max_depth) => {
+        write!(f,  "[line {}] Stack overflow, more than {} levels of nested calls and expressions.", token.line, max_depth)
+      },
+      RuntimeError::Exit(ref code) => {
+        write!(f,  "exit with status {}", code)
       }
     }
   }
 }
+impl std::fmt::Display for WorngError {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match *self{
+      WorngError::ValueError(ref err) => write!(f, "{}", err),
+      WorngError::ScanningError(ref err) => write!(f, "{}", err),
+      WorngError::ParsingError(ref err) => write!(f, "{}", err),
+      WorngError::RuntimeError(ref err) => write!(f, "{}", err),
+    }
+  }
+}
+
 impl std::fmt::Display for ValueError {
   fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
     match *self{
@@ -127,6 +207,19 @@ impl std::fmt::Display for ValueError {
   }
 }
 
+impl std::fmt::Display for ScanningError {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match *self{
+      ScanningError::UnexpectedCharacter(ref line, ref c) => {
+        write!(f, "Line: {} Unexpected character '{}'.", line, c)
+      },
+      ScanningError::UnterminatedString(ref line) => {
+        write!(f, "Unterminated string. Line: {}", line)
+      },
+    }
+  }
+}
+
 impl std::fmt::Display for ParsingError {
   fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
     match *self{
@@ -134,7 +227,7 @@ impl std::fmt::Display for ParsingError {
         write!(f, "[Line: {}] Unexpected token error: {} {}",token.line, string,token.lexeme )
       },
       ParsingError::UnexpectedEofError => {
-        write!(f, "unexpected error")
+        write!(f, "Unexpected end of input.")
       },
       ParsingError::InvalidAssignmentError(ref token) => {
         write!(f, "[Line: {}] invalid assingment error", token.line)
@@ -142,9 +235,15 @@ impl std::fmt::Display for ParsingError {
       ParsingError::TooManyArgumentsError => {
         write!(f, "Cannot have more than 255 arguments.")
       },
+      ParsingError::TooDeeplyNestedError(ref token, ref max_depth) => {
+        write!(f, "[Line: {}] Too deeply nested, at most {} levels are allowed.", token.line, max_depth)
+      },
       ParsingError::TooManyParametersError => {
         write!(f, "too many params error")
       },
+      ParsingError::ResolvingError(ref token, ref string) => {
+        write!(f, "[Line: {}] Error at '{}': {}", token.line, token.lexeme, string)
+      },
       ParsingError::InternalError(ref string) => {
         write!(f, "Error: {}", string)
       },
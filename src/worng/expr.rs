@@ -1,65 +1,409 @@
-use super::token::{Literal, Token};
+// generated by `cargo run --bin generate_ast -- src/worng` from the spec in
+// src/tool/GenerateAst.rs, change that instead.
 
 use std::fmt;
 
+use super::ast_printer::{list, print_literal};
+use super::token::{Literal, Token};
+use super::visitor::VisitResult;
+
 #[derive(Debug, Clone)]
-pub enum Expr{
-  Unary(Token,Box<Expr>),
+pub enum Expr {
+  /// operator, right.
+  Unary(Token, Box<Expr>),
+  /// value.
   Literal(Literal),
-  Binary(Box<Expr>,Token,Box<Expr>),
+  /// left, operator, right.
+  Binary(Box<Expr>, Token, Box<Expr>),
+  /// callee, paren, arguments. the paren is the one closing the arguments.
   Call(Box<Expr>, Token, Vec<Expr>),
+  /// object, name.
   Get(Box<Expr>, Token),
+  /// expression.
   Grouping(Box<Expr>),
+  /// name, depth. the depth is what the resolver found, none for globals.
   Var(Token, Option<usize>),
+  /// name, value, depth.
   Assign(Token, Box<Expr>, Option<usize>),
+  /// object, name, value.
   Set(Box<Expr>, Token, Box<Expr>),
+  /// left, operator, right.
   Logical(Box<Expr>, Token, Box<Expr>),
+  /// keyword, depth.
   This(Token, Option<usize>),
-  Super(Token, Token, Option<usize>)
+  /// keyword, method, depth.
+  Super(Token, Token, Option<usize>),
+}
+
+/// a pass over expressions giving an `R` for each. `visit_expr` hands each kind to its own method,
+/// a pass overrides those of the kinds it looks at, the others visit the parts, giving the
+/// first break one of them gives, or `R::walked()`.
+pub trait ExprVisitor<R: VisitResult> {
+  fn visit_expr(&mut self, expr: &Expr) -> R {
+    match *expr {
+      Expr::Unary(ref operator, ref right) => self.visit_unary_expr(operator, right),
+      Expr::Literal(ref value) => self.visit_literal_expr(value),
+      Expr::Binary(ref left, ref operator, ref right) => self.visit_binary_expr(left, operator, right),
+      Expr::Call(ref callee, ref paren, ref arguments) => self.visit_call_expr(callee, paren, arguments),
+      Expr::Get(ref object, ref name) => self.visit_get_expr(object, name),
+      Expr::Grouping(ref expression) => self.visit_grouping_expr(expression),
+      Expr::Var(ref name, ref depth) => self.visit_var_expr(name, depth),
+      Expr::Assign(ref name, ref value, ref depth) => self.visit_assign_expr(name, value, depth),
+      Expr::Set(ref object, ref name, ref value) => self.visit_set_expr(object, name, value),
+      Expr::Logical(ref left, ref operator, ref right) => self.visit_logical_expr(left, operator, right),
+      Expr::This(ref keyword, ref depth) => self.visit_this_expr(keyword, depth),
+      Expr::Super(ref keyword, ref method, ref depth) => self.visit_super_expr(keyword, method, depth),
+    }
+  }
+
+  fn visit_unary_expr(&mut self, _operator: &Token, right: &Expr) -> R {
+    if let Some(stop) = self.visit_expr(right).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_literal_expr(&mut self, _value: &Literal) -> R {
+    R::walked()
+  }
+
+  fn visit_binary_expr(&mut self, left: &Expr, _operator: &Token, right: &Expr) -> R {
+    if let Some(stop) = self.visit_expr(left).into_break() {
+      return R::from_break(stop);
+    }
+    if let Some(stop) = self.visit_expr(right).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_call_expr(&mut self, callee: &Expr, _paren: &Token, arguments: &[Expr]) -> R {
+    if let Some(stop) = self.visit_expr(callee).into_break() {
+      return R::from_break(stop);
+    }
+    for item in arguments.iter() {
+      if let Some(stop) = self.visit_expr(item).into_break() {
+        return R::from_break(stop);
+      }
+    }
+    R::walked()
+  }
+
+  fn visit_get_expr(&mut self, object: &Expr, _name: &Token) -> R {
+    if let Some(stop) = self.visit_expr(object).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_grouping_expr(&mut self, expression: &Expr) -> R {
+    if let Some(stop) = self.visit_expr(expression).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_var_expr(&mut self, _name: &Token, _depth: &Option<usize>) -> R {
+    R::walked()
+  }
+
+  fn visit_assign_expr(&mut self, _name: &Token, value: &Expr, _depth: &Option<usize>) -> R {
+    if let Some(stop) = self.visit_expr(value).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_set_expr(&mut self, object: &Expr, _name: &Token, value: &Expr) -> R {
+    if let Some(stop) = self.visit_expr(object).into_break() {
+      return R::from_break(stop);
+    }
+    if let Some(stop) = self.visit_expr(value).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_logical_expr(&mut self, left: &Expr, _operator: &Token, right: &Expr) -> R {
+    if let Some(stop) = self.visit_expr(left).into_break() {
+      return R::from_break(stop);
+    }
+    if let Some(stop) = self.visit_expr(right).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_this_expr(&mut self, _keyword: &Token, _depth: &Option<usize>) -> R {
+    R::walked()
+  }
+
+  fn visit_super_expr(&mut self, _keyword: &Token, _method: &Token, _depth: &Option<usize>) -> R {
+    R::walked()
+  }
 }
 
+/// visits the nodes directly in `expr`, in source order, giving the first break or `R::walked()`.
+pub fn walk_expr<R: VisitResult, V: ExprVisitor<R> + ?Sized>(visitor: &mut V, expr: &Expr) -> R {
+  match *expr {
+    Expr::Unary(_, ref right) => {
+      if let Some(stop) = visitor.visit_expr(right).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Expr::Literal(_) => {},
+    Expr::Binary(ref left, _, ref right) => {
+      if let Some(stop) = visitor.visit_expr(left).into_break() {
+        return R::from_break(stop);
+      }
+      if let Some(stop) = visitor.visit_expr(right).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Expr::Call(ref callee, _, ref arguments) => {
+      if let Some(stop) = visitor.visit_expr(callee).into_break() {
+        return R::from_break(stop);
+      }
+      for item in arguments.iter() {
+        if let Some(stop) = visitor.visit_expr(item).into_break() {
+          return R::from_break(stop);
+        }
+      }
+    },
+    Expr::Get(ref object, _) => {
+      if let Some(stop) = visitor.visit_expr(object).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Expr::Grouping(ref expression) => {
+      if let Some(stop) = visitor.visit_expr(expression).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Expr::Var(_, _) => {},
+    Expr::Assign(_, ref value, _) => {
+      if let Some(stop) = visitor.visit_expr(value).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Expr::Set(ref object, _, ref value) => {
+      if let Some(stop) = visitor.visit_expr(object).into_break() {
+        return R::from_break(stop);
+      }
+      if let Some(stop) = visitor.visit_expr(value).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Expr::Logical(ref left, _, ref right) => {
+      if let Some(stop) = visitor.visit_expr(left).into_break() {
+        return R::from_break(stop);
+      }
+      if let Some(stop) = visitor.visit_expr(right).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Expr::This(_, _) => {},
+    Expr::Super(_, _, _) => {},
+  }
+  R::walked()
+}
+
+/// the `ExprVisitor` of passes changing the expressions they visit, like the resolver
+/// filling in depths, with the same methods taking `&mut` parts.
+pub trait ExprVisitorMut<R: VisitResult> {
+  fn visit_expr_mut(&mut self, expr: &mut Expr) -> R {
+    match *expr {
+      Expr::Unary(ref mut operator, ref mut right) => self.visit_unary_expr_mut(operator, right),
+      Expr::Literal(ref mut value) => self.visit_literal_expr_mut(value),
+      Expr::Binary(ref mut left, ref mut operator, ref mut right) => self.visit_binary_expr_mut(left, operator, right),
+      Expr::Call(ref mut callee, ref mut paren, ref mut arguments) => self.visit_call_expr_mut(callee, paren, arguments),
+      Expr::Get(ref mut object, ref mut name) => self.visit_get_expr_mut(object, name),
+      Expr::Grouping(ref mut expression) => self.visit_grouping_expr_mut(expression),
+      Expr::Var(ref mut name, ref mut depth) => self.visit_var_expr_mut(name, depth),
+      Expr::Assign(ref mut name, ref mut value, ref mut depth) => self.visit_assign_expr_mut(name, value, depth),
+      Expr::Set(ref mut object, ref mut name, ref mut value) => self.visit_set_expr_mut(object, name, value),
+      Expr::Logical(ref mut left, ref mut operator, ref mut right) => self.visit_logical_expr_mut(left, operator, right),
+      Expr::This(ref mut keyword, ref mut depth) => self.visit_this_expr_mut(keyword, depth),
+      Expr::Super(ref mut keyword, ref mut method, ref mut depth) => self.visit_super_expr_mut(keyword, method, depth),
+    }
+  }
+
+  fn visit_unary_expr_mut(&mut self, _operator: &mut Token, right: &mut Expr) -> R {
+    if let Some(stop) = self.visit_expr_mut(right).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_literal_expr_mut(&mut self, _value: &mut Literal) -> R {
+    R::walked()
+  }
+
+  fn visit_binary_expr_mut(&mut self, left: &mut Expr, _operator: &mut Token, right: &mut Expr) -> R {
+    if let Some(stop) = self.visit_expr_mut(left).into_break() {
+      return R::from_break(stop);
+    }
+    if let Some(stop) = self.visit_expr_mut(right).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_call_expr_mut(&mut self, callee: &mut Expr, _paren: &mut Token, arguments: &mut Vec<Expr>) -> R {
+    if let Some(stop) = self.visit_expr_mut(callee).into_break() {
+      return R::from_break(stop);
+    }
+    for item in arguments.iter_mut() {
+      if let Some(stop) = self.visit_expr_mut(item).into_break() {
+        return R::from_break(stop);
+      }
+    }
+    R::walked()
+  }
+
+  fn visit_get_expr_mut(&mut self, object: &mut Expr, _name: &mut Token) -> R {
+    if let Some(stop) = self.visit_expr_mut(object).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_grouping_expr_mut(&mut self, expression: &mut Expr) -> R {
+    if let Some(stop) = self.visit_expr_mut(expression).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_var_expr_mut(&mut self, _name: &mut Token, _depth: &mut Option<usize>) -> R {
+    R::walked()
+  }
+
+  fn visit_assign_expr_mut(&mut self, _name: &mut Token, value: &mut Expr, _depth: &mut Option<usize>) -> R {
+    if let Some(stop) = self.visit_expr_mut(value).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_set_expr_mut(&mut self, object: &mut Expr, _name: &mut Token, value: &mut Expr) -> R {
+    if let Some(stop) = self.visit_expr_mut(object).into_break() {
+      return R::from_break(stop);
+    }
+    if let Some(stop) = self.visit_expr_mut(value).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_logical_expr_mut(&mut self, left: &mut Expr, _operator: &mut Token, right: &mut Expr) -> R {
+    if let Some(stop) = self.visit_expr_mut(left).into_break() {
+      return R::from_break(stop);
+    }
+    if let Some(stop) = self.visit_expr_mut(right).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_this_expr_mut(&mut self, _keyword: &mut Token, _depth: &mut Option<usize>) -> R {
+    R::walked()
+  }
+
+  fn visit_super_expr_mut(&mut self, _keyword: &mut Token, _method: &mut Token, _depth: &mut Option<usize>) -> R {
+    R::walked()
+  }
+}
+
+/// visits the nodes directly in `expr`, in source order, giving the first break or `R::walked()`.
+pub fn walk_expr_mut<R: VisitResult, V: ExprVisitorMut<R> + ?Sized>(visitor: &mut V, expr: &mut Expr) -> R {
+  match *expr {
+    Expr::Unary(_, ref mut right) => {
+      if let Some(stop) = visitor.visit_expr_mut(right).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Expr::Literal(_) => {},
+    Expr::Binary(ref mut left, _, ref mut right) => {
+      if let Some(stop) = visitor.visit_expr_mut(left).into_break() {
+        return R::from_break(stop);
+      }
+      if let Some(stop) = visitor.visit_expr_mut(right).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Expr::Call(ref mut callee, _, ref mut arguments) => {
+      if let Some(stop) = visitor.visit_expr_mut(callee).into_break() {
+        return R::from_break(stop);
+      }
+      for item in arguments.iter_mut() {
+        if let Some(stop) = visitor.visit_expr_mut(item).into_break() {
+          return R::from_break(stop);
+        }
+      }
+    },
+    Expr::Get(ref mut object, _) => {
+      if let Some(stop) = visitor.visit_expr_mut(object).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Expr::Grouping(ref mut expression) => {
+      if let Some(stop) = visitor.visit_expr_mut(expression).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Expr::Var(_, _) => {},
+    Expr::Assign(_, ref mut value, _) => {
+      if let Some(stop) = visitor.visit_expr_mut(value).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Expr::Set(ref mut object, _, ref mut value) => {
+      if let Some(stop) = visitor.visit_expr_mut(object).into_break() {
+        return R::from_break(stop);
+      }
+      if let Some(stop) = visitor.visit_expr_mut(value).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Expr::Logical(ref mut left, _, ref mut right) => {
+      if let Some(stop) = visitor.visit_expr_mut(left).into_break() {
+        return R::from_break(stop);
+      }
+      if let Some(stop) = visitor.visit_expr_mut(right).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Expr::This(_, _) => {},
+    Expr::Super(_, _, _) => {},
+  }
+  R::walked()
+}
+
+/// `expr` as an S-expression, see `print_program`.
+pub fn print_expr(expr: &Expr) -> String {
+  match *expr {
+    Expr::Unary(ref operator, ref right) => list(&operator.lexeme, &[print_expr(right)]),
+    Expr::Literal(ref value) => print_literal(value),
+    Expr::Binary(ref left, ref operator, ref right) => list(&operator.lexeme, &[print_expr(left), print_expr(right)]),
+    Expr::Call(ref callee, _, ref arguments) => {
+      let mut parts = vec![print_expr(callee)];
+      parts.extend(arguments.iter().map(print_expr));
+      list("call", &parts)
+    },
+    Expr::Get(ref object, ref name) => list("get", &[print_expr(object), name.lexeme.clone()]),
+    Expr::Grouping(ref expression) => list("group", &[print_expr(expression)]),
+    Expr::Var(ref name, _) => name.lexeme.clone(),
+    Expr::Assign(ref name, ref value, _) => list("assign", &[name.lexeme.clone(), print_expr(value)]),
+    Expr::Set(ref object, ref name, ref value) => list("set", &[print_expr(object), name.lexeme.clone(), print_expr(value)]),
+    Expr::Logical(ref left, ref operator, ref right) => list(&operator.lexeme, &[print_expr(left), print_expr(right)]),
+    Expr::This(ref keyword, _) => keyword.lexeme.clone(),
+    Expr::Super(_, ref method, _) => format!("(super {})", method.lexeme),
+  }
+}
 
 impl fmt::Display for Expr {
-  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    match *self{
-      Expr::Binary(ref left, ref operator, ref right) => {
-        write!(f, "({} {} {})", operator.lexeme, left, right)
-      },
-
-      Expr::Unary(ref operator, ref right) => {
-        write!(f, "({} {})", operator.lexeme, right) 
-      },
-
-      Expr::Grouping(ref expression) => {
-        write!(f, "(group {})", expression) 
-      }, 
-      Expr::Get(ref object, ref name) => {
-        write!(f, "get obj = {}, name = {}", object, name.lexeme) 
-      }, 
-      Expr::Literal(ref expression) => {
-        write!(f, "{}", expression) 
-      },
-      Expr::Var(ref token, ref value) => {
-        write!(f, "var {}", token.lexeme )
-      },
-      Expr::Assign(ref token, ref expr, _) => { 
-       write!(f, "(assign {} {})", token.lexeme, expr)
-      },
-      Expr::Set(ref object, ref name, ref value) => { 
-       write!(f, "set {} {} to value: {}", object, name.lexeme , value )
-      },
-      Expr::Logical(ref left, ref token, ref right) => { 
-       write!(f, "Logical = ({} {} {})", left, token.lexeme, right)
-      },
-      Expr::Call(ref callee, ref paren, ref arguments) => { 
-       write!(f, "Call = {}, {:?}, {:?}", callee, paren, arguments)
-      },
-      Expr::This(ref keyword, _) => {
-        write!(f, "this = {}", keyword.lexeme)
-      },
-      Expr::Super(ref keyword, ref method, _) => {
-        write!(f, "super key = {}, method = {}", keyword.lexeme, method.lexeme )
-      }
-    }
-  }
-}
\ No newline at end of file
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str(&print_expr(self))
+  }
+}
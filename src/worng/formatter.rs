@@ -0,0 +1,444 @@
+use super::scanner::Scanner;
+use super::parser::Parser;
+use super::statement::Stmt;
+use super::expr::Expr;
+use super::token::{Literal, Token};
+use super::token_type::TokenType;
+use super::error::{WorngError, ParsingError};
+
+const INDENT: &str = "  ";
+
+/// the canonical layout of `source`, as printed by `worng fmt`.
+/// the syntax tree decides the layout, but it has no comments and `for` loops come out of
+/// the parser as `while` loops, so the formatter walks the tokens along with it and takes
+/// both from there. blank lines between statements are kept, at most one in a row.
+pub fn format_source(source: &str) -> Result<String, Vec<WorngError>> {
+  let mut scanner = Scanner::new(source);
+  let tokens = scanner.scan_tokens();
+  if !scanner.errors.is_empty() {
+    return Err(scanner.errors.into_iter().map(WorngError::ScanningError).collect());
+  }
+
+  let statements = Parser::new(tokens.clone())
+    .parse()
+    .map_err(|errors| errors.into_iter().map(WorngError::ParsingError).collect::<Vec<WorngError>>())?;
+
+  let mut formatter = Formatter::new(&tokens, &scanner.comments);
+  formatter.statements(&statements, false).map_err(|err| vec![WorngError::ParsingError(err)])?;
+  Ok(formatter.finish())
+}
+
+struct Formatter<'a> {
+  tokens: &'a [Token],
+  comments: &'a [Token],
+  current: usize,
+  comment: usize,
+  out: String,
+  indent: usize,
+  // nothing written on the output line yet.
+  line_start: bool,
+  // a `//` comment ended the line, whatever comes next goes on a new one.
+  break_line: bool,
+  // the line continues the statement above, it is indented once more.
+  continuation: bool,
+  // a blank line in the source before the next token is kept.
+  statement_start: bool,
+  // right after `{` or the start of the file, where blank lines are dropped.
+  block_start: bool,
+  // the source line the last written token or comment ends on.
+  last_line: i32,
+}
+
+impl<'a> Formatter<'a> {
+  fn new(tokens: &'a [Token], comments: &'a [Token]) -> Formatter<'a> {
+    Formatter {
+      tokens,
+      comments,
+      current: 0,
+      comment: 0,
+      out: String::new(),
+      indent: 0,
+      line_start: true,
+      break_line: false,
+      continuation: false,
+      statement_start: false,
+      block_start: true,
+      last_line: 0,
+    }
+  }
+
+  fn finish(mut self) -> String {
+    self.leading_comments(usize::MAX);
+    self.newline();
+    self.out
+  }
+
+  fn statements(&mut self, statements: &[Stmt], methods: bool) -> Result<(), ParsingError> {
+    for statement in statements {
+      self.newline();
+      self.statement_start = true;
+      match *statement {
+        Stmt::Func(..) if methods => self.function(statement, true)?,
+        _ => self.statement(statement)?,
+      }
+    }
+    self.newline();
+    Ok(())
+  }
+
+  fn statement(&mut self, statement: &Stmt) -> Result<(), ParsingError> {
+    match *statement {
+      Stmt::Expr(ref expr) => {
+        self.expression(expr)?;
+        self.token(TokenType::Semicolon)
+      },
+      Stmt::Print(_, ref expr) => {
+        self.token(TokenType::Print)?;
+        self.space();
+        self.expression(expr)?;
+        self.token(TokenType::Semicolon)
+      },
+      Stmt::Var(_, ref initializer) => {
+        self.token(TokenType::Var)?;
+        self.space();
+        self.token(TokenType::Identifier)?;
+        // `var a;` and `var a = nil;` give the same tree.
+        if self.check(TokenType::Equal) {
+          self.space();
+          self.token(TokenType::Equal)?;
+          self.space();
+          self.expression(initializer)?;
+        }
+        self.token(TokenType::Semicolon)
+      },
+      Stmt::Block(ref statements) if self.check(TokenType::For) => self.for_loop(statements),
+      Stmt::Block(ref statements) => self.block(statements, false),
+      Stmt::If(_, ref condition, ref then_branch, ref else_branch) => {
+        self.token(TokenType::If)?;
+        self.space();
+        self.condition(condition)?;
+        self.body(then_branch)?;
+
+        if let Some(ref else_branch) = **else_branch {
+          match **then_branch {
+            Stmt::Block(_) => self.space(),
+            _ => self.newline(),
+          }
+          self.token(TokenType::Else)?;
+          self.body(else_branch)?;
+        }
+        Ok(())
+      },
+      Stmt::While(_, ref condition, ref body) => {
+        self.token(TokenType::While)?;
+        self.space();
+        self.condition(condition)?;
+        self.body(body)
+      },
+      Stmt::Func(..) => self.function(statement, false),
+      Stmt::Return(_, ref value) => {
+        self.token(TokenType::Return)?;
+        // `return;` and `return nil;` give the same tree.
+        if !self.check(TokenType::Semicolon) {
+          self.space();
+          self.expression(value)?;
+        }
+        self.token(TokenType::Semicolon)
+      },
+      Stmt::Class(_, ref superclass, ref methods) => {
+        self.token(TokenType::Class)?;
+        self.space();
+        self.token(TokenType::Identifier)?;
+        if superclass.is_some() {
+          self.space();
+          self.token(TokenType::Less)?;
+          self.space();
+          self.token(TokenType::Identifier)?;
+        }
+        self.space();
+        self.block(methods, true)
+      },
+    }
+  }
+
+  // the parser turns `for (init; condition; increment) body` into
+  // `{ init; while (condition) { body; increment; } }`.
+  fn for_loop(&mut self, statements: &[Stmt]) -> Result<(), ParsingError> {
+    let (initializer, condition, body, increment) = match *statements {
+      [ref initializer, Stmt::While(_, ref condition, ref body)] => match **body {
+        Stmt::Block(ref body) => match *body.as_slice() {
+          [ref body, Stmt::Expr(ref increment)] => (initializer, condition, body, increment),
+          _ => return Err(self.mismatch("a `for` loop")),
+        },
+        _ => return Err(self.mismatch("a `for` loop")),
+      },
+      _ => return Err(self.mismatch("a `for` loop")),
+    };
+
+    self.token(TokenType::For)?;
+    self.space();
+    self.token(TokenType::LeftParen)?;
+    if self.check(TokenType::Semicolon) {
+      self.token(TokenType::Semicolon)?;
+    } else {
+      self.statement(initializer)?;
+    }
+    if !self.check(TokenType::Semicolon) {
+      self.space();
+      self.expression(condition)?;
+    }
+    self.token(TokenType::Semicolon)?;
+    if !self.check(TokenType::RightParen) {
+      self.space();
+      self.expression(increment)?;
+    }
+    self.token(TokenType::RightParen)?;
+    self.body(body)
+  }
+
+  fn function(&mut self, function: &Stmt, method: bool) -> Result<(), ParsingError> {
+    let (params, body) = match *function {
+      Stmt::Func(_, ref params, ref body) => match **body {
+        Stmt::Block(ref body) => (params, body),
+        _ => return Err(self.mismatch("a function body")),
+      },
+      _ => return Err(self.mismatch("a function")),
+    };
+
+    if !method {
+      self.token(TokenType::Func)?;
+      self.space();
+    }
+    self.token(TokenType::Identifier)?;
+    self.token(TokenType::LeftParen)?;
+    for i in 0..params.len() {
+      if i > 0 {
+        self.token(TokenType::Comma)?;
+        self.space();
+      }
+      self.token(TokenType::Identifier)?;
+    }
+    self.token(TokenType::RightParen)?;
+    self.space();
+    self.block(body, false)
+  }
+
+  fn block(&mut self, statements: &[Stmt], methods: bool) -> Result<(), ParsingError> {
+    self.token(TokenType::LeftBrace)?;
+    if statements.is_empty() && !self.break_line && !self.has_comments_before_token() {
+      return self.token(TokenType::RightBrace);
+    }
+
+    self.indent += 1;
+    self.statements(statements, methods)?;
+    let close = self.tokens.get(self.current).map_or(usize::MAX, |token| token.offset);
+    self.leading_comments(close);
+    self.indent -= 1;
+    self.newline();
+    self.token(TokenType::RightBrace)
+  }
+
+  // `if` and `while` bodies follow their header on the same line.
+  fn body(&mut self, body: &Stmt) -> Result<(), ParsingError> {
+    self.space();
+    self.statement(body)
+  }
+
+  fn condition(&mut self, condition: &Expr) -> Result<(), ParsingError> {
+    self.token(TokenType::LeftParen)?;
+    self.expression(condition)?;
+    self.token(TokenType::RightParen)
+  }
+
+  fn expression(&mut self, expression: &Expr) -> Result<(), ParsingError> {
+    match *expression {
+      Expr::Literal(ref literal) => self.token(match *literal {
+        Literal::Number(_) => TokenType::Number,
+        Literal::String(_) => TokenType::String,
+        Literal::Bool(true) => TokenType::True,
+        Literal::Bool(false) => TokenType::False,
+        Literal::Nil => TokenType::Nil,
+      }),
+      Expr::Var(..) => self.token(TokenType::Identifier),
+      Expr::This(..) => self.token(TokenType::This),
+      Expr::Super(..) => {
+        self.token(TokenType::Super)?;
+        self.token(TokenType::Dot)?;
+        self.token(TokenType::Identifier)
+      },
+      Expr::Assign(_, ref value, _) => {
+        self.token(TokenType::Identifier)?;
+        self.assigned(value)
+      },
+      Expr::Binary(ref left, ref operator, ref right) | Expr::Logical(ref left, ref operator, ref right) => {
+        self.expression(left)?;
+        self.space();
+        self.token(operator.token_type.clone())?;
+        self.space();
+        self.expression(right)
+      },
+      Expr::Unary(ref operator, ref right) => {
+        self.token(operator.token_type.clone())?;
+        self.expression(right)
+      },
+      Expr::Grouping(ref expression) => {
+        self.token(TokenType::LeftParen)?;
+        self.expression(expression)?;
+        self.token(TokenType::RightParen)
+      },
+      Expr::Call(ref callee, _, ref arguments) => {
+        self.expression(callee)?;
+        self.token(TokenType::LeftParen)?;
+        for (i, argument) in arguments.iter().enumerate() {
+          if i > 0 {
+            self.token(TokenType::Comma)?;
+            self.space();
+          }
+          self.expression(argument)?;
+        }
+        self.token(TokenType::RightParen)
+      },
+      Expr::Get(ref object, _) => {
+        self.expression(object)?;
+        self.token(TokenType::Dot)?;
+        self.token(TokenType::Identifier)
+      },
+      Expr::Set(ref object, _, ref value) => {
+        self.expression(object)?;
+        self.token(TokenType::Dot)?;
+        self.token(TokenType::Identifier)?;
+        self.assigned(value)
+      },
+    }
+  }
+
+  fn assigned(&mut self, value: &Expr) -> Result<(), ParsingError> {
+    self.space();
+    self.token(TokenType::Equal)?;
+    self.space();
+    self.expression(value)
+  }
+
+  // writes the next source token, which has to be the one the tree says comes next,
+  // along with the comments around it.
+  fn token(&mut self, expected: TokenType) -> Result<(), ParsingError> {
+    let tokens = self.tokens;
+    let token = match tokens.get(self.current) {
+      Some(token) if token.token_type == expected => token,
+      _ => return Err(self.mismatch(&format!("{:?}", expected))),
+    };
+
+    self.leading_comments(token.offset);
+    if self.statement_start && self.line_start && self.gap(start_line(token)) {
+      self.blank_line();
+    }
+
+    self.write(&token.lexeme);
+    self.statement_start = false;
+    self.block_start = expected == TokenType::LeftBrace;
+    self.last_line = token.line;
+    self.current += 1;
+    self.trailing_comments();
+    Ok(())
+  }
+
+  fn check(&self, token_type: TokenType) -> bool {
+    self.tokens.get(self.current).is_some_and(|token| token.token_type == token_type)
+  }
+
+  // comments on a line of their own, before `offset`.
+  fn leading_comments(&mut self, offset: usize) {
+    let comments = self.comments;
+    // between the tokens of a statement, the comments and the rest are indented as a continuation.
+    let inside = !self.line_start;
+    while let Some(comment) = comments.get(self.comment).filter(|comment| comment.offset < offset) {
+      self.newline();
+      self.continuation = inside;
+      if self.gap(comment.line) {
+        self.blank_line();
+      }
+      self.write(comment.lexeme.trim_end());
+      self.newline();
+      self.block_start = false;
+      self.last_line = comment.line + newlines(&comment.lexeme);
+      self.comment += 1;
+      self.continuation = inside;
+    }
+  }
+
+  // comments on the same line as the token just written.
+  fn trailing_comments(&mut self) {
+    let next = self.tokens.get(self.current).map_or(usize::MAX, |token| token.offset);
+    let comments = self.comments;
+    while let Some(comment) = comments.get(self.comment).filter(|comment| comment.offset < next && comment.line == self.last_line) {
+      self.out.push(' ');
+      self.out.push_str(comment.lexeme.trim_end());
+      self.last_line = comment.line + newlines(&comment.lexeme);
+      self.break_line = comment.lexeme.starts_with("//");
+      self.comment += 1;
+    }
+  }
+
+  fn has_comments_before_token(&self) -> bool {
+    match (self.comments.get(self.comment), self.tokens.get(self.current)) {
+      (Some(comment), Some(token)) => comment.offset < token.offset,
+      _ => false,
+    }
+  }
+
+  // a blank line in the source between the last thing written and `line`.
+  fn gap(&self, line: i32) -> bool {
+    !self.block_start && self.last_line > 0 && line > self.last_line + 1
+  }
+
+  fn write(&mut self, text: &str) {
+    if self.break_line {
+      self.newline();
+      self.continuation = true;
+    }
+    if self.line_start {
+      let depth = self.indent + if self.continuation { 1 } else { 0 };
+      self.out.push_str(&INDENT.repeat(depth));
+      self.line_start = false;
+    }
+    self.out.push_str(text);
+  }
+
+  fn space(&mut self) {
+    if !self.line_start && !self.break_line {
+      self.out.push(' ');
+    }
+  }
+
+  fn newline(&mut self) {
+    if !self.line_start {
+      let trimmed = self.out.trim_end_matches(' ').len();
+      self.out.truncate(trimmed);
+      self.out.push('\n');
+      self.line_start = true;
+    }
+    self.break_line = false;
+    self.continuation = false;
+  }
+
+  fn blank_line(&mut self) {
+    self.newline();
+    if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+      self.out.push('\n');
+    }
+  }
+
+  fn mismatch(&self, expected: &str) -> ParsingError {
+    let found = self.tokens.get(self.current).map_or(String::from("the end"), |token| format!("'{}' on line {}", token.lexeme, token.line));
+    ParsingError::InternalError(format!("formatter expected {} but found {}.", expected, found))
+  }
+}
+
+fn newlines(text: &str) -> i32 {
+  text.matches('\n').count() as i32
+}
+
+// tokens carry the line they end on, a string may span several.
+fn start_line(token: &Token) -> i32 {
+  token.line - newlines(&token.lexeme)
+}
@@ -3,37 +3,130 @@ use std::io;
 use std::cell::RefCell;
 use std::error::{Error};
 use std::collections::HashMap;
+use std::time::Instant;
 
-use super::expr::Expr;
+use super::expr::{Expr, ExprVisitor};
 use super::token_type::TokenType;
 use super::token::{Literal, Token};
 use super::worng_value::{WorngValue};
-use super::statement::Stmt;
+use super::statement::{Stmt, StmtVisitor};
 use super::error::{WorngError, ParsingError, RuntimeError };
 use super::worng_function::WorngFunction;
 use super::worng_class::WorngClass;
+use super::worng_instance::WorngInstance;
 use super::environment::Environment;
+use super::native_fs::FsAccess;
+use super::native_random::Rng;
+use super::debugger::{self, DebugHook};
+use super::profiler::Profiler;
+use super::coverage::Hits;
+use super::{native_string, native_list, native_map, native_regex};
+
+// statements and expressions being evaluated at once, each of them takes a stack frame of the host.
+// a script recursing deeper than this gets a runtime error instead of crashing it,
+// as long as it runs inside `with_stack`.
+pub const MAX_DEPTH: usize = 5000;
+
+// enough for `MAX_DEPTH` in a debug build, far more than a thread gets by default.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// runs `run` on a thread with a stack big enough for the interpreter's limits, the `worng`
+/// binary and `run_string` go through it.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn with_stack<T: Send + 'static>(run: impl FnOnce() -> T + Send + 'static) -> T {
+  std::thread::Builder::new()
+    .stack_size(STACK_SIZE)
+    .spawn(run)
+    .expect("Couldn't spawn the interpreter thread")
+    .join()
+    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+/// wasm has no threads, its stack is the one set when linking.
+#[cfg(target_arch = "wasm32")]
+pub fn with_stack<T: Send + 'static>(run: impl FnOnce() -> T + Send + 'static) -> T {
+  run()
+}
+
+/// a function being run: where it was called and the scope of the caller, for the debugger's stack.
+pub struct Call {
+  pub name: String,
+  pub line: i32,
+  pub environment: Rc<RefCell<Environment>>,
+}
 
 pub struct Interpreter<'a>{
   pub globals: Rc<RefCell<Environment>>,
   environment: Rc<RefCell<Environment>>,
   locals: HashMap<Expr, usize>,
   writer: Rc<RefCell<&'a mut io::Write>>,
+  reader: Rc<RefCell<&'a mut dyn io::BufRead>>,
+  pub args: Vec<String>,
+  pub fs_access: FsAccess,
+  pub rng: Rng,
+  pub started: Instant,
+  depth: usize,
+  // innermost last.
+  calls: Vec<Call>,
+  /// asked before every statement, `worng debug` and `worng dap` pause the program in it.
+  pub debug_hook: Option<Box<dyn DebugHook + 'a>>,
+  /// `--profile`, times the calls and counts the statements of each line.
+  pub profiler: Option<Profiler>,
+  /// `--coverage`, the lines and branches that ran.
+  pub coverage: Option<Hits>,
 }
 
 impl<'a> Interpreter<'a> {
 
-  pub fn new(writer: Rc<RefCell<&'a mut io::Write>>) -> Self{
+  pub fn new(writer: Rc<RefCell<&'a mut io::Write>>, reader: Rc<RefCell<&'a mut dyn io::BufRead>>) -> Self{
     let globals = Rc::new(RefCell::new(Environment::global()));
 
     Interpreter{
       globals: globals.clone(),
       environment: globals.clone(),
       locals: HashMap::new(),
-      writer: writer
+      writer,
+      reader,
+      args: Vec::new(),
+      fs_access: FsAccess::Denied,
+      rng: Rng::from_time(),
+      started: Instant::now(),
+      depth: 0,
+      calls: Vec::new(),
+      debug_hook: None,
+      profiler: None,
+      coverage: None
     }
   }
 
+  pub fn write(&mut self, text: &str) -> io::Result<()> {
+    let mut writer = self.writer.borrow_mut();
+    writer.write_all(text.as_bytes())?;
+    writer.flush()
+  }
+
+  /// the next line without its line ending, `None` at the end of input.
+  pub fn read_line(&mut self) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if self.reader.borrow_mut().read_line(&mut line)? == 0 {
+      return Ok(None);
+    }
+
+    if line.ends_with('\n') {
+      line.pop();
+      if line.ends_with('\r') {
+        line.pop();
+      }
+    }
+    Ok(Some(line))
+  }
+
+  pub fn read_all(&mut self) -> io::Result<String> {
+    let mut contents = String::new();
+    self.reader.borrow_mut().read_to_string(&mut contents)?;
+    Ok(contents)
+  }
+
   pub fn check_number_operand(&self, _operator: &Token, operand: &WorngValue) -> Result<(), RuntimeError> {
     match operand {
       WorngValue::Number(value) => { return Ok(()) },
@@ -42,169 +135,45 @@ impl<'a> Interpreter<'a> {
   }
 
   pub fn interpret(&mut self, statement: Vec<Stmt>) -> Option<RuntimeError>{
+    let mut error = None;
     for stmt in statement.iter() {
       if let Err(err) = self.interpret_statement(stmt) {
-        return Some(err);
+        error = Some(err);
+        break;
       }
     }
-    None
+
+    // the host may exit right after `os.exit()`, nothing written so far should get lost.
+    let _ = self.writer.borrow_mut().flush();
+    error
   }
 
   pub fn interpret_statement(&mut self, statement: &Stmt) -> Result<Option<WorngValue>, RuntimeError> {
-    match *statement {
-      Stmt::Print(ref expr) => self.interpret_expression(expr).map(|val| {
-        self.writer
-            .borrow_mut()
-            .write_all(format!("{}\n", val).as_ref())
-            .expect("Error writing to stdout/writer");
-        None
-    }),
-      Stmt::Expr(ref expr) => { self.interpret_expression(expr).map(|_| None) },
-      Stmt::Var(ref token, ref expr) => self.interpret_expression(expr).map(|value| {
-        self.environment.borrow_mut().define(token.lexeme.clone(), value);
-        None
-      }),
-      Stmt::If(ref condition, ref then_branch, ref else_branch) => {
-        self.interpret_expression(condition).and_then(|condition_result| {
-          if condition_result.is_truthy() {
-            self.interpret_statement(then_branch)
-          } else if let Some(ref else_branch) = **else_branch {
-            self.interpret_statement(else_branch)
-          } else {
-            Ok(None)
-          }
-        })
+    // taken out while it runs, so what it evaluates in a paused program doesn't pause again.
+    if let Some(mut hook) = self.debug_hook.take() {
+      let result = hook.before(self, statement);
+      self.debug_hook = Some(hook);
+      result?;
     }
-      Stmt::While(ref condition, ref body) => {
-        while self.interpret_expression(condition)?.is_truthy() {
-          self.interpret_statement(body)?;
-        }
-
-        Ok(None)
-      },
-      Stmt::Block(ref statements) => {
-        let env = Environment::enclose(self.environment.clone());
-        self.interpret_block(statements, RefCell::new(env))
-      },
-      Stmt::Func(ref name, ref params, ref body) => {
-        let function = WorngValue::Func(Rc::new(WorngFunction::new(statement.clone(), self.environment.clone(), false ) ) );
-        self.environment.borrow_mut().define(name.clone().lexeme, function);
-        return Ok(None);
-      },
-      Stmt::Return(_, ref expr) => { 
-        Ok(Some(self.interpret_expression(expr)?)) 
-      },
-      Stmt::Class(ref token, ref superclass,  ref method_statements) => {
-        let mut methods = HashMap::new();
-        let mut parent_env = None;
-
-        // let mut _superclass = None;
-        // let mut resolved_superclass = None;
-
-        let resolved_superclass = if let &Some(ref superclass) = superclass {
-          let superclass = match self.interpret_expression(superclass)? {
-              WorngValue::Class(ref class) => class.clone(),
-              _ => return Err(RuntimeError::InvalidSuperclass(token.clone())),
-          };
-
-          parent_env = Some(self.environment.clone());
-          let mut env = Environment::enclose(self.environment.clone());
-          env.define("super".to_string(), WorngValue::Class(superclass.clone()));
-          self.environment = Rc::new(RefCell::new(env));
-
-          Some(superclass)
-        } else {
-            None
-        };
-        
-        // if let Some(_super) = superclass {
-        //   _superclass = self.interpret_expression(_super).ok();
-        //   if let WorngValue::Class(ref klass) = _superclass.unwrap() {
-        //     resolved_superclass = Some(klass.clone());
-
-        //     parent_env = Some(self.environment.clone());
-        //     let mut env = Environment::enclose(self.environment.clone());
-        //     env.define("super".to_string(), WorngValue::Class(klass.clone()));
-        //     self.environment = Rc::new(RefCell::new(env));
-
-        //     Some(superclass)
-        //   } else {
-        //     return Err(RuntimeError::InvalidSuperclass(token.clone()))
-        //   }
-        // }
-    
-        for method_statement in method_statements {
-            match method_statement {
-                &Stmt::Func(ref name, _, _) => {
-                    let method = WorngValue::Func(Rc::new(WorngFunction::new(
-                        method_statement.clone(),
-                        self.environment.clone(),
-                        name.lexeme == "init"
-                    )));
-                    methods.insert(name.lexeme.clone(), method);
-                }
-                _ => {
-                    return Err(RuntimeError::InternalError(
-                        "Found a non Stmt::Func as a method of a class".to_string(),
-                    ))
-                }
-            };
-        }
-
-        let class = WorngValue::Class(Rc::new(WorngClass::new(
-            token.lexeme.clone(),
-            methods,
-            resolved_superclass
-        )));
-
-        if superclass.is_some() {
-          self.environment = parent_env.expect("When interpreting a subclass, a parent environment should always be present");
-        }
-
-        self.environment.borrow_mut().define(token.lexeme.clone(), class);
-
-        Ok(None)
-      },
-      // Stmt::Class(ref name, ref methods ) => {
-      //   self.environment.borrow_mut().define(name.lexeme.clone(), WorngValue::Nil);
-      //   let mut methods_statements = HashMap::new();
-
-      //   for method_stmt in methods {
-      //     match method_stmt {
-      //       &Stmt::Func(ref name, _, _) => {
-      //           let _method = WorngValue::Func(Rc::new(WorngFunction::new(
-      //             method_stmt.clone(),
-      //               self.environment.clone(),
-      //               // name.lexeme == "init",
-      //           )));
-      //           methods_statements.insert(name.lexeme.clone(), _method);
-      //       }
-      //       _ => {
-      //         return Err(RuntimeError::InternalError(
-      //           "Found a non Stmt::Func as a method of a class".to_string(),
-      //         ))
-      //       }
-      //     };
-      //   };
-
-        
-      //   let klass = WorngValue::Class(Rc::new(WorngClass::new(
-      //     name.lexeme.clone(),
-      //     methods_statements,
-      //   )));
-        
-      //   self.environment.borrow_mut().assign(&name.lexeme, klass ).expect("class assign error");
-      //   return Ok(None);
-      // }
+    if self.profiler.is_some() || self.coverage.is_some() {
+      if let Some(line) = debugger::statement_line(statement) {
+        self.profiler.iter_mut().for_each(|profiler| profiler.hit(line));
+        self.coverage.iter_mut().for_each(|coverage| coverage.line(line));
+      }
     }
+
+    self.depth += 1;
+    let result = self.visit_stmt(statement);
+    self.depth -= 1;
+    result
   }
 
-  pub fn interpret_block( &mut self, statements: &Vec<Stmt>, _environment: RefCell<Environment>) -> Result<Option<WorngValue>, RuntimeError> {
+  pub fn interpret_block( &mut self, statements: &[Stmt], _environment: RefCell<Environment>) -> Result<Option<WorngValue>, RuntimeError> {
     let mut return_value = None;
     let previous = self.environment.clone();
     self.environment = Rc::new(_environment);
 
-    for ref stmt in statements {
+    for stmt in statements {
       return_value = self.interpret_statement(stmt)?;
 
       if return_value.is_some() {
@@ -216,174 +185,414 @@ impl<'a> Interpreter<'a> {
     Ok(return_value)
   }
 
+  /// evaluates `expression` as if it was written where `environment` is the scope.
+  pub fn evaluate_in(&mut self, expression: &Expr, environment: Rc<RefCell<Environment>>) -> Result<WorngValue, RuntimeError> {
+    let previous = std::mem::replace(&mut self.environment, environment);
+    let result = self.interpret_expression(expression);
+    self.environment = previous;
+    result
+  }
+
+  fn cover_branch(&mut self, token: &Token, first: bool) {
+    if let Some(ref mut coverage) = self.coverage {
+      coverage.branch(token, first);
+    }
+  }
+
+  pub fn environment(&self) -> Rc<RefCell<Environment>> {
+    self.environment.clone()
+  }
+
+  /// the functions being run, innermost last. `WorngFunction::call` keeps them.
+  pub fn calls(&self) -> &[Call] {
+    &self.calls
+  }
+
+  pub fn push_call(&mut self, name: &Token, paren: &Token) {
+    self.calls.push(Call { name: name.lexeme.clone(), line: paren.line, environment: self.environment.clone() });
+    if let Some(ref mut profiler) = self.profiler {
+      profiler.enter(name);
+    }
+  }
+
+  pub fn pop_call(&mut self) {
+    self.calls.pop();
+    if let Some(ref mut profiler) = self.profiler {
+      profiler.leave();
+    }
+  }
+
   pub fn interpret_expression(&mut self, expression: &Expr) -> Result<WorngValue, RuntimeError>  {
-    match *expression {
-      Expr::Literal(ref literal) => {
-        if let Some(value) = literal.value() {
-          Ok(value)
-        } else {
-          Err(RuntimeError::InternalError("Invalid literal - no value".to_string()))
-        }
-      },
-      Expr::Binary(ref left, ref operator, ref right) => {
-        let l = self.interpret_expression(left)?;
-        let r = self.interpret_expression(right)?;
-
-        match operator.token_type {
-          TokenType::Minus => return l.subtract(r) .map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
-          TokenType::Slash => return l.divide(r).map_err(|_| RuntimeError::DivideByZero(operator.clone())),
-          TokenType::Star => return l.multiply(r).map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
-          TokenType::Plus => return l.add(r).map_err(|_| RuntimeError::AddNonNumbers(operator.clone())),
-          TokenType::Greater => return l.greater_than(r).map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
-          TokenType::GreaterEqual => return l.greater_equal(r).map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
-          TokenType::Less => return l.less_than(r).map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
-          TokenType::LessEqual => return l.less_equal(r).map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
-          TokenType::BangEqual => return l.bang_equal(r).map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
-          TokenType::EqualEqual => return l.equal_equal(r).map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
-          _ => return Err(RuntimeError::InternalError("operator not support".to_string()))
+    self.depth += 1;
+    let result = self.visit_expr(expression);
+    self.depth -= 1;
+    result
+  }
+}
+
+// `interpret_statement` and `interpret_expression` count the depth and call the hooks
+// around these.
+impl<'a> StmtVisitor<Result<Option<WorngValue>, RuntimeError>, Result<WorngValue, RuntimeError>> for Interpreter<'a> {
+  fn visit_print_stmt(&mut self, _keyword: &Token, expr: &Expr) -> Result<Option<WorngValue>, RuntimeError> {
+    self.interpret_expression(expr).map(|val| {
+      self.writer
+          .borrow_mut()
+          .write_all(format!("{}\n", val).as_ref())
+          .expect("Error writing to stdout/writer");
+      None
+    })
+  }
+
+  fn visit_expr_stmt(&mut self, expr: &Expr) -> Result<Option<WorngValue>, RuntimeError> {
+    self.interpret_expression(expr).map(|_| None)
+  }
+
+  fn visit_var_stmt(&mut self, token: &Token, expr: &Expr) -> Result<Option<WorngValue>, RuntimeError> {
+    self.interpret_expression(expr).map(|value| {
+      self.environment.borrow_mut().define(token.lexeme.clone(), value);
+      None
+    })
+  }
+
+  fn visit_if_stmt(&mut self, keyword: &Token, condition: &Expr, then_branch: &Stmt, else_branch: &Option<Stmt>) -> Result<Option<WorngValue>, RuntimeError> {
+    self.interpret_expression(condition).and_then(|condition_result| {
+      self.cover_branch(keyword, condition_result.is_truthy());
+      if condition_result.is_truthy() {
+        self.interpret_statement(then_branch)
+      } else if let Some(ref else_branch) = *else_branch {
+        self.interpret_statement(else_branch)
+      } else {
+        Ok(None)
+      }
+    })
+  }
+
+  fn visit_while_stmt(&mut self, keyword: &Token, condition: &Expr, body: &Stmt) -> Result<Option<WorngValue>, RuntimeError> {
+    loop {
+      let truthy = self.interpret_expression(condition)?.is_truthy();
+      self.cover_branch(keyword, truthy);
+      if !truthy {
+        break;
+      }
+      self.interpret_statement(body)?;
+    }
+
+    Ok(None)
+  }
+
+  fn visit_block_stmt(&mut self, statements: &[Stmt]) -> Result<Option<WorngValue>, RuntimeError> {
+    let env = Environment::enclose(self.environment.clone());
+    self.interpret_block(statements, RefCell::new(env))
+  }
+
+  fn visit_func_stmt(&mut self, name: &Token, params: &[Token], body: &Stmt) -> Result<Option<WorngValue>, RuntimeError> {
+    let declaration = Stmt::Func(name.clone(), params.to_vec(), Box::new(body.clone()));
+    let function = WorngValue::Func(Rc::new(WorngFunction::new(declaration, self.environment.clone(), false ) ) );
+    self.environment.borrow_mut().define(name.clone().lexeme, function);
+    Ok(None)
+  }
+
+  fn visit_return_stmt(&mut self, _keyword: &Token, expr: &Expr) -> Result<Option<WorngValue>, RuntimeError> {
+    Ok(Some(self.interpret_expression(expr)?))
+  }
+
+  fn visit_class_stmt(&mut self, token: &Token, superclass: &Option<Expr>, method_statements: &[Stmt]) -> Result<Option<WorngValue>, RuntimeError> {
+    let mut methods = HashMap::new();
+    let mut parent_env = None;
+
+    // let mut _superclass = None;
+    // let mut resolved_superclass = None;
+
+    let resolved_superclass = if let Some(ref superclass) = *superclass {
+      let superclass = match self.interpret_expression(superclass)? {
+          WorngValue::Class(ref class) => class.clone(),
+          _ => return Err(RuntimeError::InvalidSuperclass(token.clone())),
+      };
+
+      parent_env = Some(self.environment.clone());
+      let mut env = Environment::enclose(self.environment.clone());
+      env.define("super".to_string(), WorngValue::Class(superclass.clone()));
+      self.environment = Rc::new(RefCell::new(env));
+
+      Some(superclass)
+    } else {
+        None
+    };
+    
+    // if let Some(_super) = superclass {
+    //   _superclass = self.interpret_expression(_super).ok();
+    //   if let WorngValue::Class(ref klass) = _superclass.unwrap() {
+    //     resolved_superclass = Some(klass.clone());
+
+    //     parent_env = Some(self.environment.clone());
+    //     let mut env = Environment::enclose(self.environment.clone());
+    //     env.define("super".to_string(), WorngValue::Class(klass.clone()));
+    //     self.environment = Rc::new(RefCell::new(env));
+
+    //     Some(superclass)
+    //   } else {
+    //     return Err(RuntimeError::InvalidSuperclass(token.clone()))
+    //   }
+    // }
+
+    for method_statement in method_statements {
+        match *method_statement {
+            Stmt::Func(ref name, _, _) => {
+                let method = WorngValue::Func(Rc::new(WorngFunction::new(
+                    method_statement.clone(),
+                    self.environment.clone(),
+                    name.lexeme == "init"
+                )));
+                methods.insert(name.lexeme.clone(), method);
+            }
+            _ => {
+                return Err(RuntimeError::InternalError(
+                    "Found a non Stmt::Func as a method of a class".to_string(),
+                ))
+            }
         };
+    }
+
+    let class = WorngValue::Class(Rc::new(WorngClass::new(
+        token.lexeme.clone(),
+        methods,
+        resolved_superclass
+    )));
+
+    if superclass.is_some() {
+      self.environment = parent_env.expect("When interpreting a subclass, a parent environment should always be present");
+    }
+
+    self.environment.borrow_mut().define(token.lexeme.clone(), class);
+
+    Ok(None)
+  }
+  // Stmt::Class(ref name, ref methods ) => {
+  //   self.environment.borrow_mut().define(name.lexeme.clone(), WorngValue::Nil);
+  //   let mut methods_statements = HashMap::new();
+
+  //   for method_stmt in methods {
+  //     match method_stmt {
+  //       &Stmt::Func(ref name, _, _) => {
+  //           let _method = WorngValue::Func(Rc::new(WorngFunction::new(
+  //             method_stmt.clone(),
+  //               self.environment.clone(),
+  //               // name.lexeme == "init",
+  //           )));
+  //           methods_statements.insert(name.lexeme.clone(), _method);
+  //       }
+  //       _ => {
+  //         return Err(RuntimeError::InternalError(
+  //           "Found a non Stmt::Func as a method of a class".to_string(),
+  //         ))
+  //       }
+  //     };
+  //   };
+
+    
+  //   let klass = WorngValue::Class(Rc::new(WorngClass::new(
+  //     name.lexeme.clone(),
+  //     methods_statements,
+  //   )));
+    
+  //   self.environment.borrow_mut().assign(&name.lexeme, klass ).expect("class assign error");
+  //   return Ok(None);
+  // }
+}
+
+impl<'a> ExprVisitor<Result<WorngValue, RuntimeError>> for Interpreter<'a> {
+  fn visit_literal_expr(&mut self, literal: &Literal) -> Result<WorngValue, RuntimeError> {
+    if let Some(value) = literal.value() {
+      Ok(value)
+    } else {
+      Err(RuntimeError::InternalError("Invalid literal - no value".to_string()))
+    }
+  }
+
+  fn visit_binary_expr(&mut self, left: &Expr, operator: &Token, right: &Expr) -> Result<WorngValue, RuntimeError> {
+    let l = self.interpret_expression(left)?;
+    let r = self.interpret_expression(right)?;
+
+    match operator.token_type {
+      TokenType::Minus => l.subtract(r) .map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
+      TokenType::Slash => match (&l, &r) {
+        (WorngValue::Number(_), WorngValue::Number(divisor)) if *divisor == 0.0 => Err(RuntimeError::DivideByZero(operator.clone())),
+        _ => l.divide(r).map_err(|_| RuntimeError::DivideInvalidType),
       },
-      Expr::Unary( ref operator,ref right ) => {
-        let r = self.interpret_expression(right)?;
-        match operator.token_type {
-          TokenType::Minus => { 
-            match self.check_number_operand(operator, &r){
-              Ok(()) => return r.negate_number().map_err(|_| RuntimeError::RuntimeError(operator.clone())),
-              Err(e) => Err(e)
-            }
-          },
-          TokenType::Bang => return r.negate().map_err(|_| RuntimeError::RuntimeError(operator.clone())),
-          _ => return Err(RuntimeError::InternalError("invalid operator for unary.".to_string()))
+      TokenType::Star => l.multiply(r).map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
+      TokenType::Plus => l.add(r).map_err(|_| RuntimeError::AddNonNumbers(operator.clone())),
+      TokenType::Greater => l.greater_than(r).map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
+      TokenType::GreaterEqual => l.greater_equal(r).map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
+      TokenType::Less => l.less_than(r).map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
+      TokenType::LessEqual => l.less_equal(r).map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
+      TokenType::BangEqual => l.bang_equal(r).map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
+      TokenType::EqualEqual => l.equal_equal(r).map_err(|_| RuntimeError::SubtractNonNumbers(operator.clone())),
+      _ => Err(RuntimeError::InternalError("operator not support".to_string()))
+    }
+  }
+
+  fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> Result<WorngValue, RuntimeError> {
+    let r = self.interpret_expression(right)?;
+    match operator.token_type {
+      TokenType::Minus => { 
+        match self.check_number_operand(operator, &r){
+          Ok(()) => r.negate_number().map_err(|_| RuntimeError::RuntimeError(operator.clone())),
+          Err(e) => Err(e)
         }
       },
-      Expr::Var(ref token, ref distance) => match distance {
-        &Some(distance) => match self.environment.borrow().get_at(distance, &token.lexeme) {
-            Ok(value) => Ok(value.clone()),
-            Err(_) => Err(RuntimeError::UndefinedVariable(token.clone())),
-        },
-        &None => match self.globals.borrow().get_value(&token.lexeme) {
-            Ok(value) => Ok(value.clone()),
-            Err(_) => Err(RuntimeError::UndefinedVariable(token.clone())),
-        },
+      TokenType::Bang => r.negate().map_err(|_| RuntimeError::RuntimeError(operator.clone())),
+      _ => Err(RuntimeError::InternalError("invalid operator for unary.".to_string()))
+    }
+  }
+
+  fn visit_var_expr(&mut self, token: &Token, distance: &Option<usize>) -> Result<WorngValue, RuntimeError> {
+    match *distance {
+      Some(distance) => match self.environment.borrow().get_at(distance, &token.lexeme) {
+          Ok(value) => Ok(value.clone()),
+          Err(_) => Err(RuntimeError::UndefinedVariable(token.clone())),
       },
-      Expr::Assign(ref token, ref expr, ref distance) => {
-        let value = self.interpret_expression(expr)?;
-
-        match distance {
-          &Some(d) => match self.environment.borrow_mut().assign_at(d, &token, value.clone()) {
-            Ok(()) => Ok(value.clone()),
-            Err(_) => Err(RuntimeError::UndefinedVariable(token.clone())),
-          },
-          &None => match self.globals.borrow_mut().assign(&token.lexeme, value.clone()){
-            Ok(()) => Ok(value.clone()),
-            Err(_) => Err(RuntimeError::UndefinedVariable(token.clone())),
-          }
-        }
+      None => match self.globals.borrow().get_value(&token.lexeme) {
+          Ok(value) => Ok(value.clone()),
+          Err(_) => Err(RuntimeError::UndefinedVariable(token.clone())),
       },
-      Expr::Logical(ref left, ref token, ref right) => {
-        let left = self.interpret_expression(left)?;
+    }
+  }
 
-        if token.token_type == TokenType::Or {
-          if left.is_truthy() { return Ok(left) };
-        } else {
-          if !left.is_truthy() { return Ok(left) };
-        }
+  fn visit_assign_expr(&mut self, token: &Token, expr: &Expr, distance: &Option<usize>) -> Result<WorngValue, RuntimeError> {
+    let value = self.interpret_expression(expr)?;
 
-        return self.interpret_expression(right);  
+    match *distance {
+      Some(d) => match self.environment.borrow_mut().assign_at(d, token, value.clone()) {
+        Ok(()) => Ok(value.clone()),
+        Err(_) => Err(RuntimeError::UndefinedVariable(token.clone())),
       },
-      Expr::Grouping(ref expr) => {
-        self.interpret_expression(&expr)
-      },
-      Expr::Call(ref call, ref paren, ref arguments) => { 
-        let function = self.interpret_expression(call)?
-                          .get_callable()
-                          .ok_or_else(|| RuntimeError::CallOnNonCallable(paren.clone()))?;
-
-        let mut _arguments = Vec::new();
-        for argument in arguments { 
-          _arguments.push(self.interpret_expression(argument)?);
-        }
+      None => match self.globals.borrow_mut().assign(&token.lexeme, value.clone()){
+        Ok(()) => Ok(value.clone()),
+        Err(_) => Err(RuntimeError::UndefinedVariable(token.clone())),
+      }
+    }
+  }
 
-        if arguments.len() != function.arity() {
-          return Err(RuntimeError::ArityError(function.arity(), arguments.len())); 
-        }
+  fn visit_logical_expr(&mut self, left: &Expr, token: &Token, right: &Expr) -> Result<WorngValue, RuntimeError> {
+    let left = self.interpret_expression(left)?;
 
-        return function.call(self, _arguments);
-      },
-      Expr::Get(ref target, ref token) => {
-        let resolved_target = self.interpret_expression(target)?;
+    let short_circuits = if token.token_type == TokenType::Or { left.is_truthy() } else { !left.is_truthy() };
+    self.cover_branch(token, short_circuits);
+    if short_circuits {
+      return Ok(left);
+    }
+
+    self.interpret_expression(right)
+  }
+
+  fn visit_grouping_expr(&mut self, expr: &Expr) -> Result<WorngValue, RuntimeError> {
+    self.interpret_expression(expr)
+  }
+
+  fn visit_call_expr(&mut self, call: &Expr, paren: &Token, arguments: &[Expr]) -> Result<WorngValue, RuntimeError> {
+    let function = self.interpret_expression(call)?
+                      .get_callable()
+                      .ok_or_else(|| RuntimeError::CallOnNonCallable(paren.clone()))?;
+
+    let mut _arguments = Vec::new();
+    for argument in arguments { 
+      _arguments.push(self.interpret_expression(argument)?);
+    }
+
+    if arguments.len() != function.arity() {
+      return Err(RuntimeError::ArityError(paren.clone(), function.arity(), arguments.len())); 
+    }
+
+    // only calls can nest without bound, the parser limits everything else.
+    if self.depth > MAX_DEPTH {
+      return Err(RuntimeError::StackOverflow(paren.clone(), MAX_DEPTH));
+    }
+
+    function.call(self, paren, _arguments)
+  }
+
+  fn visit_get_expr(&mut self, target: &Expr, token: &Token) -> Result<WorngValue, RuntimeError> {
+    let resolved_target = self.interpret_expression(target)?;
+
+    match resolved_target {
+        WorngValue::Instance(ref instance) => WorngInstance::get(instance, token),
+        WorngValue::Module(ref module) => module.get(token),
+        WorngValue::String(_) => native_string::get(&resolved_target, token),
+        WorngValue::List(_) => native_list::get(&resolved_target, token),
+        WorngValue::Map(_) => native_map::get(&resolved_target, token),
+        WorngValue::Regex(_) => native_regex::get(&resolved_target, token),
+        _ => Err(RuntimeError::InvalidGetTarget(token.clone())),
+    }
+  }
+
+  fn visit_set_expr(&mut self, target: &Expr, token: &Token, expr: &Expr) -> Result<WorngValue, RuntimeError> {
+    let resolved_target = self.interpret_expression(target)?;
 
-        match resolved_target {
-            WorngValue::Instance(ref instance) => Ok(instance.borrow().get(&token)?.clone()),
-            _ => Err(RuntimeError::InvalidGetTarget(token.clone())),
+    let value = match resolved_target {
+        WorngValue::Instance(instance) => {
+            let resolved_value = self.interpret_expression(expr)?;
+            instance
+                .borrow_mut()
+                .set(token.clone(), resolved_value.clone());
+            resolved_value.clone()
         }
-      }
-    Expr::Set(ref target, ref token, ref expr) => {
-        let resolved_target = self.interpret_expression(target)?;
-
-        let value = match resolved_target {
-            WorngValue::Instance(instance) => {
-                let resolved_value = self.interpret_expression(expr)?;
-                instance
-                    .borrow_mut()
-                    .set(token.clone(), resolved_value.clone());
-                resolved_value.clone()
+        _ => return Err(RuntimeError::InvalidGetTarget(token.clone())),
+    };
+
+    Ok(value)
+  }
+
+  fn visit_super_expr(&mut self, _keyword: &Token, method: &Token, distance: &Option<usize>) -> Result<WorngValue, RuntimeError> {
+    match *distance {
+      Some(distance) => {
+        let superclass = self.environment
+            .borrow()
+            .get_at(distance, &"super".to_string())
+            .expect("Couldn't find `super` when interpreting");
+        let instance = self.environment
+            .borrow()
+            .get_at(distance - 1, &"this".to_string())
+            .expect("Couldn't find `this` when interpreting `super` call");
+
+        let superclass = match superclass {
+            WorngValue::Class(ref class) => class,
+            _ => {
+              return Err(RuntimeError::InternalError(
+                  "Couldn't extract WorngClass from WorngValue::Class".to_string(),
+              ))
             }
-            _ => return Err(RuntimeError::InvalidGetTarget(token.clone())),
         };
 
-        Ok(value)
-      },
-      Expr::Super(_, ref method, ref distance) => match distance {
-        &Some(distance) => {
-          let superclass = self.environment
-              .borrow()
-              .get_at(distance, &"super".to_string())
-              .expect("Couldn't find `super` when interpreting");
-          let instance = self.environment
-              .borrow()
-              .get_at(distance - 1, &"this".to_string())
-              .expect("Couldn't find `this` when interpreting `super` call");
-
-          let superclass = match superclass {
-              WorngValue::Class(ref class) => class,
-              _ => {
-                return Err(RuntimeError::InternalError(
-                    "Couldn't extract WorngClass from WorngValue::Class".to_string(),
-                ))
-              }
-          };
-
-          let instance = match instance {
-              WorngValue::Instance(ref instance) => instance,
-              _ => {
-                return Err(RuntimeError::InternalError(
-                  "Couldn't extract WorngInstance from WorngValue::Instance".to_string(),
-                ))
-              }
-          };
+        let instance = match instance {
+            WorngValue::Instance(ref instance) => instance,
+            _ => {
+              return Err(RuntimeError::InternalError(
+                "Couldn't extract WorngInstance from WorngValue::Instance".to_string(),
+              ))
+            }
+        };
 
-          let resolved_method = superclass.find_method(&method.lexeme, instance.clone());
+        let resolved_method = superclass.find_method(&method.lexeme, instance.clone());
 
-          match resolved_method {
-            Some(method) => Ok(WorngValue::Func(Rc::new(method))),
-            None => Err(RuntimeError::UndefinedProperty(method.clone())),
-          }
+        match resolved_method {
+          Some(method) => Ok(WorngValue::Func(Rc::new(method))),
+          None => Err(RuntimeError::UndefinedProperty(method.clone())),
         }
-        &None => Err(RuntimeError::InternalError( "Couldn't find distance to super reference".to_string())),
-      },
-      Expr::This(ref token, ref distance) => { 
-        match distance {
-        &Some(distance) => match self.environment.borrow().get_at(distance, &token.lexeme) {
-            Ok(value) => Ok(value.clone()),
-            Err(_) => { Err(RuntimeError::UndefinedVariable(token.clone())) },
-        },
-        &None => match self.globals.borrow().get_value(&token.lexeme) {
-            Ok(value) => Ok(value.clone()),
-            Err(_) => Err(RuntimeError::UndefinedVariable(token.clone())),
-        },
-      }
       }
+      None => Err(RuntimeError::InternalError( "Couldn't find distance to super reference".to_string())),
+    }
+  }
+
+  fn visit_this_expr(&mut self, token: &Token, distance: &Option<usize>) -> Result<WorngValue, RuntimeError> {
+    match *distance {
+      Some(distance) => match self.environment.borrow().get_at(distance, &token.lexeme) {
+          Ok(value) => Ok(value.clone()),
+          Err(_) => { Err(RuntimeError::UndefinedVariable(token.clone())) },
+      },
+      None => match self.globals.borrow().get_value(&token.lexeme) {
+          Ok(value) => Ok(value.clone()),
+          Err(_) => Err(RuntimeError::UndefinedVariable(token.clone())),
+      },
     }
   }
-}
\ No newline at end of file
+}
@@ -0,0 +1,348 @@
+use std::fmt::Write;
+
+// arrays and objects nested deeper are rejected, before parsing or writing them runs out of stack.
+pub const MAX_DEPTH: usize = 512;
+
+/// a JSON document, objects keep the order of their keys.
+/// shared by the `json` natives and everything talking JSON to other tools.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+  Null,
+  Bool(bool),
+  Number(f64),
+  String(String),
+  Array(Vec<Json>),
+  Object(Vec<(String, Json)>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonError {
+  pub line: usize,
+  pub column: usize,
+  pub message: String,
+}
+
+impl std::fmt::Display for JsonError {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "{} at line {} column {}", self.message, self.line, self.column)
+  }
+}
+
+impl Json {
+  pub fn parse(text: &str) -> Result<Json, JsonError> {
+    let mut parser = JsonParser { chars: text.chars().collect(), current: 0, depth: 0 };
+    parser.skip_whitespace();
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.current < parser.chars.len() {
+      return Err(parser.error("Expected the end of input"));
+    }
+    Ok(value)
+  }
+
+  /// one item per line, nested `indent` spaces deeper than their parent.
+  /// an indent of 0 gives the same compact text as `to_string()`.
+  pub fn pretty(&self, indent: usize) -> String {
+    let mut out = String::new();
+    self.write(&mut out, indent, 0);
+    out
+  }
+
+  /// the value of `key`, when this is an object that has one.
+  pub fn get(&self, key: &str) -> Option<&Json> {
+    match *self {
+      Json::Object(ref members) => members.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+      _ => None,
+    }
+  }
+
+  pub fn as_str(&self) -> Option<&str> {
+    match *self {
+      Json::String(ref string) => Some(string),
+      _ => None,
+    }
+  }
+
+  pub fn as_f64(&self) -> Option<f64> {
+    match *self {
+      Json::Number(number) => Some(number),
+      _ => None,
+    }
+  }
+
+  fn write(&self, out: &mut String, indent: usize, depth: usize) {
+    let newline = |out: &mut String, depth: usize| {
+      if indent > 0 {
+        out.push('\n');
+        out.push_str(&" ".repeat(indent * depth));
+      }
+    };
+
+    match *self {
+      Json::Null => out.push_str("null"),
+      Json::Bool(b) => out.push_str(if b { "true" } else { "false" }),
+      Json::Number(number) => write_number(out, number),
+      Json::String(ref string) => write_string(out, string),
+      Json::Array(ref items) if items.is_empty() => out.push_str("[]"),
+      Json::Array(ref items) => {
+        out.push('[');
+        for (i, item) in items.iter().enumerate() {
+          if i > 0 { out.push(',') }
+          newline(out, depth + 1);
+          item.write(out, indent, depth + 1);
+        }
+        newline(out, depth);
+        out.push(']');
+      },
+      Json::Object(ref members) if members.is_empty() => out.push_str("{}"),
+      Json::Object(ref members) => {
+        out.push('{');
+        for (i, (key, value)) in members.iter().enumerate() {
+          if i > 0 { out.push(',') }
+          newline(out, depth + 1);
+          write_string(out, key);
+          out.push_str(if indent > 0 { ": " } else { ":" });
+          value.write(out, indent, depth + 1);
+        }
+        newline(out, depth);
+        out.push('}');
+      },
+    }
+  }
+}
+
+impl std::fmt::Display for Json {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    f.write_str(&self.pretty(0))
+  }
+}
+
+// JSON has no NaN or infinities, they are written as null like browsers do.
+fn write_number(out: &mut String, number: f64) {
+  if number.is_finite() {
+    let _ = write!(out, "{}", number);
+  } else {
+    out.push_str("null");
+  }
+}
+
+fn write_string(out: &mut String, string: &str) {
+  out.push('"');
+  for c in string.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      c if (c as u32) < 0x20 => { let _ = write!(out, "\\u{:04x}", c as u32); },
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+}
+
+struct JsonParser {
+  chars: Vec<char>,
+  current: usize,
+  // the arrays and objects the parser is in.
+  depth: usize,
+}
+
+impl JsonParser {
+  fn value(&mut self) -> Result<Json, JsonError> {
+    match self.peek() {
+      Some('n') => self.keyword("null", Json::Null),
+      Some('t') => self.keyword("true", Json::Bool(true)),
+      Some('f') => self.keyword("false", Json::Bool(false)),
+      Some('"') => Ok(Json::String(self.string()?)),
+      Some(c) if c == '[' || c == '{' => {
+        if self.depth == MAX_DEPTH {
+          return Err(self.error("Nested too deeply"));
+        }
+        self.depth += 1;
+        let value = if c == '[' { self.array() } else { self.object() };
+        self.depth -= 1;
+        value
+      },
+      Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+      Some(_) => Err(self.error("Expected a value")),
+      None => Err(self.error("Unexpected end of input")),
+    }
+  }
+
+  fn keyword(&mut self, keyword: &str, value: Json) -> Result<Json, JsonError> {
+    for expected in keyword.chars() {
+      if self.peek() != Some(expected) {
+        return Err(self.error("Expected a value"));
+      }
+      self.current += 1;
+    }
+    Ok(value)
+  }
+
+  fn number(&mut self) -> Result<Json, JsonError> {
+    let start = self.current;
+    self.eat('-');
+    if !self.eat('0') && !self.digits() {
+      return Err(self.error("Expected a digit"));
+    }
+    if self.eat('.') && !self.digits() {
+      return Err(self.error("Expected a digit after the decimal point"));
+    }
+    if self.eat('e') || self.eat('E') {
+      if !self.eat('+') { self.eat('-'); }
+      if !self.digits() {
+        return Err(self.error("Expected a digit in the exponent"));
+      }
+    }
+
+    let text: String = self.chars[start..self.current].iter().collect();
+    text.parse().map(Json::Number).map_err(|_| self.error("Invalid number"))
+  }
+
+  // true when at least one digit was consumed.
+  fn digits(&mut self) -> bool {
+    let start = self.current;
+    while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+      self.current += 1;
+    }
+    self.current > start
+  }
+
+  fn string(&mut self) -> Result<String, JsonError> {
+    self.current += 1; // opening quote.
+    let mut string = String::new();
+    loop {
+      match self.advance() {
+        Some('"') => return Ok(string),
+        Some('\\') => match self.advance() {
+          Some('"') => string.push('"'),
+          Some('\\') => string.push('\\'),
+          Some('/') => string.push('/'),
+          Some('b') => string.push('\u{8}'),
+          Some('f') => string.push('\u{c}'),
+          Some('n') => string.push('\n'),
+          Some('r') => string.push('\r'),
+          Some('t') => string.push('\t'),
+          Some('u') => string.push(self.unicode_escape()?),
+          _ => return Err(self.error("Invalid escape")),
+        },
+        Some(c) if (c as u32) < 0x20 => return Err(self.error("Unescaped control character in string")),
+        Some(c) => string.push(c),
+        None => return Err(self.error("Unterminated string")),
+      }
+    }
+  }
+
+  // `\uXXXX`, joining utf-16 surrogate pairs.
+  fn unicode_escape(&mut self) -> Result<char, JsonError> {
+    let high = self.hex4()?;
+    if !(0xd800..0xdc00).contains(&high) {
+      return std::char::from_u32(high).ok_or_else(|| self.error("Invalid unicode escape"));
+    }
+
+    if !(self.eat('\\') && self.eat('u')) {
+      return Err(self.error("Expected a low surrogate"));
+    }
+    let low = self.hex4()?;
+    if !(0xdc00..0xe000).contains(&low) {
+      return Err(self.error("Expected a low surrogate"));
+    }
+    std::char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+      .ok_or_else(|| self.error("Invalid unicode escape"))
+  }
+
+  fn hex4(&mut self) -> Result<u32, JsonError> {
+    let mut value = 0;
+    for _ in 0..4 {
+      let digit = self.advance().and_then(|c| c.to_digit(16)).ok_or_else(|| self.error("Invalid unicode escape"))?;
+      value = value * 16 + digit;
+    }
+    Ok(value)
+  }
+
+  fn array(&mut self) -> Result<Json, JsonError> {
+    self.current += 1;
+    let mut items = Vec::new();
+    self.skip_whitespace();
+    if self.eat(']') {
+      return Ok(Json::Array(items));
+    }
+
+    loop {
+      self.skip_whitespace();
+      items.push(self.value()?);
+      self.skip_whitespace();
+      if self.eat(']') {
+        return Ok(Json::Array(items));
+      }
+      if !self.eat(',') {
+        return Err(self.error("Expected ',' or ']'"));
+      }
+    }
+  }
+
+  fn object(&mut self) -> Result<Json, JsonError> {
+    self.current += 1;
+    let mut members = Vec::new();
+    self.skip_whitespace();
+    if self.eat('}') {
+      return Ok(Json::Object(members));
+    }
+
+    loop {
+      self.skip_whitespace();
+      if self.peek() != Some('"') {
+        return Err(self.error("Expected a string key"));
+      }
+      let key = self.string()?;
+      self.skip_whitespace();
+      if !self.eat(':') {
+        return Err(self.error("Expected ':'"));
+      }
+      self.skip_whitespace();
+      members.push((key, self.value()?));
+      self.skip_whitespace();
+      if self.eat('}') {
+        return Ok(Json::Object(members));
+      }
+      if !self.eat(',') {
+        return Err(self.error("Expected ',' or '}'"));
+      }
+    }
+  }
+
+  fn skip_whitespace(&mut self) {
+    while self.peek().is_some_and(|c| c == ' ' || c == '\t' || c == '\n' || c == '\r') {
+      self.current += 1;
+    }
+  }
+
+  fn eat(&mut self, expected: char) -> bool {
+    if self.peek() == Some(expected) {
+      self.current += 1;
+      return true;
+    }
+    false
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.chars.get(self.current).cloned()
+  }
+
+  fn advance(&mut self) -> Option<char> {
+    let c = self.peek();
+    if c.is_some() {
+      self.current += 1;
+    }
+    c
+  }
+
+  fn error(&self, message: &str) -> JsonError {
+    let before = &self.chars[..self.current.min(self.chars.len())];
+    let line = before.iter().filter(|&&c| c == '\n').count() + 1;
+    let column = before.iter().rev().take_while(|&&c| c != '\n').count() + 1;
+    JsonError { line, column, message: message.to_string() }
+  }
+}
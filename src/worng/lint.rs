@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use super::scanner::Scanner;
+use super::parser::Parser;
+use super::resolver::Resolver;
+use super::environment::Environment;
+use super::worng_value::WorngValue;
+use super::token::Token;
+use super::error::WorngError;
+
+const ALLOW: &str = "worng-allow:";
+
+/// what `worng lint` looks for, the `Resolver` finds them while it resolves the scopes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rule {
+  UnusedVariable,
+  UnusedParameter,
+  UnreachableCode,
+  Shadowing,
+  UndeclaredAssignment,
+  SelfComparison,
+  ArityMismatch,
+}
+
+pub const RULES: [Rule; 7] = [
+  Rule::UnusedVariable,
+  Rule::UnusedParameter,
+  Rule::UnreachableCode,
+  Rule::Shadowing,
+  Rule::UndeclaredAssignment,
+  Rule::SelfComparison,
+  Rule::ArityMismatch,
+];
+
+impl Rule {
+  /// as written in `// worng-allow: ` comments and `--allow`.
+  pub fn name(self) -> &'static str {
+    match self {
+      Rule::UnusedVariable => "unused-variable",
+      Rule::UnusedParameter => "unused-parameter",
+      Rule::UnreachableCode => "unreachable-code",
+      Rule::Shadowing => "shadowing",
+      Rule::UndeclaredAssignment => "undeclared-assignment",
+      Rule::SelfComparison => "self-comparison",
+      Rule::ArityMismatch => "arity-mismatch",
+    }
+  }
+
+  pub fn from_name(name: &str) -> Option<Rule> {
+    RULES.iter().find(|rule| rule.name() == name).cloned()
+  }
+}
+
+/// something legal but most likely a mistake, `token` is what it is about.
+#[derive(Debug, Clone)]
+pub struct Warning {
+  pub rule: Rule,
+  pub token: Token,
+  pub message: String,
+}
+
+impl Warning {
+  /// 1-based, counted in chars like `Token::offset`.
+  pub fn column(&self, source: &str) -> usize {
+    let before: Vec<char> = source.chars().take(self.token.offset).collect();
+    before.iter().rev().take_while(|&&c| c != '\n').count() + 1
+  }
+}
+
+/// the warnings for `source`, minus the `allowed` rules and those silenced by a
+/// `// worng-allow: rule, other-rule` comment, which covers its own line when it
+/// ends one and the next line when it stands alone.
+/// a program that doesn't compile gives its errors instead.
+pub fn lint(source: &str, allowed: &[Rule]) -> Result<Vec<Warning>, Vec<WorngError>> {
+  let mut scanner = Scanner::new(source);
+  let tokens = scanner.scan_tokens();
+  if !scanner.errors.is_empty() {
+    return Err(scanner.errors.into_iter().map(WorngError::ScanningError).collect());
+  }
+
+  let mut statements = Parser::new(tokens.clone())
+    .parse()
+    .map_err(|errors| errors.into_iter().map(WorngError::ParsingError).collect::<Vec<WorngError>>())?;
+
+  let mut resolver = with_natives(Resolver::new());
+  resolver.resolve(&mut statements);
+  if !resolver.errors.is_empty() {
+    return Err(resolver.errors.into_iter().map(WorngError::ParsingError).collect());
+  }
+
+  Ok(silence(resolver.warnings, &scanner.comments, &tokens, allowed))
+}
+
+/// a resolver that knows the natives, their names aren't undeclared and their arity is checked.
+pub(crate) fn with_natives(mut resolver: Resolver) -> Resolver {
+  let natives = Environment::global();
+  for name in natives.names() {
+    let arity = match natives.get_value(&name) {
+      Ok(WorngValue::Func(ref function)) => Some(function.arity()),
+      _ => None,
+    };
+    resolver.define_global(&name, arity);
+  }
+  resolver
+}
+
+/// `warnings` in source order, without the `allowed` rules and those the comments silence.
+pub(crate) fn silence(warnings: Vec<Warning>, comments: &[Token], tokens: &[Token], allowed: &[Rule]) -> Vec<Warning> {
+  let silenced = silenced_lines(comments, tokens);
+  let mut warnings: Vec<Warning> = warnings
+    .into_iter()
+    .filter(|warning| !allowed.contains(&warning.rule))
+    .filter(|warning| !silenced.get(&warning.token.line).is_some_and(|rules| rules.contains(&warning.rule)))
+    .collect();
+  warnings.sort_by_key(|warning| warning.token.offset);
+  warnings
+}
+
+// the rules each line allows.
+fn silenced_lines(comments: &[Token], tokens: &[Token]) -> HashMap<i32, Vec<Rule>> {
+  let mut silenced: HashMap<i32, Vec<Rule>> = HashMap::new();
+  for comment in comments {
+    let text = comment.lexeme.trim_start_matches('/').trim();
+    let names = match text.strip_prefix(ALLOW) {
+      Some(names) => names,
+      None => continue,
+    };
+
+    let ends_line = tokens.iter().any(|token| token.line == comment.line && token.offset < comment.offset);
+    let line = if ends_line { comment.line } else { comment.line + 1 };
+    silenced
+      .entry(line)
+      .or_default()
+      .extend(names.split(',').filter_map(|name| Rule::from_name(name.trim())));
+  }
+  silenced
+}
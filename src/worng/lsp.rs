@@ -0,0 +1,668 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Read, Write};
+
+use super::json::Json;
+use super::scanner::Scanner;
+use super::parser::Parser;
+use super::resolver::{Resolver, Declaration, BindingKind};
+use super::lint::{self, Rule};
+use super::environment::Environment;
+use super::worng_value::WorngValue;
+use super::statement::Stmt;
+use super::expr::Expr;
+use super::token::Token;
+use super::token_type::TokenType;
+use super::error::{ScanningError, ParsingError};
+
+// the length a client may announce for a message, larger ones are refused before reading them.
+const MAX_MESSAGE_LENGTH: usize = 64 * 1024 * 1024;
+
+// JSON-RPC error codes.
+const PARSE_ERROR: f64 = -32700.0;
+const INVALID_REQUEST: f64 = -32600.0;
+const METHOD_NOT_FOUND: f64 = -32601.0;
+const INVALID_PARAMS: f64 = -32602.0;
+
+// the numbers LSP uses for these kinds.
+const SEVERITY_ERROR: f64 = 1.0;
+const SEVERITY_WARNING: f64 = 2.0;
+const TEXT_DOCUMENT_SYNC_FULL: f64 = 1.0;
+const SYMBOL_CLASS: f64 = 5.0;
+const SYMBOL_METHOD: f64 = 6.0;
+const SYMBOL_FUNCTION: f64 = 12.0;
+const COMPLETION_FUNCTION: f64 = 3.0;
+const COMPLETION_VARIABLE: f64 = 6.0;
+const COMPLETION_CLASS: f64 = 7.0;
+const COMPLETION_MODULE: f64 = 9.0;
+
+/// `worng lsp`: answers the Language Server Protocol messages from `reader` until `exit` or
+/// the end of the input, true when the client asked to `shutdown` before.
+/// documents are synced in full and analyzed again on every change, the declarations and
+/// references come from the `Resolver`.
+pub fn serve(reader: &mut dyn BufRead, writer: &mut dyn Write) -> io::Result<bool> {
+  let mut server = Server { documents: HashMap::new(), shut_down: false };
+
+  while let Some(body) = read_message(reader)? {
+    let message = match Json::parse(&body) {
+      Ok(message) => message,
+      Err(err) => {
+        write_message(writer, &error_response(Json::Null, PARSE_ERROR, err.to_string()))?;
+        continue;
+      },
+    };
+
+    if message.get("method").and_then(Json::as_str) == Some("exit") {
+      break;
+    }
+    for reply in server.handle(&message) {
+      write_message(writer, &reply)?;
+    }
+  }
+
+  Ok(server.shut_down)
+}
+
+struct Server {
+  documents: HashMap<String, Document>,
+  shut_down: bool,
+}
+
+type RequestError = (f64, String);
+
+impl Server {
+  // the responses and notifications to send back.
+  fn handle(&mut self, message: &Json) -> Vec<Json> {
+    let params = message.get("params").cloned().unwrap_or(Json::Null);
+    match (message.get("method").and_then(Json::as_str), message.get("id")) {
+      // only responses have no method, and nothing is ever requested from the client.
+      (None, _) => Vec::new(),
+      (Some(method), Some(id)) => {
+        let reply = match self.request(method, &params) {
+          Ok(result) => object(vec![("jsonrpc", string("2.0")), ("id", id.clone()), ("result", result)]),
+          Err((code, message)) => error_response(id.clone(), code, message),
+        };
+        vec![reply]
+      },
+      (Some(method), None) => self.notification(method, &params),
+    }
+  }
+
+  fn request(&mut self, method: &str, params: &Json) -> Result<Json, RequestError> {
+    if self.shut_down {
+      return Err((INVALID_REQUEST, "The server is shut down.".to_string()));
+    }
+
+    match method {
+      "initialize" => Ok(object(vec![
+        ("capabilities", object(vec![
+          ("textDocumentSync", Json::Number(TEXT_DOCUMENT_SYNC_FULL)),
+          ("definitionProvider", Json::Bool(true)),
+          ("referencesProvider", Json::Bool(true)),
+          ("hoverProvider", Json::Bool(true)),
+          ("documentSymbolProvider", Json::Bool(true)),
+          ("completionProvider", object(Vec::new())),
+        ])),
+        ("serverInfo", object(vec![("name", string("worng")), ("version", string(env!("CARGO_PKG_VERSION")))])),
+      ])),
+      "shutdown" => {
+        self.shut_down = true;
+        Ok(Json::Null)
+      },
+      "textDocument/definition" => {
+        let (document, offset) = self.position(params)?;
+        Ok(document.declaration_at(offset).map_or(Json::Null, |declaration| document.location(&declaration.name)))
+      },
+      "textDocument/references" => {
+        let (document, offset) = self.position(params)?;
+        let include_declaration = params.get("context").and_then(|context| context.get("includeDeclaration")) != Some(&Json::Bool(false));
+        let locations = match document.declaration_at(offset) {
+          Some(declaration) => {
+            let mut tokens: Vec<&Token> = declaration.references.iter().collect();
+            if include_declaration {
+              tokens.push(&declaration.name);
+            }
+            tokens.sort_by_key(|token| token.offset);
+            tokens.into_iter().map(|token| document.location(token)).collect()
+          },
+          None => Vec::new(),
+        };
+        Ok(Json::Array(locations))
+      },
+      "textDocument/hover" => {
+        let (document, offset) = self.position(params)?;
+        Ok(document.hover(offset).unwrap_or(Json::Null))
+      },
+      "textDocument/documentSymbol" => {
+        let document = self.document(params)?;
+        Ok(Json::Array(document.symbols(&document.statements)))
+      },
+      "textDocument/completion" => {
+        let (document, offset) = self.position(params)?;
+        Ok(Json::Array(document.completions(offset)))
+      },
+      _ => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'.", method))),
+    }
+  }
+
+  fn notification(&mut self, method: &str, params: &Json) -> Vec<Json> {
+    let document = params.get("textDocument");
+    let uri = document.and_then(|document| document.get("uri")).and_then(Json::as_str).map(str::to_string);
+    let text = match method {
+      "textDocument/didOpen" => document.and_then(|document| document.get("text")).and_then(Json::as_str),
+      // full sync, the last change has all of the text.
+      "textDocument/didChange" => match params.get("contentChanges") {
+        Some(Json::Array(ref changes)) => changes.last().and_then(|change| change.get("text")).and_then(Json::as_str),
+        _ => None,
+      },
+      "textDocument/didClose" => {
+        return match uri {
+          Some(uri) => {
+            self.documents.remove(&uri);
+            vec![diagnostics_notification(&uri, Vec::new())]
+          },
+          None => Vec::new(),
+        };
+      },
+      _ => return Vec::new(),
+    };
+
+    match (uri, text) {
+      (Some(uri), Some(text)) => {
+        let previous = self.documents.remove(&uri);
+        let document = Document::new(&uri, text, previous);
+        let notification = diagnostics_notification(&uri, document.diagnostics.clone());
+        self.documents.insert(uri, document);
+        vec![notification]
+      },
+      _ => Vec::new(),
+    }
+  }
+
+  fn document(&self, params: &Json) -> Result<&Document, RequestError> {
+    let uri = params
+      .get("textDocument")
+      .and_then(|document| document.get("uri"))
+      .and_then(Json::as_str)
+      .ok_or_else(|| (INVALID_PARAMS, "Expected a textDocument.uri.".to_string()))?;
+    self.documents.get(uri).ok_or_else(|| (INVALID_PARAMS, format!("'{}' isn't open.", uri)))
+  }
+
+  fn position(&self, params: &Json) -> Result<(&Document, usize), RequestError> {
+    let document = self.document(params)?;
+    let offset = params
+      .get("position")
+      .and_then(|position| document.offset(position))
+      .ok_or_else(|| (INVALID_PARAMS, "Expected a position.".to_string()))?;
+    Ok((document, offset))
+  }
+}
+
+/// an open file and what is known about it. while it doesn't parse, the declarations from
+/// the last time it did are kept for completion, but their places are out of date.
+struct Document {
+  uri: String,
+  chars: Vec<char>,
+  // the offset each line starts at.
+  lines: Vec<usize>,
+  tokens: Vec<Token>,
+  statements: Vec<Stmt>,
+  declarations: Vec<Declaration>,
+  stale: bool,
+  diagnostics: Vec<Json>,
+}
+
+impl Document {
+  fn new(uri: &str, text: &str, previous: Option<Document>) -> Document {
+    let chars: Vec<char> = text.chars().collect();
+    let mut lines = vec![0];
+    lines.extend(chars.iter().enumerate().filter(|(_, &c)| c == '\n').map(|(i, _)| i + 1));
+
+    let mut scanner = Scanner::new(text);
+    let tokens = scanner.scan_tokens();
+    let mut document = Document {
+      uri: uri.to_string(),
+      chars,
+      lines,
+      tokens: tokens.clone(),
+      statements: Vec::new(),
+      declarations: Vec::new(),
+      stale: false,
+      diagnostics: Vec::new(),
+    };
+
+    for error in scanner.errors.iter() {
+      let line = match *error {
+        ScanningError::UnexpectedCharacter(line, _) | ScanningError::UnterminatedString(line) => line,
+      };
+      document.diagnostics.push(document.diagnostic(document.line_range(line), SEVERITY_ERROR, error.to_string(), None));
+    }
+
+    match Parser::new(tokens).parse() {
+      Ok(mut statements) => {
+        let mut resolver = lint::with_natives(Resolver::new());
+        resolver.resolve(&mut statements);
+        for error in resolver.errors.iter() {
+          document.error(error);
+        }
+        for warning in lint::silence(resolver.warnings, &scanner.comments, &document.tokens, &[]) {
+          let range = document.token_range(&warning.token);
+          document.diagnostics.push(document.diagnostic(range, SEVERITY_WARNING, warning.message, Some(warning.rule)));
+        }
+        document.statements = statements;
+        document.declarations = resolver.declarations;
+      },
+      Err(errors) => {
+        for error in errors.iter() {
+          document.error(error);
+        }
+        if let Some(previous) = previous {
+          document.declarations = previous.declarations;
+          document.stale = true;
+        }
+      },
+    }
+    document
+  }
+
+  fn error(&mut self, error: &ParsingError) {
+    let range = match (error.token(), error) {
+      (Some(token), _) => self.token_range(token),
+      (None, ParsingError::UnexpectedEofError) => self.range(self.chars.len(), self.chars.len()),
+      (None, _) => self.range(0, 0),
+    };
+    let diagnostic = self.diagnostic(range, SEVERITY_ERROR, error.to_string(), None);
+    self.diagnostics.push(diagnostic);
+  }
+
+  fn diagnostic(&self, range: Json, severity: f64, message: String, rule: Option<Rule>) -> Json {
+    let mut members = vec![("range", range), ("severity", Json::Number(severity)), ("source", string("worng"))];
+    if let Some(rule) = rule {
+      members.push(("code", string(rule.name())));
+    }
+    members.push(("message", Json::String(message)));
+    object(members)
+  }
+
+  // the declaration of the name at `offset`, or of the name declared there.
+  fn declaration_at(&self, offset: usize) -> Option<&Declaration> {
+    if self.stale {
+      return None;
+    }
+    self.declarations.iter().find(|declaration| {
+      std::iter::once(&declaration.name).chain(declaration.references.iter()).any(|token| contains(token, offset))
+    })
+  }
+
+  fn hover(&self, offset: usize) -> Option<Json> {
+    if self.stale {
+      return None;
+    }
+    let token = self.tokens.iter().find(|token| token.token_type == TokenType::Identifier && contains(token, offset))?;
+
+    let text = match self.declaration_at(offset) {
+      Some(declaration) => {
+        let mut text = format!("```worng\n{}\n```", self.signature(declaration));
+        if declaration.kind == BindingKind::Class {
+          text.push_str(&format!("\n\n`{}` creates an instance.", self.constructor(declaration)));
+        }
+        text
+      },
+      None => {
+        let natives = Environment::global();
+        match natives.get_value(&token.lexeme) {
+          Ok(WorngValue::Func(ref function)) => {
+            format!("native function `{}`, takes {}.", token.lexeme, arguments(function.arity()))
+          },
+          Ok(WorngValue::Module(_)) => format!("native module `{}`.", token.lexeme),
+          _ => return None,
+        }
+      },
+    };
+
+    Some(object(vec![
+      ("contents", object(vec![("kind", string("markdown")), ("value", Json::String(text))])),
+      ("range", self.token_range(token)),
+    ]))
+  }
+
+  // how the declaration reads in the source, with the class hierarchy for classes.
+  fn signature(&self, declaration: &Declaration) -> String {
+    let name = &declaration.name.lexeme;
+    match (declaration.kind, self.statement(declaration)) {
+      (BindingKind::Function, Some(Stmt::Func(_, params, _))) => format!("fun {}({})", name, parameters(params)),
+      (BindingKind::Class, Some(_)) => {
+        let ancestors: Vec<String> = self.ancestors(declaration).iter().map(|ancestor| ancestor.lexeme.clone()).collect();
+        std::iter::once(format!("class {}", name)).chain(ancestors).collect::<Vec<String>>().join(" < ")
+      },
+      (BindingKind::Variable, _) => format!("var {}", name),
+      (BindingKind::Parameter, _) => format!("parameter {}", name),
+      (kind, _) => format!("{} {}", kind.describe(), name),
+    }
+  }
+
+  // `Name(params)` of the first `init` up the hierarchy.
+  fn constructor(&self, class: &Declaration) -> String {
+    let mut classes = vec![class];
+    classes.extend(self.ancestors(class).iter().filter_map(|ancestor| self.declaration_at(ancestor.offset)));
+    let init = classes.iter().filter_map(|class| self.statement(class)).find_map(|stmt| match *stmt {
+      Stmt::Class(_, _, ref methods) => methods.iter().find_map(|method| match *method {
+        Stmt::Func(ref name, ref params, _) if name.lexeme == "init" => Some(params),
+        _ => None,
+      }),
+      _ => None,
+    });
+    format!("{}({})", class.name.lexeme, init.map_or(String::new(), |params| parameters(params)))
+  }
+
+  // the superclass names, nearest first. a class that isn't declared in the document ends the list.
+  fn ancestors(&self, class: &Declaration) -> Vec<&Token> {
+    let mut ancestors: Vec<&Token> = Vec::new();
+    let mut current = self.statement(class);
+    while let Some(&Stmt::Class(_, Some(Expr::Var(ref superclass, _)), _)) = current {
+      if ancestors.iter().any(|ancestor| ancestor.lexeme == superclass.lexeme) {
+        break;
+      }
+      ancestors.push(superclass);
+      current = self.declaration_at(superclass.offset).and_then(|declaration| self.statement(declaration));
+    }
+    ancestors
+  }
+
+  // the function or class statement of a declaration.
+  fn statement(&self, declaration: &Declaration) -> Option<&Stmt> {
+    find_statement(&self.statements, declaration.name.offset)
+  }
+
+  fn symbols(&self, statements: &[Stmt]) -> Vec<Json> {
+    let mut symbols = Vec::new();
+    for stmt in statements {
+      match *stmt {
+        Stmt::Func(ref name, ref params, ref body) => {
+          let detail = format!("fun {}({})", name.lexeme, parameters(params));
+          symbols.push(self.symbol(name, SYMBOL_FUNCTION, detail, self.symbols(std::slice::from_ref(&**body))));
+        },
+        Stmt::Class(ref name, _, ref methods) => {
+          let children = methods
+            .iter()
+            .filter_map(|method| match *method {
+              Stmt::Func(ref name, ref params, ref body) => {
+                let detail = format!("{}({})", name.lexeme, parameters(params));
+                Some(self.symbol(name, SYMBOL_METHOD, detail, self.symbols(std::slice::from_ref(&**body))))
+              },
+              _ => None,
+            })
+            .collect();
+          symbols.push(self.symbol(name, SYMBOL_CLASS, self.class_detail(name), children));
+        },
+        Stmt::Block(ref statements) => symbols.extend(self.symbols(statements)),
+        Stmt::If(_, _, ref then_stmt, ref else_stmt) => {
+          symbols.extend(self.symbols(std::slice::from_ref(&**then_stmt)));
+          if let Some(ref else_stmt) = **else_stmt {
+            symbols.extend(self.symbols(std::slice::from_ref(else_stmt)));
+          }
+        },
+        Stmt::While(_, _, ref body) => symbols.extend(self.symbols(std::slice::from_ref(&**body))),
+        Stmt::Expr(_) | Stmt::Print(..) | Stmt::Var(..) | Stmt::Return(..) => {},
+      }
+    }
+    symbols
+  }
+
+  // from the `fun` or `class` keyword to the closing brace.
+  fn symbol(&self, name: &Token, kind: f64, detail: String, children: Vec<Json>) -> Json {
+    let index = self.token_index(name);
+    let start = match index.checked_sub(1).map(|before| &self.tokens[before]) {
+      Some(keyword) if keyword.token_type == TokenType::Func || keyword.token_type == TokenType::Class => keyword.offset,
+      _ => name.offset,
+    };
+    let end = self.closing_brace(index).map_or(name.offset + length(name), |brace| self.tokens[brace].offset + 1);
+
+    let mut members = vec![
+      ("name", Json::String(name.lexeme.clone())),
+      ("detail", Json::String(detail)),
+      ("kind", Json::Number(kind)),
+      ("range", self.range(start, end)),
+      ("selectionRange", self.token_range(name)),
+    ];
+    if !children.is_empty() {
+      members.push(("children", Json::Array(children)));
+    }
+    object(members)
+  }
+
+  fn class_detail(&self, name: &Token) -> String {
+    match self.declarations.iter().find(|declaration| declaration.name.offset == name.offset) {
+      Some(declaration) if !self.stale => self.signature(declaration),
+      _ => format!("class {}", name.lexeme),
+    }
+  }
+
+  // the names in scope at `offset`, inner ones first: local declarations up to the end of
+  // their block, the top level ones anywhere, then the natives.
+  fn completions(&self, offset: usize) -> Vec<Json> {
+    let mut locals: Vec<&Declaration> = self.declarations
+      .iter()
+      .filter(|declaration| declaration.local && !self.stale)
+      .filter(|declaration| declaration.name.offset <= offset && offset <= self.scope_end(declaration))
+      .collect();
+    locals.sort_by_key(|declaration| std::cmp::Reverse(declaration.name.offset));
+    let globals = self.declarations.iter().filter(|declaration| !declaration.local);
+
+    let mut seen = HashSet::new();
+    let mut items = Vec::new();
+    for declaration in locals.into_iter().chain(globals) {
+      if seen.insert(declaration.name.lexeme.clone()) {
+        let kind = match declaration.kind {
+          BindingKind::Function => COMPLETION_FUNCTION,
+          BindingKind::Class => COMPLETION_CLASS,
+          _ => COMPLETION_VARIABLE,
+        };
+        let detail = if self.stale { declaration.kind.describe().to_string() } else { self.signature(declaration) };
+        items.push(completion(&declaration.name.lexeme, kind, detail));
+      }
+    }
+
+    let natives = Environment::global();
+    let mut names = natives.names();
+    names.sort();
+    for name in names {
+      if !seen.insert(name.clone()) {
+        continue;
+      }
+      match natives.get_value(&name) {
+        Ok(WorngValue::Module(_)) => items.push(completion(&name, COMPLETION_MODULE, "native module".to_string())),
+        _ => items.push(completion(&name, COMPLETION_FUNCTION, "native function".to_string())),
+      }
+    }
+    items
+  }
+
+  // the offset of the brace closing the block a local name is declared in, parameters are
+  // in scope to the end of the function body.
+  fn scope_end(&self, declaration: &Declaration) -> usize {
+    let index = self.token_index(&declaration.name);
+    let brace = if declaration.kind == BindingKind::Parameter {
+      self.closing_brace(index)
+    } else {
+      let mut depth = 0;
+      let open = (0..index).rev().find(|&i| {
+        match self.tokens[i].token_type {
+          TokenType::RightBrace => depth += 1,
+          TokenType::LeftBrace if depth == 0 => return true,
+          TokenType::LeftBrace => depth -= 1,
+          _ => {},
+        }
+        false
+      });
+      open.and_then(|open| self.closing_brace(open))
+    };
+    brace.map_or(self.chars.len(), |brace| self.tokens[brace].offset)
+  }
+
+  // the index of the brace closing the first block opened from `index` on.
+  fn closing_brace(&self, index: usize) -> Option<usize> {
+    let open = (index..self.tokens.len()).find(|&i| self.tokens[i].token_type == TokenType::LeftBrace)?;
+    let mut depth = 0;
+    (open..self.tokens.len()).find(|&i| {
+      match self.tokens[i].token_type {
+        TokenType::LeftBrace => depth += 1,
+        TokenType::RightBrace => depth -= 1,
+        _ => {},
+      }
+      depth == 0
+    })
+  }
+
+  fn token_index(&self, token: &Token) -> usize {
+    self.tokens.binary_search_by_key(&token.offset, |other| other.offset).unwrap_or(0)
+  }
+
+  fn location(&self, token: &Token) -> Json {
+    object(vec![("uri", Json::String(self.uri.clone())), ("range", self.token_range(token))])
+  }
+
+  fn token_range(&self, token: &Token) -> Json {
+    self.range(token.offset, token.offset + length(token))
+  }
+
+  // all of a 1-based line, for errors that know nothing more.
+  fn line_range(&self, line: i32) -> Json {
+    let index = (line.max(1) as usize - 1).min(self.lines.len() - 1);
+    let start = self.lines[index];
+    let end = self.lines.get(index + 1).map_or(self.chars.len(), |next| next - 1);
+    self.range(start, end)
+  }
+
+  fn range(&self, start: usize, end: usize) -> Json {
+    object(vec![("start", self.position(start)), ("end", self.position(end))])
+  }
+
+  // LSP counts lines from 0 and characters in UTF-16 code units.
+  fn position(&self, offset: usize) -> Json {
+    let offset = offset.min(self.chars.len());
+    let line = self.lines.partition_point(|&start| start <= offset) - 1;
+    let character: usize = self.chars[self.lines[line]..offset].iter().map(|c| c.len_utf16()).sum();
+    object(vec![("line", Json::Number(line as f64)), ("character", Json::Number(character as f64))])
+  }
+
+  fn offset(&self, position: &Json) -> Option<usize> {
+    let line = position.get("line").and_then(Json::as_f64)? as usize;
+    let character = position.get("character").and_then(Json::as_f64)? as usize;
+    let start = match self.lines.get(line) {
+      Some(&start) => start,
+      None => return Some(self.chars.len()),
+    };
+
+    let mut offset = start;
+    let mut units = 0;
+    while offset < self.chars.len() && self.chars[offset] != '\n' && units < character {
+      units += self.chars[offset].len_utf16();
+      offset += 1;
+    }
+    Some(offset)
+  }
+}
+
+fn find_statement(statements: &[Stmt], offset: usize) -> Option<&Stmt> {
+  statements.iter().find_map(|stmt| match *stmt {
+    Stmt::Func(ref name, _, _) | Stmt::Class(ref name, _, _) if name.offset == offset => Some(stmt),
+    Stmt::Func(_, _, ref body) => find_statement(std::slice::from_ref(&**body), offset),
+    Stmt::Class(_, _, ref methods) => find_statement(methods, offset),
+    Stmt::Block(ref statements) => find_statement(statements, offset),
+    Stmt::If(_, _, ref then_stmt, ref else_stmt) => {
+      find_statement(std::slice::from_ref(&**then_stmt), offset).or_else(|| else_stmt.as_ref().as_ref().and_then(|else_stmt| {
+        find_statement(std::slice::from_ref(else_stmt), offset)
+      }))
+    },
+    Stmt::While(_, _, ref body) => find_statement(std::slice::from_ref(&**body), offset),
+    Stmt::Expr(_) | Stmt::Print(..) | Stmt::Var(..) | Stmt::Return(..) => None,
+  })
+}
+
+// the cursor may be right after the name too.
+fn contains(token: &Token, offset: usize) -> bool {
+  token.offset <= offset && offset <= token.offset + length(token)
+}
+
+fn length(token: &Token) -> usize {
+  token.lexeme.chars().count()
+}
+
+fn parameters(params: &[Token]) -> String {
+  params.iter().map(|param| param.lexeme.clone()).collect::<Vec<String>>().join(", ")
+}
+
+fn arguments(count: usize) -> String {
+  format!("{} argument{}", count, if count == 1 { "" } else { "s" })
+}
+
+fn completion(label: &str, kind: f64, detail: String) -> Json {
+  object(vec![("label", string(label)), ("kind", Json::Number(kind)), ("detail", Json::String(detail))])
+}
+
+fn diagnostics_notification(uri: &str, diagnostics: Vec<Json>) -> Json {
+  object(vec![
+    ("jsonrpc", string("2.0")),
+    ("method", string("textDocument/publishDiagnostics")),
+    ("params", object(vec![("uri", string(uri)), ("diagnostics", Json::Array(diagnostics))])),
+  ])
+}
+
+fn error_response(id: Json, code: f64, message: String) -> Json {
+  object(vec![
+    ("jsonrpc", string("2.0")),
+    ("id", id),
+    ("error", object(vec![("code", Json::Number(code)), ("message", Json::String(message))])),
+  ])
+}
+
+pub(crate) fn object(members: Vec<(&str, Json)>) -> Json {
+  Json::Object(members.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+pub(crate) fn string(value: &str) -> Json {
+  Json::String(value.to_string())
+}
+
+// a message is a `Content-Length` header, a blank line and that many bytes of JSON,
+// the Debug Adapter Protocol frames them the same. `None` at the end of the input.
+pub(crate) fn read_message(reader: &mut dyn BufRead) -> io::Result<Option<String>> {
+  let mut length = None;
+  loop {
+    let mut header = String::new();
+    if reader.read_line(&mut header)? == 0 {
+      return Ok(None);
+    }
+    let header = header.trim_end();
+    if header.is_empty() {
+      break;
+    }
+    if let Some((name, value)) = header.split_once(':') {
+      if name.trim().eq_ignore_ascii_case("content-length") {
+        length = value.trim().parse::<usize>().ok();
+      }
+    }
+  }
+
+  let length = length.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header"))?;
+  if length > MAX_MESSAGE_LENGTH {
+    return Err(io::Error::new(
+      io::ErrorKind::InvalidData,
+      format!("Content-Length of {} bytes is over the limit of {}", length, MAX_MESSAGE_LENGTH)
+    ));
+  }
+
+  // only what arrives is allocated, a message cut short is an error.
+  let mut body = Vec::new();
+  Read::take(reader, length as u64).read_to_end(&mut body)?;
+  if body.len() < length {
+    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "message shorter than its Content-Length"));
+  }
+  Ok(Some(String::from_utf8_lossy(&body).into_owned()))
+}
+
+pub(crate) fn write_message(writer: &mut dyn Write, message: &Json) -> io::Result<()> {
+  let body = message.to_string();
+  write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+  writer.flush()
+}
@@ -1,18 +1,45 @@
 pub mod worng_value;
+pub mod driver;
 pub mod token;
 pub mod token_type;
 pub mod scanner;
 pub mod expr;
+pub mod visitor;
 pub mod interpreter;
 pub mod ast_printer;
+pub mod ast_json;
 pub mod parser;
 pub mod error;
 pub mod statement;
 pub mod environment;
 pub mod callable;
 pub mod native_function;
+pub mod native_os;
+pub mod native_io;
+pub mod native_fs;
+pub mod native_math;
+pub mod native_string;
+pub mod native_list;
+pub mod native_convert;
+pub mod native_random;
+pub mod native_time;
+pub mod native_map;
+pub mod native_json;
+pub mod native_regex;
+pub mod native_assert;
+pub mod test_runner;
+pub mod formatter;
+pub mod lint;
+pub mod lsp;
+pub mod debugger;
+pub mod dap;
+pub mod profiler;
+pub mod coverage;
+pub mod json;
 pub mod worng_function;
 pub mod worng_instance;
 pub mod worng_class;
+pub mod worng_module;
 pub mod resolver;
-pub mod wasm;
\ No newline at end of file
+pub mod wasm;
+pub mod repl;
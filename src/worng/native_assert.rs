@@ -0,0 +1,23 @@
+use super::worng_value::WorngValue;
+use super::interpreter::Interpreter;
+use super::error::RuntimeError;
+use super::token::Token;
+
+// the message is shown as is, so any value reads fine.
+pub fn assert(_interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  if args[0].is_truthy() {
+    return Ok(WorngValue::Nil);
+  }
+  Err(RuntimeError::AssertionError(paren.clone(), args[1].to_string()))
+}
+
+// compares like `==` does.
+pub fn assert_eq(_interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  if args[0].is_equal(&args[1]) {
+    return Ok(WorngValue::Nil);
+  }
+  Err(RuntimeError::AssertionError(
+    paren.clone(),
+    format!("{} ({}) is not equal to {} ({}).", args[0], args[0].type_name(), args[1], args[1].type_name())
+  ))
+}
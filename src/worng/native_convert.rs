@@ -0,0 +1,48 @@
+use super::native_function::type_error;
+use super::worng_value::WorngValue;
+use super::interpreter::Interpreter;
+use super::error::RuntimeError;
+use super::token::Token;
+
+pub fn str(_interpreter: &mut Interpreter, _paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(WorngValue::String(args[0].to_string()))
+}
+
+// nil when the string does not hold a number.
+pub fn num(_interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  match args[0] {
+    WorngValue::Number(number) => Ok(WorngValue::Number(number)),
+    WorngValue::String(ref string) => Ok(parse_number(string.trim()).map_or(WorngValue::Nil, WorngValue::Number)),
+    ref other => Err(type_error(paren, other, "num", "number or a string"))
+  }
+}
+
+// rust also parses "inf" and "NaN", which are not numbers in worng source.
+fn parse_number(string: &str) -> Option<f64> {
+  let allowed = |c: char| c.is_ascii_digit() || "+-.eE".contains(c);
+  if string.is_empty() || !string.chars().all(allowed) {
+    return None;
+  }
+  string.parse().ok()
+}
+
+pub fn bool(_interpreter: &mut Interpreter, _paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(WorngValue::Bool(args[0].is_truthy()))
+}
+
+pub fn type_of(_interpreter: &mut Interpreter, _paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(WorngValue::String(args[0].type_name().to_string()))
+}
+
+// true for instances of the class or of any class inheriting from it.
+pub fn is_instance(_interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let class = match args[1] {
+    WorngValue::Class(ref class) => class,
+    ref other => return Err(type_error(paren, other, "is_instance", "class"))
+  };
+
+  match args[0] {
+    WorngValue::Instance(ref instance) => Ok(WorngValue::Bool(instance.borrow().class().inherits(class))),
+    _ => Ok(WorngValue::Bool(false))
+  }
+}
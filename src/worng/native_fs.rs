@@ -0,0 +1,160 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+use super::native_function::{NativeFunction, expect_string};
+use super::worng_module::WorngModule;
+use super::worng_value::WorngValue;
+use super::interpreter::Interpreter;
+use super::error::RuntimeError;
+use super::token::Token;
+
+/// how much of the file system the `fs` natives may touch.
+/// embedded interpreters start with `Denied`, the command line runs `Unrestricted`
+/// unless a root directory is given.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FsAccess {
+  Denied,
+  Unrestricted,
+  Within(PathBuf),
+}
+
+pub fn module() -> WorngModule {
+  let mut module = WorngModule::new("fs");
+  module.define("read_file", WorngValue::Func(Rc::new(NativeFunction::new("fs.read_file", 1, read_file))));
+  module.define("write_file", WorngValue::Func(Rc::new(NativeFunction::new("fs.write_file", 2, write_file))));
+  module.define("append", WorngValue::Func(Rc::new(NativeFunction::new("fs.append", 2, append))));
+  module.define("exists", WorngValue::Func(Rc::new(NativeFunction::new("fs.exists", 1, exists))));
+  module.define("list_dir", WorngValue::Func(Rc::new(NativeFunction::new("fs.list_dir", 1, list_dir))));
+  module.define("remove", WorngValue::Func(Rc::new(NativeFunction::new("fs.remove", 1, remove))));
+  module
+}
+
+fn read_file(interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let path = resolve(interpreter, paren, &args[0], "fs.read_file")?;
+  let contents = fs::read_to_string(&path).map_err(|err| fs_error(paren, &path, err))?;
+  Ok(WorngValue::String(contents))
+}
+
+fn write_file(interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let path = resolve(interpreter, paren, &args[0], "fs.write_file")?;
+  let contents = expect_string(paren, &args[1], "fs.write_file")?;
+  fs::write(&path, contents).map_err(|err| fs_error(paren, &path, err))?;
+  Ok(WorngValue::Nil)
+}
+
+fn append(interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let path = resolve(interpreter, paren, &args[0], "fs.append")?;
+  let contents = expect_string(paren, &args[1], "fs.append")?;
+  fs::OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(&path)
+    .and_then(|mut file| file.write_all(contents.as_bytes()))
+    .map_err(|err| fs_error(paren, &path, err))?;
+  Ok(WorngValue::Nil)
+}
+
+fn exists(interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let path = resolve(interpreter, paren, &args[0], "fs.exists")?;
+  Ok(WorngValue::Bool(path.exists()))
+}
+
+// entry names, sorted so scripts behave the same on every platform.
+fn list_dir(interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let path = resolve(interpreter, paren, &args[0], "fs.list_dir")?;
+  let mut names = Vec::new();
+  for entry in fs::read_dir(&path).map_err(|err| fs_error(paren, &path, err))? {
+    let entry = entry.map_err(|err| fs_error(paren, &path, err))?;
+    names.push(entry.file_name().to_string_lossy().into_owned());
+  }
+  names.sort();
+
+  let names = names.into_iter().map(WorngValue::String).collect();
+  Ok(WorngValue::List(Rc::new(RefCell::new(names))))
+}
+
+// files and empty directories only, there is no recursive removal on purpose.
+fn remove(interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let path = resolve(interpreter, paren, &args[0], "fs.remove")?;
+  let result = if path.is_dir() {
+    fs::remove_dir(&path)
+  } else {
+    fs::remove_file(&path)
+  };
+  result.map_err(|err| fs_error(paren, &path, err))?;
+  Ok(WorngValue::Nil)
+}
+
+/// checks the interpreter's `FsAccess` and turns the script's path into the one to open.
+/// relative paths are taken from the root directory when there is one.
+fn resolve(interpreter: &Interpreter, paren: &Token, path: &WorngValue, name: &str) -> Result<PathBuf, RuntimeError> {
+  let path = expect_string(paren, path, name)?;
+
+  match interpreter.fs_access {
+    FsAccess::Denied => Err(RuntimeError::NativeError(
+      paren.clone(),
+      format!("{}(): file system access is disabled.", name)
+    )),
+    FsAccess::Unrestricted => Ok(PathBuf::from(path)),
+    FsAccess::Within(ref root) => {
+      let outside = || RuntimeError::NativeError(
+        paren.clone(),
+        format!("{}(): '{}' is outside of the allowed directory.", name, path)
+      );
+
+      let root = root.canonicalize().map_err(|err| fs_error(paren, root, err))?;
+      let resolved = real_path(&root.join(&path)).ok_or_else(outside)?;
+
+      if resolved.starts_with(&root) {
+        Ok(resolved)
+      } else {
+        Err(outside())
+      }
+    }
+  }
+}
+
+// the most symlinks a path may go through, like the limit of the OS.
+const MAX_LINKS: usize = 40;
+
+// the path with every symlink in it replaced by its target, one component at a time, so `..`
+// goes up from where a link leads. a link is followed whether its target exists or not, a
+// dangling one inside the root may point out of it. the parts that don't exist are kept as
+// they are. `None` for a loop of links.
+fn real_path(path: &Path) -> Option<PathBuf> {
+  let mut real = PathBuf::new();
+  // the components still to go, the next one last.
+  let mut pending: Vec<PathBuf> = path.components().rev().map(|component| PathBuf::from(component.as_os_str())).collect();
+  let mut links = 0;
+
+  while let Some(next) = pending.pop() {
+    match next.components().next() {
+      Some(Component::Normal(name)) => {
+        let candidate = real.join(name);
+        match fs::symlink_metadata(&candidate) {
+          Ok(metadata) if metadata.file_type().is_symlink() => {
+            links += 1;
+            if links > MAX_LINKS {
+              return None;
+            }
+            let target = fs::read_link(&candidate).ok()?;
+            pending.extend(target.components().rev().map(|component| PathBuf::from(component.as_os_str())));
+          },
+          _ => real = candidate,
+        }
+      },
+      Some(Component::ParentDir) => { real.pop(); },
+      Some(Component::CurDir) | None => {},
+      // the root or a prefix, an absolute link starts over from it.
+      Some(other) => real.push(other.as_os_str()),
+    }
+  }
+  Some(real)
+}
+
+fn fs_error(paren: &Token, path: &Path, err: std::io::Error) -> RuntimeError {
+  RuntimeError::NativeError(paren.clone(), format!("{}: {}", path.display(), err))
+}
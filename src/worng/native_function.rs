@@ -1,17 +1,20 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::any::Any;
+use std::rc::Rc;
 
 
 use super::callable::Callable;
 use super::worng_value::WorngValue;
 use super::error::RuntimeError;
 use super::interpreter::Interpreter;
+use super::token::Token;
 
-pub fn get_current_time() -> u64 {
+// seconds since UNIX epoch, with the fraction.
+pub fn get_current_time() -> f64 {
   SystemTime::now()
     .duration_since(UNIX_EPOCH)
     .unwrap()
-    .as_secs()
+    .as_secs_f64()
 }
 
 #[derive(Debug)]
@@ -28,8 +31,8 @@ impl Callable for NativeClock {
     return 0;
   }
 
-  fn call(&self, interpreter: &mut Interpreter, args: Vec<WorngValue> ) ->  Result<WorngValue, RuntimeError>{
-    Ok(WorngValue::Number(get_current_time() as f64)) 
+  fn call(&self, _interpreter: &mut Interpreter, _paren: &Token, _args: Vec<WorngValue> ) ->  Result<WorngValue, RuntimeError>{
+    Ok(WorngValue::Number(get_current_time())) 
   }
 
   fn func_to_string(&self) -> String{
@@ -41,5 +44,117 @@ impl Callable for NativeClock {
   }
 }
 
+pub type NativeFn = fn(&mut Interpreter, &Token, Vec<WorngValue>) -> Result<WorngValue, RuntimeError>;
 
+/// a native backed by a plain rust function, used by the modules of natives (`os`, ...).
+/// the interpreter checks the arity before calling, so `function` can index `args` directly.
+#[derive(Debug)]
+pub struct NativeFunction {
+  name: String,
+  arity: usize,
+  function: NativeFn
+}
+
+impl NativeFunction {
+  pub fn new(name: &str, arity: usize, function: NativeFn) -> NativeFunction {
+    NativeFunction { name: name.to_string(), arity, function }
+  }
+}
+
+impl Callable for NativeFunction {
+  fn arity(&self) -> usize {
+    self.arity
+  }
+
+  fn call(&self, interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+    (self.function)(interpreter, paren, args)
+  }
+
+  fn func_to_string(&self) -> String {
+    format!("<native function {}>", self.name)
+  }
+
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+}
+
+pub type NativeMethodFn = fn(&mut Interpreter, &Token, &WorngValue, Vec<WorngValue>) -> Result<WorngValue, RuntimeError>;
+
+/// a built-in method bound to the value it was read from, eg. `"abc".upper`.
+#[derive(Debug)]
+pub struct NativeMethod {
+  receiver: WorngValue,
+  name: String,
+  arity: usize,
+  function: NativeMethodFn
+}
+
+impl NativeMethod {
+  pub fn new(receiver: WorngValue, name: &str, arity: usize, function: NativeMethodFn) -> NativeMethod {
+    NativeMethod { receiver, name: name.to_string(), arity, function }
+  }
+}
+
+impl Callable for NativeMethod {
+  fn arity(&self) -> usize {
+    self.arity
+  }
+
+  fn call(&self, interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+    (self.function)(interpreter, paren, &self.receiver, args)
+  }
+
+  fn func_to_string(&self) -> String {
+    format!("<native method {}>", self.name)
+  }
+
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+}
+
+/// looks `name` up in a table of built-in methods and binds it to `receiver`.
+pub fn bind_method(methods: &[(&str, usize, NativeMethodFn)], receiver: &WorngValue, name: &Token) -> Result<WorngValue, RuntimeError> {
+  match methods.iter().find(|(method, _, _)| *method == name.lexeme) {
+    Some(&(method, arity, function)) => {
+      Ok(WorngValue::Func(Rc::new(NativeMethod::new(receiver.clone(), method, arity, function))))
+    },
+    None => Err(RuntimeError::UndefinedProperty(name.clone()))
+  }
+}
+
+pub fn expect_number(paren: &Token, value: &WorngValue, name: &str) -> Result<f64, RuntimeError> {
+  match *value {
+    WorngValue::Number(number) => Ok(number),
+    _ => Err(type_error(paren, value, name, "number"))
+  }
+}
+
+pub fn expect_string(paren: &Token, value: &WorngValue, name: &str) -> Result<String, RuntimeError> {
+  match *value {
+    WorngValue::String(ref string) => Ok(string.clone()),
+    _ => Err(type_error(paren, value, name, "string"))
+  }
+}
+
+/// a whole number, negative ones count from `len` like in slices.
+pub fn expect_index(paren: &Token, value: &WorngValue, name: &str, len: usize) -> Result<usize, RuntimeError> {
+  let number = expect_number(paren, value, name)?;
+  if number.fract() != 0.0 {
+    return Err(RuntimeError::TypeError(
+      paren.clone(),
+      format!("{}() expects a whole number but got {}.", name, number)
+    ));
+  }
 
+  let index = if number < 0.0 { len as f64 + number } else { number };
+  Ok(index.max(0.0).min(len as f64) as usize)
+}
+
+pub fn type_error(paren: &Token, value: &WorngValue, name: &str, expected: &str) -> RuntimeError {
+  RuntimeError::TypeError(
+    paren.clone(),
+    format!("{}() expects a {} but got {}.", name, expected, value.type_name())
+  )
+}
@@ -0,0 +1,27 @@
+use super::worng_value::WorngValue;
+use super::interpreter::Interpreter;
+use super::error::RuntimeError;
+use super::token::Token;
+
+// input natives read through the interpreter's reader, stdin when running a file.
+
+pub fn input(interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  interpreter.write(&args[0].to_string()).map_err(|err| io_error(paren, err))?;
+  read_line(interpreter, paren, Vec::new())
+}
+
+pub fn read_line(interpreter: &mut Interpreter, paren: &Token, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  match interpreter.read_line().map_err(|err| io_error(paren, err))? {
+    Some(line) => Ok(WorngValue::String(line)),
+    None => Ok(WorngValue::Nil)
+  }
+}
+
+pub fn read_all(interpreter: &mut Interpreter, paren: &Token, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let contents = interpreter.read_all().map_err(|err| io_error(paren, err))?;
+  Ok(WorngValue::String(contents))
+}
+
+fn io_error(paren: &Token, err: std::io::Error) -> RuntimeError {
+  RuntimeError::NativeError(paren.clone(), err.to_string())
+}
@@ -0,0 +1,152 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use super::json::{Json, MAX_DEPTH};
+use super::native_function::{NativeFunction, expect_string, expect_number};
+use super::worng_module::WorngModule;
+use super::worng_value::WorngValue;
+use super::worng_instance::WorngInstance;
+use super::interpreter::Interpreter;
+use super::error::RuntimeError;
+use super::token::Token;
+use super::token_type::TokenType;
+
+/// `json` module: objects become maps and arrays become lists.
+pub fn module() -> WorngModule {
+  let mut module = WorngModule::new("json");
+  module.define("parse", WorngValue::Func(Rc::new(NativeFunction::new("json.parse", 1, parse))));
+  module.define("stringify", WorngValue::Func(Rc::new(NativeFunction::new("json.stringify", 2, stringify))));
+  module
+}
+
+fn parse(_interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let text = expect_string(paren, &args[0], "parse")?;
+  match Json::parse(&text) {
+    Ok(json) => Ok(from_json(json)),
+    Err(err) => Err(RuntimeError::NativeError(paren.clone(), format!("parse() got invalid JSON: {}.", err)))
+  }
+}
+
+// `indent` is the number of spaces per level, nil or 0 for a single line.
+fn stringify(interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let indent = match args[1] {
+    WorngValue::Nil => 0.0,
+    ref indent => expect_number(paren, indent, "stringify")?
+  };
+  if indent < 0.0 || indent.fract() != 0.0 {
+    return Err(RuntimeError::TypeError(
+      paren.clone(),
+      format!("stringify() expects a whole number of spaces but got {}.", indent)
+    ));
+  }
+
+  let json = to_json(interpreter, paren, &args[0], &mut Vec::new())?;
+  Ok(WorngValue::String(json.pretty(indent as usize)))
+}
+
+fn from_json(json: Json) -> WorngValue {
+  match json {
+    Json::Null => WorngValue::Nil,
+    Json::Bool(b) => WorngValue::Bool(b),
+    Json::Number(number) => WorngValue::Number(number),
+    Json::String(string) => WorngValue::String(string),
+    Json::Array(items) => WorngValue::List(Rc::new(RefCell::new(items.into_iter().map(from_json).collect()))),
+    Json::Object(members) => {
+      let entries = members.into_iter().map(|(key, value)| (key, from_json(value))).collect();
+      WorngValue::Map(Rc::new(RefCell::new(entries)))
+    }
+  }
+}
+
+// `parents` holds the lists, maps and instances being serialized, to catch cycles and
+// `to_json()` methods that keep returning new instances.
+fn to_json(interpreter: &mut Interpreter, paren: &Token, value: &WorngValue, parents: &mut Vec<*const ()>) -> Result<Json, RuntimeError> {
+  match *value {
+    WorngValue::Nil => Ok(Json::Null),
+    WorngValue::Bool(b) => Ok(Json::Bool(b)),
+    WorngValue::Number(number) if number.is_finite() => Ok(Json::Number(number)),
+    WorngValue::String(ref string) => Ok(Json::String(string.clone())),
+    WorngValue::List(ref items) => {
+      enter(paren, parents, Rc::as_ptr(items) as *const ())?;
+      let items: Vec<WorngValue> = items.borrow().clone();
+      let json = items
+        .iter()
+        .map(|item| to_json(interpreter, paren, item, parents))
+        .collect::<Result<Vec<Json>, RuntimeError>>()?;
+      parents.pop();
+      Ok(Json::Array(json))
+    },
+    WorngValue::Map(ref entries) => {
+      enter(paren, parents, Rc::as_ptr(entries) as *const ())?;
+      let entries: Vec<(String, WorngValue)> = entries.borrow().iter().map(|(key, value)| (key.clone(), value.clone())).collect();
+      let json = members(interpreter, paren, entries, parents)?;
+      parents.pop();
+      Ok(json)
+    },
+    WorngValue::Instance(ref instance) => {
+      enter(paren, parents, Rc::as_ptr(instance) as *const ())?;
+      let json = instance_to_json(interpreter, paren, value, parents)?;
+      parents.pop();
+      Ok(json)
+    },
+    WorngValue::Number(number) => Err(RuntimeError::NativeError(
+      paren.clone(),
+      format!("stringify() can't serialize the number {}.", number)
+    )),
+    ref other => Err(RuntimeError::NativeError(
+      paren.clone(),
+      format!("stringify() can't serialize a {}.", other.type_name())
+    ))
+  }
+}
+
+// through its `to_json()` method when it has one, else its fields.
+fn instance_to_json(interpreter: &mut Interpreter, paren: &Token, value: &WorngValue, parents: &mut Vec<*const ()>) -> Result<Json, RuntimeError> {
+  let instance = match *value {
+    WorngValue::Instance(ref instance) => instance.clone(),
+    _ => unreachable!()
+  };
+
+  let to_json_name = Token::new(TokenType::Identifier, "to_json".to_string(), None, paren.line, paren.offset);
+  let hook = WorngInstance::get(&instance, &to_json_name).ok().and_then(|hook| hook.get_callable());
+  if let Some(hook) = hook {
+    if hook.arity() != 0 {
+      return Err(RuntimeError::ArityError(paren.clone(), hook.arity(), 0));
+    }
+    let converted = hook.call(interpreter, paren, Vec::new())?;
+    return to_json(interpreter, paren, &converted, parents);
+  }
+
+  let mut names = instance.borrow().field_names();
+  names.sort();
+  let fields = names
+    .into_iter()
+    .map(|name| {
+      let token = Token::new(TokenType::Identifier, name.clone(), None, paren.line, paren.offset);
+      WorngInstance::get(&instance, &token).map(|value| (name, value))
+    })
+    .collect::<Result<Vec<(String, WorngValue)>, RuntimeError>>()?;
+  members(interpreter, paren, fields, parents)
+}
+
+fn members(interpreter: &mut Interpreter, paren: &Token, entries: Vec<(String, WorngValue)>, parents: &mut Vec<*const ()>) -> Result<Json, RuntimeError> {
+  let members = entries
+    .into_iter()
+    .map(|(key, value)| Ok((key, to_json(interpreter, paren, &value, parents)?)))
+    .collect::<Result<Vec<(String, Json)>, RuntimeError>>()?;
+  Ok(Json::Object(members))
+}
+
+fn enter(paren: &Token, parents: &mut Vec<*const ()>, pointer: *const ()) -> Result<(), RuntimeError> {
+  if parents.contains(&pointer) {
+    return Err(RuntimeError::NativeError(paren.clone(), "stringify() can't serialize a value containing itself.".to_string()));
+  }
+  if parents.len() == MAX_DEPTH {
+    return Err(RuntimeError::NativeError(
+      paren.clone(),
+      format!("stringify() can't serialize values nested more than {} levels deep.", MAX_DEPTH)
+    ));
+  }
+  parents.push(pointer);
+  Ok(())
+}
@@ -0,0 +1,80 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use super::native_function::{NativeMethodFn, bind_method, expect_number, expect_string};
+use super::worng_value::WorngValue;
+use super::interpreter::Interpreter;
+use super::error::RuntimeError;
+use super::token::Token;
+
+pub const METHODS: [(&str, usize, NativeMethodFn); 6] = [
+  ("len", 0, len),
+  ("get", 1, get_item),
+  ("set", 2, set_item),
+  ("push", 1, push),
+  ("pop", 0, pop),
+  ("join", 1, join),
+];
+
+pub fn get(receiver: &WorngValue, name: &Token) -> Result<WorngValue, RuntimeError> {
+  bind_method(&METHODS, receiver, name)
+}
+
+/// `list()`, an empty list.
+pub fn new(_interpreter: &mut Interpreter, _paren: &Token, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(WorngValue::List(Rc::new(RefCell::new(Vec::new()))))
+}
+
+fn items(value: &WorngValue) -> &RefCell<Vec<WorngValue>> {
+  match *value {
+    WorngValue::List(ref items) => items,
+    _ => unreachable!("list methods are only bound to lists")
+  }
+}
+
+// unlike slices, reading or writing past either end is an error.
+fn index(paren: &Token, value: &WorngValue, name: &str, len: usize) -> Result<usize, RuntimeError> {
+  let number = expect_number(paren, value, name)?;
+  let index = if number < 0.0 { len as f64 + number } else { number };
+  if number.fract() != 0.0 || index < 0.0 || index >= len as f64 {
+    return Err(RuntimeError::NativeError(
+      paren.clone(),
+      format!("{}() index {} is out of range for a list of length {}.", name, number, len)
+    ));
+  }
+  Ok(index as usize)
+}
+
+fn len(_interpreter: &mut Interpreter, _paren: &Token, this: &WorngValue, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(WorngValue::Number(items(this).borrow().len() as f64))
+}
+
+fn get_item(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let items = items(this).borrow();
+  let index = index(paren, &args[0], "get", items.len())?;
+  Ok(items[index].clone())
+}
+
+fn set_item(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, mut args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let mut items = items(this).borrow_mut();
+  let index = index(paren, &args[0], "set", items.len())?;
+  let value = args.pop().unwrap_or(WorngValue::Nil);
+  items[index] = value.clone();
+  Ok(value)
+}
+
+fn push(_interpreter: &mut Interpreter, _paren: &Token, this: &WorngValue, mut args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  items(this).borrow_mut().push(args.pop().unwrap_or(WorngValue::Nil));
+  Ok(WorngValue::Nil)
+}
+
+// nil when the list is empty.
+fn pop(_interpreter: &mut Interpreter, _paren: &Token, this: &WorngValue, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(items(this).borrow_mut().pop().unwrap_or(WorngValue::Nil))
+}
+
+fn join(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let separator = expect_string(paren, &args[0], "join")?;
+  let parts: Vec<String> = items(this).borrow().iter().map(|item| item.to_string()).collect();
+  Ok(WorngValue::String(parts.join(&separator)))
+}
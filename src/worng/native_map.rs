@@ -0,0 +1,76 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use super::native_function::{NativeMethodFn, bind_method, expect_string};
+use super::worng_value::WorngValue;
+use super::interpreter::Interpreter;
+use super::error::RuntimeError;
+use super::token::Token;
+
+// keys are strings and come out sorted.
+pub const METHODS: [(&str, usize, NativeMethodFn); 7] = [
+  ("len", 0, len),
+  ("get", 1, get_item),
+  ("set", 2, set_item),
+  ("has", 1, has),
+  ("remove", 1, remove),
+  ("keys", 0, keys),
+  ("values", 0, values),
+];
+
+pub fn get(receiver: &WorngValue, name: &Token) -> Result<WorngValue, RuntimeError> {
+  bind_method(&METHODS, receiver, name)
+}
+
+/// `map()`, an empty map.
+pub fn new(_interpreter: &mut Interpreter, _paren: &Token, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(WorngValue::Map(Rc::new(RefCell::new(BTreeMap::new()))))
+}
+
+fn entries(value: &WorngValue) -> &RefCell<BTreeMap<String, WorngValue>> {
+  match *value {
+    WorngValue::Map(ref entries) => entries,
+    _ => unreachable!("map methods are only bound to maps")
+  }
+}
+
+fn list(items: Vec<WorngValue>) -> WorngValue {
+  WorngValue::List(Rc::new(RefCell::new(items)))
+}
+
+fn len(_interpreter: &mut Interpreter, _paren: &Token, this: &WorngValue, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(WorngValue::Number(entries(this).borrow().len() as f64))
+}
+
+// nil when the key is missing.
+fn get_item(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let key = expect_string(paren, &args[0], "get")?;
+  Ok(entries(this).borrow().get(&key).cloned().unwrap_or(WorngValue::Nil))
+}
+
+fn set_item(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, mut args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let key = expect_string(paren, &args[0], "set")?;
+  let value = args.pop().unwrap_or(WorngValue::Nil);
+  entries(this).borrow_mut().insert(key, value.clone());
+  Ok(value)
+}
+
+fn has(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let key = expect_string(paren, &args[0], "has")?;
+  Ok(WorngValue::Bool(entries(this).borrow().contains_key(&key)))
+}
+
+// the removed value, nil when the key was missing.
+fn remove(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let key = expect_string(paren, &args[0], "remove")?;
+  Ok(entries(this).borrow_mut().remove(&key).unwrap_or(WorngValue::Nil))
+}
+
+fn keys(_interpreter: &mut Interpreter, _paren: &Token, this: &WorngValue, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(list(entries(this).borrow().keys().cloned().map(WorngValue::String).collect()))
+}
+
+fn values(_interpreter: &mut Interpreter, _paren: &Token, this: &WorngValue, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(list(entries(this).borrow().values().cloned().collect()))
+}
@@ -0,0 +1,62 @@
+use std::rc::Rc;
+use std::f64::consts;
+
+use super::native_function::{NativeFunction, NativeFn, expect_number};
+use super::worng_module::WorngModule;
+use super::worng_value::WorngValue;
+
+/// `math` module, thin wrappers over `f64`.
+/// results follow IEEE 754, eg. `math.sqrt(-1)` is NaN rather than an error.
+pub fn module() -> WorngModule {
+  let mut module = WorngModule::new("math");
+  module.define("PI", WorngValue::Number(consts::PI));
+  module.define("E", WorngValue::Number(consts::E));
+
+  define(&mut module, "sqrt", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.sqrt")?.sqrt())));
+  define(&mut module, "pow", 2, |_, paren, args| {
+    let base = expect_number(paren, &args[0], "math.pow")?;
+    let exponent = expect_number(paren, &args[1], "math.pow")?;
+    Ok(WorngValue::Number(base.powf(exponent)))
+  });
+  define(&mut module, "floor", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.floor")?.floor())));
+  define(&mut module, "ceil", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.ceil")?.ceil())));
+  define(&mut module, "round", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.round")?.round())));
+  define(&mut module, "abs", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.abs")?.abs())));
+  define(&mut module, "min", 2, |_, paren, args| {
+    let a = expect_number(paren, &args[0], "math.min")?;
+    let b = expect_number(paren, &args[1], "math.min")?;
+    Ok(WorngValue::Number(a.min(b)))
+  });
+  define(&mut module, "max", 2, |_, paren, args| {
+    let a = expect_number(paren, &args[0], "math.max")?;
+    let b = expect_number(paren, &args[1], "math.max")?;
+    Ok(WorngValue::Number(a.max(b)))
+  });
+
+  define(&mut module, "sin", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.sin")?.sin())));
+  define(&mut module, "cos", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.cos")?.cos())));
+  define(&mut module, "tan", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.tan")?.tan())));
+  define(&mut module, "asin", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.asin")?.asin())));
+  define(&mut module, "acos", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.acos")?.acos())));
+  define(&mut module, "atan", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.atan")?.atan())));
+  define(&mut module, "atan2", 2, |_, paren, args| {
+    let y = expect_number(paren, &args[0], "math.atan2")?;
+    let x = expect_number(paren, &args[1], "math.atan2")?;
+    Ok(WorngValue::Number(y.atan2(x)))
+  });
+
+  define(&mut module, "exp", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.exp")?.exp())));
+  define(&mut module, "log", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.log")?.ln())));
+  define(&mut module, "log10", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.log10")?.log10())));
+  define(&mut module, "log2", 1, |_, paren, args| Ok(WorngValue::Number(expect_number(paren, &args[0], "math.log2")?.log2())));
+
+  define(&mut module, "is_nan", 1, |_, paren, args| Ok(WorngValue::Bool(expect_number(paren, &args[0], "math.is_nan")?.is_nan())));
+  define(&mut module, "is_finite", 1, |_, paren, args| Ok(WorngValue::Bool(expect_number(paren, &args[0], "math.is_finite")?.is_finite())));
+
+  module
+}
+
+fn define(module: &mut WorngModule, name: &str, arity: usize, function: NativeFn) {
+  let native = NativeFunction::new(&format!("math.{}", name), arity, function);
+  module.define(name, WorngValue::Func(Rc::new(native)));
+}
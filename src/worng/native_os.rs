@@ -0,0 +1,42 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::env;
+
+use super::native_function::{NativeFunction, expect_number, expect_string};
+use super::worng_module::WorngModule;
+use super::worng_value::WorngValue;
+use super::interpreter::Interpreter;
+use super::error::RuntimeError;
+use super::token::Token;
+
+/// `os` module: what the script gets from the process running it.
+pub fn module() -> WorngModule {
+  let mut module = WorngModule::new("os");
+  module.define("args", WorngValue::Func(Rc::new(NativeFunction::new("os.args", 0, args))));
+  module.define("env", WorngValue::Func(Rc::new(NativeFunction::new("os.env", 1, env_var))));
+  module.define("exit", WorngValue::Func(Rc::new(NativeFunction::new("os.exit", 1, exit))));
+  module
+}
+
+fn args(interpreter: &mut Interpreter, _paren: &Token, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let args = interpreter.args
+    .iter()
+    .map(|arg| WorngValue::String(arg.clone()))
+    .collect();
+  Ok(WorngValue::List(Rc::new(RefCell::new(args))))
+}
+
+// nil when the variable is not set (or not unicode).
+fn env_var(_interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let name = expect_string(paren, &args[0], "os.env")?;
+  match env::var(name) {
+    Ok(value) => Ok(WorngValue::String(value)),
+    Err(_) => Ok(WorngValue::Nil)
+  }
+}
+
+// unwinds the interpreter like an error, the host decides what exiting means.
+fn exit(_interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let code = expect_number(paren, &args[0], "os.exit")?;
+  Err(RuntimeError::Exit(code as i32))
+}
@@ -0,0 +1,130 @@
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::native_function::{NativeFunction, expect_number, type_error};
+use super::worng_module::WorngModule;
+use super::worng_value::WorngValue;
+use super::interpreter::Interpreter;
+use super::error::RuntimeError;
+use super::token::Token;
+
+/// splitmix64, small and good enough for games and simulations (not for secrets).
+/// every interpreter owns one, so embedded sessions never share a sequence.
+#[derive(Debug, Clone)]
+pub struct Rng {
+  state: u64
+}
+
+impl Rng {
+  pub fn new(seed: u64) -> Rng {
+    Rng { state: seed }
+  }
+
+  // seeded from the clock, `random.seed(n)` makes runs reproducible.
+  pub fn from_time() -> Rng {
+    let nanos = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|duration| duration.as_nanos() as u64)
+      .unwrap_or(0);
+    Rng::new(nanos)
+  }
+
+  pub fn next_u64(&mut self) -> u64 {
+    self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = self.state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  }
+
+  // in [0, 1), from the top 53 bits.
+  pub fn next_f64(&mut self) -> f64 {
+    (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+  }
+
+  // in [0, bound), rejecting the values that would make the low ones more likely.
+  pub fn below(&mut self, bound: u64) -> u64 {
+    let zone = u64::MAX - u64::MAX % bound;
+    loop {
+      let value = self.next_u64();
+      if value < zone {
+        return value % bound;
+      }
+    }
+  }
+}
+
+/// `random` module, backed by the interpreter's `Rng`.
+pub fn module() -> WorngModule {
+  let mut module = WorngModule::new("random");
+  module.define("random", WorngValue::Func(Rc::new(NativeFunction::new("random.random", 0, random))));
+  module.define("random_int", WorngValue::Func(Rc::new(NativeFunction::new("random.random_int", 2, random_int))));
+  module.define("choice", WorngValue::Func(Rc::new(NativeFunction::new("random.choice", 1, choice))));
+  module.define("shuffle", WorngValue::Func(Rc::new(NativeFunction::new("random.shuffle", 1, shuffle))));
+  module.define("seed", WorngValue::Func(Rc::new(NativeFunction::new("random.seed", 1, seed))));
+  module
+}
+
+fn whole_number(paren: &Token, value: &WorngValue, name: &str) -> Result<i64, RuntimeError> {
+  let number = expect_number(paren, value, name)?;
+  if number.fract() != 0.0 || !number.is_finite() {
+    return Err(RuntimeError::TypeError(
+      paren.clone(),
+      format!("{}() expects a whole number but got {}.", name, number)
+    ));
+  }
+  Ok(number as i64)
+}
+
+fn random(interpreter: &mut Interpreter, _paren: &Token, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(WorngValue::Number(interpreter.rng.next_f64()))
+}
+
+// both bounds are included.
+fn random_int(interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let low = whole_number(paren, &args[0], "random_int")?;
+  let high = whole_number(paren, &args[1], "random_int")?;
+  if low > high {
+    return Err(RuntimeError::NativeError(
+      paren.clone(),
+      format!("random_int() expects the low bound {} to be at most the high bound {}.", low, high)
+    ));
+  }
+
+  let span = high.wrapping_sub(low) as u64;
+  let offset = if span == u64::MAX { interpreter.rng.next_u64() } else { interpreter.rng.below(span + 1) };
+  Ok(WorngValue::Number(low.wrapping_add(offset as i64) as f64))
+}
+
+fn choice(interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let items = match args[0] {
+    WorngValue::List(ref items) => items.borrow(),
+    ref other => return Err(type_error(paren, other, "choice", "list"))
+  };
+  if items.is_empty() {
+    return Err(RuntimeError::NativeError(paren.clone(), "choice() expects a non empty list.".to_string()));
+  }
+
+  let index = interpreter.rng.below(items.len() as u64) as usize;
+  Ok(items[index].clone())
+}
+
+// in place, fisher-yates.
+fn shuffle(interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let mut items = match args[0] {
+    WorngValue::List(ref items) => items.borrow_mut(),
+    ref other => return Err(type_error(paren, other, "shuffle", "list"))
+  };
+
+  for i in (1..items.len()).rev() {
+    let j = interpreter.rng.below(i as u64 + 1) as usize;
+    items.swap(i, j);
+  }
+  Ok(WorngValue::Nil)
+}
+
+fn seed(interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let seed = whole_number(paren, &args[0], "seed")?;
+  interpreter.rng = Rng::new(seed as u64);
+  Ok(WorngValue::Nil)
+}
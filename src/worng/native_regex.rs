@@ -0,0 +1,127 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use regex::{Regex, Captures, Match};
+
+use super::native_function::{NativeFunction, NativeMethodFn, bind_method, expect_string, type_error};
+use super::worng_module::WorngModule;
+use super::worng_value::WorngValue;
+use super::interpreter::Interpreter;
+use super::error::RuntimeError;
+use super::token::Token;
+
+/// `regex` module. every function takes a pattern string or a compiled regex first,
+/// compiled regexes have the same functions as methods, eg. `regex.compile("a+").find(text)`.
+/// positions are character indices, like the string methods.
+pub fn module() -> WorngModule {
+  let mut module = WorngModule::new("regex");
+  module.define("compile", WorngValue::Func(Rc::new(NativeFunction::new("regex.compile", 1, compile))));
+  module.define("is_match", WorngValue::Func(Rc::new(NativeFunction::new("regex.is_match", 2, |i, p, a| with_regex(i, p, a, "is_match", is_match)))));
+  module.define("find", WorngValue::Func(Rc::new(NativeFunction::new("regex.find", 2, |i, p, a| with_regex(i, p, a, "find", find)))));
+  module.define("find_all", WorngValue::Func(Rc::new(NativeFunction::new("regex.find_all", 2, |i, p, a| with_regex(i, p, a, "find_all", find_all)))));
+  module.define("captures", WorngValue::Func(Rc::new(NativeFunction::new("regex.captures", 2, |i, p, a| with_regex(i, p, a, "captures", captures)))));
+  module.define("replace", WorngValue::Func(Rc::new(NativeFunction::new("regex.replace", 3, |i, p, a| with_regex(i, p, a, "replace", replace)))));
+  module
+}
+
+pub const METHODS: [(&str, usize, NativeMethodFn); 5] = [
+  ("is_match", 1, is_match),
+  ("find", 1, find),
+  ("find_all", 1, find_all),
+  ("captures", 1, captures),
+  ("replace", 2, replace),
+];
+
+pub fn get(receiver: &WorngValue, name: &Token) -> Result<WorngValue, RuntimeError> {
+  bind_method(&METHODS, receiver, name)
+}
+
+fn compile(_interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let pattern = expect_string(paren, &args[0], "compile")?;
+  Ok(WorngValue::Regex(Rc::new(build(paren, &pattern)?)))
+}
+
+fn build(paren: &Token, pattern: &str) -> Result<Regex, RuntimeError> {
+  Regex::new(pattern).map_err(|err| {
+    // the error draws the pattern over several lines, only its last line says what is wrong.
+    let err = err.to_string();
+    let reason = err.lines().last().unwrap_or("").trim_start_matches("error: ").to_string();
+    RuntimeError::NativeError(paren.clone(), format!("regex pattern '{}' doesn't compile: {}.", pattern, reason))
+  })
+}
+
+// runs a method with the pattern (compiled here when given as a string) as receiver.
+fn with_regex(interpreter: &mut Interpreter, paren: &Token, mut args: Vec<WorngValue>, name: &str, method: NativeMethodFn) -> Result<WorngValue, RuntimeError> {
+  let regex = match args.remove(0) {
+    WorngValue::String(ref pattern) => WorngValue::Regex(Rc::new(build(paren, pattern)?)),
+    regex @ WorngValue::Regex(_) => regex,
+    ref other => return Err(type_error(paren, other, name, "pattern string or a regex"))
+  };
+  method(interpreter, paren, &regex, args)
+}
+
+fn regex(value: &WorngValue) -> &Regex {
+  match *value {
+    WorngValue::Regex(ref regex) => regex,
+    _ => unreachable!("regex methods are only bound to regexes")
+  }
+}
+
+fn char_index(text: &str, byte_index: usize) -> f64 {
+  text[..byte_index].chars().count() as f64
+}
+
+// {text, start, end} of a match.
+fn matched(text: &str, found: Match) -> WorngValue {
+  let mut entries = BTreeMap::new();
+  entries.insert("text".to_string(), WorngValue::String(found.as_str().to_string()));
+  entries.insert("start".to_string(), WorngValue::Number(char_index(text, found.start())));
+  entries.insert("end".to_string(), WorngValue::Number(char_index(text, found.end())));
+  WorngValue::Map(Rc::new(RefCell::new(entries)))
+}
+
+fn is_match(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let text = expect_string(paren, &args[0], "is_match")?;
+  Ok(WorngValue::Bool(regex(this).is_match(&text)))
+}
+
+// the first match, nil when there is none.
+fn find(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let text = expect_string(paren, &args[0], "find")?;
+  Ok(regex(this).find(&text).map_or(WorngValue::Nil, |found| matched(&text, found)))
+}
+
+fn find_all(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let text = expect_string(paren, &args[0], "find_all")?;
+  let matches = regex(this).find_iter(&text).map(|found| matched(&text, found)).collect();
+  Ok(WorngValue::List(Rc::new(RefCell::new(matches))))
+}
+
+// groups of the first match by number ("0" is the whole match) and by name,
+// nil for the groups that didn't take part, nil when there is no match.
+fn captures(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let text = expect_string(paren, &args[0], "captures")?;
+  let regex = regex(this);
+  let groups: Captures = match regex.captures(&text) {
+    Some(groups) => groups,
+    None => return Ok(WorngValue::Nil)
+  };
+
+  let group = |found: Option<Match>| found.map_or(WorngValue::Nil, |found| WorngValue::String(found.as_str().to_string()));
+  let mut entries = BTreeMap::new();
+  for (i, name) in regex.capture_names().enumerate() {
+    entries.insert(i.to_string(), group(groups.get(i)));
+    if let Some(name) = name {
+      entries.insert(name.to_string(), group(groups.get(i)));
+    }
+  }
+  Ok(WorngValue::Map(Rc::new(RefCell::new(entries))))
+}
+
+// every match, `$1` or `${name}` in the replacement stand for the groups.
+fn replace(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let text = expect_string(paren, &args[0], "replace")?;
+  let replacement = expect_string(paren, &args[1], "replace")?;
+  Ok(WorngValue::String(regex(this).replace_all(&text, replacement.as_str()).into_owned()))
+}
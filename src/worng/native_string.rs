@@ -0,0 +1,102 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use super::native_function::{NativeMethodFn, bind_method, expect_string, expect_index};
+use super::worng_value::WorngValue;
+use super::interpreter::Interpreter;
+use super::error::RuntimeError;
+use super::token::Token;
+
+// lengths and indices count characters, not bytes.
+pub const METHODS: [(&str, usize, NativeMethodFn); 11] = [
+  ("len", 0, len),
+  ("upper", 0, upper),
+  ("lower", 0, lower),
+  ("trim", 0, trim),
+  ("split", 1, split),
+  ("contains", 1, contains),
+  ("replace", 2, replace),
+  ("find", 1, find),
+  ("slice", 2, slice),
+  ("chars", 0, chars),
+  ("to_string", 0, to_string),
+];
+
+pub fn get(receiver: &WorngValue, name: &Token) -> Result<WorngValue, RuntimeError> {
+  bind_method(&METHODS, receiver, name)
+}
+
+fn receiver(value: &WorngValue) -> &str {
+  match *value {
+    WorngValue::String(ref string) => string,
+    _ => unreachable!("string methods are only bound to strings")
+  }
+}
+
+fn list(items: Vec<String>) -> WorngValue {
+  let items = items.into_iter().map(WorngValue::String).collect();
+  WorngValue::List(Rc::new(RefCell::new(items)))
+}
+
+fn len(_interpreter: &mut Interpreter, _paren: &Token, this: &WorngValue, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(WorngValue::Number(receiver(this).chars().count() as f64))
+}
+
+fn upper(_interpreter: &mut Interpreter, _paren: &Token, this: &WorngValue, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(WorngValue::String(receiver(this).to_uppercase()))
+}
+
+fn lower(_interpreter: &mut Interpreter, _paren: &Token, this: &WorngValue, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(WorngValue::String(receiver(this).to_lowercase()))
+}
+
+fn trim(_interpreter: &mut Interpreter, _paren: &Token, this: &WorngValue, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(WorngValue::String(receiver(this).trim().to_string()))
+}
+
+// an empty separator splits into characters.
+fn split(interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let separator = expect_string(paren, &args[0], "split")?;
+  if separator.is_empty() {
+    return chars(interpreter, paren, this, args);
+  }
+  Ok(list(receiver(this).split(separator.as_str()).map(|part| part.to_string()).collect()))
+}
+
+fn contains(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let needle = expect_string(paren, &args[0], "contains")?;
+  Ok(WorngValue::Bool(receiver(this).contains(needle.as_str())))
+}
+
+fn replace(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let from = expect_string(paren, &args[0], "replace")?;
+  let to = expect_string(paren, &args[1], "replace")?;
+  Ok(WorngValue::String(receiver(this).replace(from.as_str(), &to)))
+}
+
+// character index of the first match, nil when there is none.
+fn find(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let needle = expect_string(paren, &args[0], "find")?;
+  let string = receiver(this);
+  match string.find(needle.as_str()) {
+    Some(byte_index) => Ok(WorngValue::Number(string[..byte_index].chars().count() as f64)),
+    None => Ok(WorngValue::Nil)
+  }
+}
+
+// characters from `start` up to (not including) `end`, out of range indices are clamped.
+fn slice(_interpreter: &mut Interpreter, paren: &Token, this: &WorngValue, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let string = receiver(this);
+  let len = string.chars().count();
+  let start = expect_index(paren, &args[0], "slice", len)?;
+  let end = expect_index(paren, &args[1], "slice", len)?;
+  Ok(WorngValue::String(string.chars().skip(start).take(end.saturating_sub(start)).collect()))
+}
+
+fn chars(_interpreter: &mut Interpreter, _paren: &Token, this: &WorngValue, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(list(receiver(this).chars().map(|c| c.to_string()).collect()))
+}
+
+fn to_string(_interpreter: &mut Interpreter, _paren: &Token, this: &WorngValue, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(this.clone())
+}
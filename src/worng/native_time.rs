@@ -0,0 +1,169 @@
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+
+use super::native_function::{NativeFunction, expect_number, expect_string};
+use super::worng_module::WorngModule;
+use super::worng_value::WorngValue;
+use super::interpreter::Interpreter;
+use super::error::RuntimeError;
+use super::token::Token;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// `time` module: a monotonic clock for measuring and UTC dates for UNIX timestamps.
+/// dates use `%Y`, `%m`, `%d`, `%H`, `%M`, `%S` and `%%` like strftime.
+pub fn module() -> WorngModule {
+  let mut module = WorngModule::new("time");
+  module.define("now_ms", WorngValue::Func(Rc::new(NativeFunction::new("time.now_ms", 0, now_ms))));
+  module.define("sleep", WorngValue::Func(Rc::new(NativeFunction::new("time.sleep", 1, sleep))));
+  module.define("format", WorngValue::Func(Rc::new(NativeFunction::new("time.format", 2, format))));
+  module.define("parse", WorngValue::Func(Rc::new(NativeFunction::new("time.parse", 2, parse))));
+  module
+}
+
+// milliseconds since the interpreter started, never goes backwards.
+fn now_ms(interpreter: &mut Interpreter, _paren: &Token, _args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  Ok(WorngValue::Number(interpreter.started.elapsed().as_secs_f64() * 1000.0))
+}
+
+fn sleep(_interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let ms = expect_number(paren, &args[0], "sleep")?;
+  let duration = Duration::try_from_secs_f64(ms / 1000.0).map_err(|_| RuntimeError::NativeError(
+    paren.clone(),
+    format!("sleep() expects a positive number of milliseconds a `Duration` can hold but got {}.", ms)
+  ))?;
+  thread::sleep(duration);
+  Ok(WorngValue::Nil)
+}
+
+// the fraction of a second is dropped.
+fn format(_interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let timestamp = expect_number(paren, &args[0], "format")?;
+  let pattern = expect_string(paren, &args[1], "format")?;
+  if !timestamp.is_finite() {
+    return Err(RuntimeError::NativeError(paren.clone(), format!("format() can't format the timestamp {}.", timestamp)));
+  }
+
+  let seconds = timestamp.floor() as i64;
+  let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+  let time = seconds.rem_euclid(SECONDS_PER_DAY);
+  let (hour, minute, second) = (time / 3600, time / 60 % 60, time % 60);
+
+  let mut formatted = String::new();
+  let mut chars = pattern.chars();
+  while let Some(c) = chars.next() {
+    if c != '%' {
+      formatted.push(c);
+      continue;
+    }
+    match chars.next() {
+      Some('Y') => formatted.push_str(&format!("{:04}", year)),
+      Some('m') => formatted.push_str(&format!("{:02}", month)),
+      Some('d') => formatted.push_str(&format!("{:02}", day)),
+      Some('H') => formatted.push_str(&format!("{:02}", hour)),
+      Some('M') => formatted.push_str(&format!("{:02}", minute)),
+      Some('S') => formatted.push_str(&format!("{:02}", second)),
+      Some('%') => formatted.push('%'),
+      other => return Err(unknown_directive(paren, "format", other))
+    }
+  }
+  Ok(WorngValue::String(formatted))
+}
+
+// the timestamp of a date written with `pattern`, nil when it does not match.
+fn parse(_interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError> {
+  let text = expect_string(paren, &args[0], "parse")?;
+  let pattern = expect_string(paren, &args[1], "parse")?;
+
+  let (mut year, mut month, mut day, mut hour, mut minute, mut second) = (1970, 1, 1, 0, 0, 0);
+  let mut text = text.chars().peekable();
+  let mut chars = pattern.chars();
+  while let Some(c) = chars.next() {
+    let field = match c {
+      '%' => match chars.next() {
+        Some('Y') => &mut year,
+        Some('m') => &mut month,
+        Some('d') => &mut day,
+        Some('H') => &mut hour,
+        Some('M') => &mut minute,
+        Some('S') => &mut second,
+        Some('%') => {
+          if text.next() != Some('%') { return Ok(WorngValue::Nil) }
+          continue;
+        },
+        other => return Err(unknown_directive(paren, "parse", other))
+      },
+      _ => {
+        if text.next() != Some(c) { return Ok(WorngValue::Nil) }
+        continue;
+      }
+    };
+
+    let mut digits = String::new();
+    while let Some(&digit) = text.peek() {
+      if !digit.is_ascii_digit() { break }
+      digits.push(digit);
+      text.next();
+    }
+    match digits.parse() {
+      Ok(value) => *field = value,
+      Err(_) => return Ok(WorngValue::Nil)
+    }
+  }
+
+  let valid = text.next().is_none()
+    && (1..=12).contains(&month)
+    && (1..=days_in_month(year, month)).contains(&day)
+    && hour < 24 && minute < 60 && second < 60;
+  if !valid {
+    return Ok(WorngValue::Nil);
+  }
+
+  let seconds = days_from_civil(year, month, day)
+    .and_then(|days| days.checked_mul(SECONDS_PER_DAY))
+    .and_then(|seconds| seconds.checked_add(hour * 3600 + minute * 60 + second));
+  match seconds {
+    Some(seconds) => Ok(WorngValue::Number(seconds as f64)),
+    None => Err(RuntimeError::NativeError(paren.clone(), format!("parse() can't give a timestamp for the year {}.", year)))
+  }
+}
+
+fn unknown_directive(paren: &Token, name: &str, directive: Option<char>) -> RuntimeError {
+  let directive = directive.map_or(String::new(), |c| c.to_string());
+  RuntimeError::NativeError(paren.clone(), format!("{}() doesn't know the directive '%{}'.", name, directive))
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+  let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+  match month {
+    2 if leap => 29,
+    2 => 28,
+    4 | 6 | 9 | 11 => 30,
+    _ => 31
+  }
+}
+
+// days since 1970-01-01 of a proleptic gregorian date, see http://howardhinnant.github.io/date_algorithms.html
+// none when they don't fit in an `i64`.
+fn days_from_civil(year: i64, month: i64, day: i64) -> Option<i64> {
+  let year = if month <= 2 { year - 1 } else { year };
+  let era = year.div_euclid(400);
+  let year_of_era = year.rem_euclid(400);
+  let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+  let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+  era.checked_mul(146_097)?.checked_add(day_of_era - 719_468)
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+  let days = days + 719_468;
+  let era = days.div_euclid(146_097);
+  let day_of_era = days.rem_euclid(146_097);
+  let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let shifted_month = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+  let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+  let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+  (year, month, day)
+}
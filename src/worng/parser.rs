@@ -1,6 +1,5 @@
 use std::rc::Rc;
 
-use super::worng_value::Worng;
 use super::token::{ Literal, Token};
 use super::token_type::TokenType;
 use super::expr::Expr;
@@ -12,17 +11,27 @@ use std::error::{Error};
 
 // type Result<T> = result::Result<T, Box<dyn Error>>;
 
+// deeper programs would overflow the stack of the parser, resolver or interpreter.
+const MAX_DEPTH: usize = 200;
+// operators, calls and gets in one statement. each puts the tree a level deeper without the
+// parser recursing, so they don't count towards `MAX_DEPTH` but are still bounded.
+const MAX_LINKS: usize = 5000;
+
 #[derive(Debug)]
 pub struct Parser {
   tokens: Vec<Token>,
-  current: usize
+  current: usize,
+  depth: usize,
+  links: usize
 }
 
 impl Parser{
   pub fn new(tokens: Vec<Token>) -> Self {
     Parser{
       tokens: tokens,
-      current: 0
+      current: 0,
+      depth: 0,
+      links: 0
     }
   }
 
@@ -31,6 +40,8 @@ impl Parser{
     let mut errors: Vec<ParsingError> = Vec::new();
 
     while !self.is_at_end() {
+      // an error may leave `depth` unbalanced, it starts over with each declaration.
+      self.depth = 0;
       match self.declaration() {
         Ok(stmt) => statements.push(stmt),
         Err(err) => errors.push(err),
@@ -46,7 +57,15 @@ impl Parser{
   }
 
   pub fn declaration(&mut self) -> Result<Stmt, ParsingError> {
-    
+    self.nest()?;
+    let links = std::mem::replace(&mut self.links, 0);
+    let declaration = self.declaration_kind();
+    self.links = links;
+    self.unnest(1);
+    declaration
+  }
+
+  fn declaration_kind(&mut self) -> Result<Stmt, ParsingError> {
     let statement;
     if self.is_match(vec![TokenType::Var]){
       statement = self.var_declaration();
@@ -129,6 +148,13 @@ impl Parser{
   }
   
   fn statement(&mut self) -> Result<Stmt, ParsingError> {
+    self.nest()?;
+    let statement = self.statement_kind();
+    self.unnest(1);
+    statement
+  }
+
+  fn statement_kind(&mut self) -> Result<Stmt, ParsingError> {
     if self.is_match(vec![TokenType::Print]) {
       self.print_statement()
     } else if self.is_match(vec![TokenType::LeftBrace]){
@@ -158,6 +184,7 @@ impl Parser{
   }
 
   fn  if_statement(&mut self) -> Result<Stmt, ParsingError> {
+    let keyword = self.previous().clone();
     self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
     let condition = self.expression()?;
     self.consume(TokenType::RightParen, "Expect ')' after if condition.")?; 
@@ -165,71 +192,61 @@ impl Parser{
     let then_branch = self.statement()?;
     let mut else_branch = None;
     if self.is_match(vec![TokenType::Else]) {
-      if let Ok(stmt) = self.statement(){
-        else_branch = Some(stmt);
-      }
+      else_branch = Some(self.statement()?);
     }
 
-    return Ok(Stmt::If(condition, Box::new(then_branch), Box::new(else_branch) ));
+    Ok(Stmt::If(keyword, condition, Box::new(then_branch), Box::new(else_branch)))
   }
 
   fn while_statement( &mut self) -> Result<Stmt, ParsingError>{
+    let keyword = self.previous().clone();
     self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
     let condition = self.expression()?;
     self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
     let body = self.statement()?;
 
-    println!("condition = {}", &condition);
-    return Ok(Stmt::While(condition, Box::new(body)));    
+    Ok(Stmt::While(keyword, condition, Box::new(body)))
   }
 
   fn for_statement(&mut self) -> Result<Stmt, ParsingError> {
+    let keyword = self.previous().clone();
     self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
 
     let initializer;
     if self.is_match(vec![TokenType::Semicolon]) {
-      initializer = Ok(Stmt::Expr(Expr::Literal(Literal::Nil)));
+      initializer = Stmt::Expr(Expr::Literal(Literal::Nil));
     } else if self.is_match(vec![TokenType::Var]) {
-      initializer = self.var_declaration();
+      initializer = self.var_declaration()?;
     } else {
-      initializer = self.expression_statement();
+      initializer = self.expression_statement()?;
     }
 
-    let mut condition = Ok(Expr::Literal(Literal::Nil));
+    let mut condition = Expr::Literal(Literal::Bool(true));
     if !self.check(&TokenType::Semicolon) {
-      condition = self.expression();
+      condition = self.expression()?;
     }
     self.consume(TokenType::Semicolon, "Expect ';' after loop condition.")?;
 
-    let mut increment = Ok(Expr::Literal(Literal::Nil));
+    let mut increment = Expr::Literal(Literal::Nil);
     if !self.check(&TokenType::RightParen) {
-      increment = self.expression();
+      increment = self.expression()?;
     }
     self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
 
-    let mut body = self.statement().unwrap();
-
-    if let Ok(value) = &increment {
-      body = Stmt::Block(vec![body, Stmt::Expr(increment.unwrap())] );
-    }
-
-    if let Ok(Expr::Literal(Literal::Nil)) = condition {
-      condition = Ok(Expr::Literal(Literal::Bool(true)));
-    }
-    body = Stmt::While(condition.unwrap(), Box::new(body));
-
-    if let Ok(init_val) = &initializer {
-      body = Stmt::Block(vec![initializer.unwrap(), body]);
-    }
+    let mut body = self.statement()?;
+    body = Stmt::Block(vec![body, Stmt::Expr(increment)]);
+    body = Stmt::While(keyword, condition, Box::new(body));
+    body = Stmt::Block(vec![initializer, body]);
 
     return Ok(body);
   }
 
   fn print_statement(&mut self) -> Result<Stmt, ParsingError> {
+    let keyword = self.previous().clone();
     let expr = self.expression()?;
 
     match self.consume( TokenType::Semicolon, "Expect ';' after expression.") {
-      Ok(_) => Ok(Stmt::Print(expr)),
+      Ok(_) => Ok(Stmt::Print(keyword, expr)),
       Err(err) => Err(err),
     }
   }
@@ -259,7 +276,10 @@ impl Parser{
 
 
   fn expression(&mut self) -> Result<Expr, ParsingError> {
-   self.assignment()
+    self.nest()?;
+    let expr = self.assignment();
+    self.unnest(1);
+    expr
   }
 
   fn assignment(&mut self) -> Result<Expr, ParsingError>{
@@ -267,7 +287,9 @@ impl Parser{
 
     if self.is_match(vec![TokenType::Equal]) {
       let equals =  self.previous().clone();
+      self.nest()?;
       let value = self.assignment()?;
+      self.unnest(1);
 
       match expr {
         Expr::Var( token, _) => {
@@ -284,8 +306,10 @@ impl Parser{
 
   fn  or(&mut self) -> Result<Expr, ParsingError> {
     let mut expr = self.and()?;
+    let levels = self.nest_before(vec![TokenType::Or])?;
 
     while self.is_match(vec![TokenType::Or]) {
+      self.link()?;
       // not sure why using this expression directly is not working.
       // eg. Expr::Logical(Box::new(expr), self.previous().clone(), Box::new(right) ); is not working.
       let operator = self.previous().clone(); 
@@ -293,78 +317,92 @@ impl Parser{
       expr = Expr::Logical(Box::new(expr), operator, Box::new(right) );
     }
 
+    self.unnest(levels);
     return Ok(expr);
   }
 
   fn and(&mut self) -> Result<Expr, ParsingError> {
     let mut expr = self.equality()?;
+    let levels = self.nest_before(vec![ TokenType::And ])?;
 
     while self.is_match(vec![ TokenType::And ]) {
+      self.link()?;
       let operator = self.previous().clone();
       let right = self.equality()?;
       expr = Expr::Logical(Box::new(expr), operator, Box::new(right));
     }
 
+    self.unnest(levels);
     return Ok(expr);
   }
 
   fn equality(&mut self) -> Result<Expr, ParsingError> {
-    let mut expr = self.comparison().unwrap();
+    let mut expr = self.comparison()?;
+    let levels = self.nest_before(vec![TokenType::BangEqual, TokenType::EqualEqual])?;
 
     while self.is_match(vec![TokenType::BangEqual, TokenType::EqualEqual]) {
+      self.link()?;
       let operator = self.previous().clone();
       let right = self.comparison()?;
       expr = Expr::Binary(Box::new(expr), operator, Box::new(right) );
     }
 
+    self.unnest(levels);
     return Ok(expr);
   }
 
   fn comparison(&mut self) -> Result<Expr, ParsingError> {
-    let mut expr = self.addition().unwrap();
-
-    while self.is_match(vec![
-      TokenType::Greater, 
-      TokenType::GreaterEqual, 
-      TokenType::Less, 
-      TokenType::LessEqual
-      ]) {
+    let mut expr = self.addition()?;
+    let operators = vec![TokenType::Greater, TokenType::GreaterEqual, TokenType::Less, TokenType::LessEqual];
+    let levels = self.nest_before(operators.clone())?;
+
+    while self.is_match(operators.clone()) {
+      self.link()?;
       let operator = self.previous().clone();
       let right = self.addition()?;
       expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
     }
 
+    self.unnest(levels);
     return Ok(expr);
   }
 
   fn addition(&mut self) -> Result<Expr, ParsingError>  {
-    let mut expr = self.multiplication().unwrap();
+    let mut expr = self.multiplication()?;
+    let levels = self.nest_before(vec![TokenType::Minus, TokenType::Plus])?;
 
     while self.is_match(vec![TokenType::Minus, TokenType::Plus]) {
+      self.link()?;
       let operator = self.previous().clone();
-      let right = self.multiplication().unwrap();
+      let right = self.multiplication()?;
       expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
     }
 
+    self.unnest(levels);
     return Ok(expr);
   }
 
   fn multiplication(&mut self) -> Result<Expr, ParsingError>  {
-    let mut expr = self.unary().unwrap();
+    let mut expr = self.unary()?;
+    let levels = self.nest_before(vec![TokenType::Slash, TokenType::Star])?;
 
     while self.is_match(vec![TokenType::Slash, TokenType::Star]) {
+      self.link()?;
       let operator = self.previous().clone();
-      let right = self.unary().unwrap();
+      let right = self.unary()?;
       expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
     }
 
+    self.unnest(levels);
     return Ok(expr);
   }
 
   fn unary(&mut self) -> Result<Expr, ParsingError>  {
     if self.is_match(vec![TokenType::Bang, TokenType::Minus]) {
       let operator = self.previous().clone();
-      let right = self.unary().unwrap();
+      self.nest()?;
+      let right = self.unary()?;
+      self.unnest(1);
       return Ok(Expr::Unary(operator, Box::new(right)) );
     }
 
@@ -375,8 +413,13 @@ impl Parser{
   fn call(&mut self) -> Result<Expr, ParsingError> {
     
     let mut expr = self.primary();
+    let levels = self.nest_before(vec![TokenType::LeftParen, TokenType::Dot])?;
 
     loop { 
+      if self.check(&TokenType::LeftParen) || self.check(&TokenType::Dot) {
+        self.link()?;
+      }
+
       if self.is_match(vec![TokenType::LeftParen]) {
         expr = self.finish_call(expr?);
       } else if self.is_match(vec![TokenType::Dot]){  
@@ -387,6 +430,7 @@ impl Parser{
       }
     }
 
+    self.unnest(levels);
     return expr;
   }
 
@@ -428,19 +472,19 @@ impl Parser{
 
     if self.is_match(vec![TokenType::Super]){
       let keyword = self.previous().clone();
-      self.consume(TokenType::Dot, "Expect '.' after 'super'.");
+      self.consume(TokenType::Dot, "Expect '.' after 'super'.")?;
       let _method = self.consume(TokenType::Identifier, "Expect superclass method name.")?;
       return Ok(Expr::Super(keyword.clone(), _method.clone(), None));
     }
 
     if self.is_match(vec![TokenType::LeftParen]) {
-      let expr = self.expression().unwrap();
+      let expr = self.expression()?;
       match self.consume(TokenType::RightParen, "Expect ')' after expression."){
         Ok(t) => return Ok(Expr::Grouping(Box::new(expr))),
         Err(err) => return Err(err), 
       }
     } else {
-      Err(ParsingError::ParsingError) 
+      Err(self.error("Expect expression."))
     }
   }
 
@@ -464,10 +508,50 @@ impl Parser{
     if self.check(&token_type) { 
       return Ok(self.advance().clone());
     } else {
-      return Err(ParsingError::ParsingError);
+      return Err(self.error(message));
     };
   }
 
+  fn nest(&mut self) -> Result<(), ParsingError> {
+    self.depth += 1;
+    if self.depth > MAX_DEPTH {
+      return Err(ParsingError::TooDeeplyNestedError(self.peek().clone(), MAX_DEPTH));
+    }
+    Ok(())
+  }
+
+  // one level for a chain like `a + b - c` or `f(a).b()` when one of `token_types` comes next,
+  // however long it is, the levels to unnest. its links are counted by `link`.
+  fn nest_before(&mut self, token_types: Vec<TokenType>) -> Result<usize, ParsingError> {
+    if token_types.iter().any(|token_type| self.check(token_type)) {
+      self.nest()?;
+      Ok(1)
+    } else {
+      Ok(0)
+    }
+  }
+
+  fn link(&mut self) -> Result<(), ParsingError> {
+    self.links += 1;
+    if self.links > MAX_LINKS {
+      return Err(ParsingError::TooDeeplyNestedError(self.peek().clone(), MAX_LINKS));
+    }
+    Ok(())
+  }
+
+  fn unnest(&mut self, levels: usize) {
+    self.depth -= levels;
+  }
+
+  // running out of tokens is reported separately, the REPL uses it to tell incomplete input apart.
+  fn error(&self, message: &str) -> ParsingError {
+    if self.is_at_end() {
+      ParsingError::UnexpectedEofError
+    } else {
+      ParsingError::UnexpectedTokenError(self.peek().clone(), message.to_string())
+    }
+  }
+
   fn synchronize(&mut self) {
     self.advance();
 
@@ -0,0 +1,163 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use super::token::Token;
+
+// the hottest lines the summary shows.
+const TOP_LINES: usize = 10;
+
+/// what `--profile` measured of one function or method, or of the script itself.
+#[derive(Debug, Clone)]
+pub struct FunctionProfile {
+  pub name: String,
+  /// of the declaration, 0 for the script.
+  pub line: i32,
+  pub calls: usize,
+  /// with the functions it calls, a recursive call isn't counted twice.
+  pub inclusive: Duration,
+  /// without the functions it calls, natives count as its own time.
+  pub exclusive: Duration,
+}
+
+impl FunctionProfile {
+  /// `name:line`, as the summary and the collapsed stacks name it.
+  pub fn label(&self) -> String {
+    if self.line == 0 { self.name.clone() } else { format!("{}:{}", self.name, self.line) }
+  }
+}
+
+#[derive(Debug)]
+struct Frame {
+  function: usize,
+  started: Instant,
+  // the inclusive time of the calls made from it.
+  children: Duration,
+}
+
+/// `--profile`: counts calls and times functions, keyed by the token declaring them, and
+/// counts the statements run on each line. `WorngFunction::call` enters and leaves the
+/// functions and `Interpreter::interpret_statement` hits the lines.
+#[derive(Debug)]
+pub struct Profiler {
+  /// the script first.
+  pub functions: Vec<FunctionProfile>,
+  pub lines: BTreeMap<i32, usize>,
+  // `functions` index by the offset of the declaring token.
+  declared: HashMap<usize, usize>,
+  stack: Vec<Frame>,
+  // exclusive time by the `functions` indexes of a stack, outermost first.
+  stacks: HashMap<Vec<usize>, Duration>,
+}
+
+impl Profiler {
+  /// starts timing the script.
+  pub fn new() -> Profiler {
+    let script = FunctionProfile { name: "<script>".to_string(), line: 0, calls: 1, inclusive: Duration::ZERO, exclusive: Duration::ZERO };
+    Profiler {
+      functions: vec![script],
+      lines: BTreeMap::new(),
+      declared: HashMap::new(),
+      stack: vec![Frame { function: 0, started: Instant::now(), children: Duration::ZERO }],
+      stacks: HashMap::new(),
+    }
+  }
+
+  pub fn enter(&mut self, name: &Token) {
+    let next = self.functions.len();
+    let function = *self.declared.entry(name.offset).or_insert(next);
+    if function == next {
+      self.functions.push(FunctionProfile { name: name.lexeme.clone(), line: name.line, calls: 0, inclusive: Duration::ZERO, exclusive: Duration::ZERO });
+    }
+    self.functions[function].calls += 1;
+    self.stack.push(Frame { function, started: Instant::now(), children: Duration::ZERO });
+  }
+
+  pub fn leave(&mut self) {
+    let path: Vec<usize> = self.stack.iter().map(|frame| frame.function).collect();
+    let frame = match self.stack.pop() {
+      Some(frame) => frame,
+      None => return,
+    };
+
+    let elapsed = frame.started.elapsed();
+    let exclusive = elapsed.checked_sub(frame.children).unwrap_or_default();
+    let profile = &mut self.functions[frame.function];
+    profile.exclusive += exclusive;
+    if !self.stack.iter().any(|outer| outer.function == frame.function) {
+      profile.inclusive += elapsed;
+    }
+    *self.stacks.entry(path).or_default() += exclusive;
+
+    if let Some(caller) = self.stack.last_mut() {
+      caller.children += elapsed;
+    }
+  }
+
+  pub fn hit(&mut self, line: i32) {
+    *self.lines.entry(line).or_default() += 1;
+  }
+
+  /// stops the clock of the script and of the calls an error left running.
+  pub fn finish(&mut self) {
+    while !self.stack.is_empty() {
+      self.leave();
+    }
+  }
+
+  /// the functions by exclusive time and the hottest lines of `source`.
+  pub fn summary(&self, source: &str) -> String {
+    let mut functions: Vec<&FunctionProfile> = self.functions.iter().collect();
+    functions.sort_by(|left, right| right.exclusive.cmp(&left.exclusive).then(left.line.cmp(&right.line)));
+    let width = functions.iter().map(|function| function.label().len()).max().unwrap_or(0).max("function".len());
+
+    let mut out = format!("{:<width$}  {:>8}  {:>14}  {:>14}\n", "function", "calls", "inclusive ms", "exclusive ms", width = width);
+    for function in functions {
+      out.push_str(&format!(
+        "{:<width$}  {:>8}  {:>14.3}  {:>14.3}\n",
+        function.label(),
+        function.calls,
+        milliseconds(function.inclusive),
+        milliseconds(function.exclusive),
+        width = width
+      ));
+    }
+
+    let mut lines: Vec<(&i32, &usize)> = self.lines.iter().collect();
+    lines.sort_by(|left, right| right.1.cmp(left.1).then(left.0.cmp(right.0)));
+    let source: Vec<&str> = source.lines().collect();
+    out.push_str(&format!("\n{:>6}  {:>8}  source\n", "line", "hits"));
+    for (&line, &hits) in lines.into_iter().take(TOP_LINES) {
+      let text = source.get((line - 1) as usize).map(|text| text.trim()).unwrap_or("");
+      out.push_str(&format!("{:>6}  {:>8}  {}\n", line, hits, text));
+    }
+    out
+  }
+
+  /// one `outer;inner count` line per stack, the count in microseconds of exclusive time,
+  /// the collapsed format `flamegraph.pl` and inferno read.
+  pub fn folded(&self) -> String {
+    let mut stacks: Vec<String> = self
+      .stacks
+      .iter()
+      .map(|(path, time)| {
+        let names: Vec<String> = path.iter().map(|&function| self.functions[function].label()).collect();
+        format!("{} {}", names.join(";"), time.as_micros())
+      })
+      .collect();
+    stacks.sort();
+
+    let mut out = stacks.join("\n");
+    out.push('\n');
+    out
+  }
+}
+
+impl Default for Profiler {
+  fn default() -> Self {
+    Profiler::new()
+  }
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+  duration.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,160 @@
+use std::borrow::Cow::{self, Borrowed, Owned};
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::{Validator, ValidationContext, ValidationResult};
+use rustyline::{Context, Helper};
+
+use super::scanner::{Scanner, KEYWORDS};
+use super::parser::Parser;
+use super::token_type::TokenType;
+use super::environment::Environment;
+use super::worng_value::WorngValue;
+use super::error::{ParsingError, ScanningError};
+use super::{native_string, native_list, native_map, native_regex};
+
+const KEYWORD_COLOR: &str = "\x1b[35m";
+const STRING_COLOR: &str = "\x1b[32m";
+const NUMBER_COLOR: &str = "\x1b[33m";
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// rustyline helper for the REPL.
+/// it completes keywords and global names (or fields/methods after `.`),
+/// colors the line with the real `Scanner` and keeps reading lines
+/// until the `Parser` stops complaining about the end of input.
+pub struct ReplHelper {
+  globals: Rc<RefCell<Environment>>,
+}
+
+impl ReplHelper {
+  pub fn new(globals: Rc<RefCell<Environment>>) -> ReplHelper {
+    ReplHelper { globals }
+  }
+
+  fn candidates(&self, line: &str, start: usize) -> Vec<String> {
+    let before = &line[..start];
+
+    // only a plain identifier receiver is looked up, eg. `point.` but not `make().` .
+    if let Some(receiver) = before.strip_suffix('.') {
+      let receiver = &receiver[word_start(receiver, receiver.len())..];
+      return match self.globals.borrow().get_value(&receiver.to_string()) {
+        Ok(WorngValue::Instance(ref instance)) => {
+          let instance = instance.borrow();
+          let mut names = instance.field_names();
+          names.extend(instance.class().method_names());
+          names
+        },
+        Ok(WorngValue::Module(ref module)) => module.member_names(),
+        Ok(WorngValue::String(_)) => native_string::METHODS.iter().map(|(name, _, _)| name.to_string()).collect(),
+        Ok(WorngValue::List(_)) => native_list::METHODS.iter().map(|(name, _, _)| name.to_string()).collect(),
+        Ok(WorngValue::Map(_)) => native_map::METHODS.iter().map(|(name, _, _)| name.to_string()).collect(),
+        Ok(WorngValue::Regex(_)) => native_regex::METHODS.iter().map(|(name, _, _)| name.to_string()).collect(),
+        _ => Vec::new(),
+      };
+    }
+
+    let mut names: Vec<String> = KEYWORDS.iter().map(|(keyword, _)| keyword.to_string()).collect();
+    names.extend(self.globals.borrow().names());
+    names
+  }
+}
+
+impl Completer for ReplHelper {
+  type Candidate = Pair;
+
+  fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+    let start = word_start(line, pos);
+    let prefix = &line[start..pos];
+
+    let mut names: Vec<String> = self.candidates(line, start)
+      .into_iter()
+      .filter(|name| name.starts_with(prefix))
+      .collect();
+    names.sort();
+    names.dedup();
+
+    let pairs = names
+      .into_iter()
+      .map(|name| Pair { display: name.clone(), replacement: name })
+      .collect();
+
+    Ok((start, pairs))
+  }
+}
+
+impl Hinter for ReplHelper {}
+
+impl Highlighter for ReplHelper {
+  fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+    let tokens = Scanner::new(line).scan_tokens();
+    if tokens.len() <= 1 {
+      return Borrowed(line);
+    }
+
+    let chars: Vec<char> = line.chars().collect();
+    let mut highlighted = String::with_capacity(line.len());
+    let mut cursor = 0;
+
+    for token in tokens.iter() {
+      let color = match token.token_type {
+        TokenType::String => STRING_COLOR,
+        TokenType::Number => NUMBER_COLOR,
+        ref token_type if KEYWORDS.iter().any(|(_, keyword)| keyword == token_type) => KEYWORD_COLOR,
+        _ => continue,
+      };
+
+      let end = token.offset + token.lexeme.chars().count();
+      if token.offset < cursor || end > chars.len() {
+        continue;
+      }
+
+      highlighted.extend(&chars[cursor..token.offset]);
+      highlighted.push_str(color);
+      highlighted.extend(&chars[token.offset..end]);
+      highlighted.push_str(RESET_COLOR);
+      cursor = end;
+    }
+
+    highlighted.extend(&chars[cursor..]);
+    Owned(highlighted)
+  }
+
+  fn highlight_char(&self, _line: &str, _pos: usize) -> bool {
+    true
+  }
+}
+
+impl Validator for ReplHelper {
+  fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+    let mut scanner = Scanner::new(ctx.input());
+    let tokens = scanner.scan_tokens();
+
+    if scanner.errors.iter().any(|err| matches!(err, ScanningError::UnterminatedString(_))) {
+      return Ok(ValidationResult::Incomplete);
+    }
+
+    // anything else than running out of tokens is left for the interpreter to report.
+    match Parser::new(tokens).parse() {
+      Err(ref errors) if errors.iter().any(|err| matches!(err, ParsingError::UnexpectedEofError)) => {
+        Ok(ValidationResult::Incomplete)
+      },
+      _ => Ok(ValidationResult::Valid(None)),
+    }
+  }
+}
+
+impl Helper for ReplHelper {}
+
+// byte index where the identifier ending at `pos` starts.
+fn word_start(line: &str, pos: usize) -> usize {
+  line[..pos]
+    .char_indices()
+    .rev()
+    .take_while(|&(_, c)| c.is_alphanumeric() || c == '_')
+    .last()
+    .map(|(i, _)| i)
+    .unwrap_or(pos)
+}
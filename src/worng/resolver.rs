@@ -1,10 +1,12 @@
 use std::collections::HashMap;
 
-use super::statement::Stmt;
+use super::statement::{Stmt, StmtVisitorMut};
 use super::interpreter::Interpreter;
-use super::error::RuntimeError;
-use super::expr::Expr;
+use super::error::ParsingError;
+use super::expr::{Expr, ExprVisitorMut};
 use super::token::{ Token, Literal };
+use super::token_type::TokenType;
+use super::lint::{Rule, Warning};
 
 // to handle using this outside function's method scope.
 // eg. print this;
@@ -22,271 +24,497 @@ enum FunctionType {
   INITIALIZER
 }
 
+/// what declared a name. `Implicit` names (`this`, `super`, methods, natives) are never
+/// warned about and have no `Declaration`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BindingKind {
+  Variable,
+  Parameter,
+  Function,
+  Class,
+  Implicit,
+}
+
+impl BindingKind {
+  pub fn describe(self) -> &'static str {
+    match self {
+      BindingKind::Variable => "variable",
+      BindingKind::Parameter => "parameter",
+      BindingKind::Function => "function",
+      BindingKind::Class => "class",
+      BindingKind::Implicit => "name",
+    }
+  }
+}
+
+// what the linting needs to know about a name besides whether it's defined yet.
+#[derive(Clone)]
+struct Binding {
+  defined: bool,
+  kind: BindingKind,
+  token: Option<Token>,
+  used: bool,
+  // known for functions and classes that are never assigned to.
+  arity: Option<usize>,
+  // index into `Resolver::declarations`.
+  declaration: Option<usize>,
+}
+
+/// a name the program declares and every place reading or assigning it, for `worng lsp`.
+#[derive(Debug, Clone)]
+pub struct Declaration {
+  pub name: Token,
+  pub kind: BindingKind,
+  /// false for top level names.
+  pub local: bool,
+  pub references: Vec<Token>,
+}
+
 /// basically, Resolver is to figure out how many "distance" the variables in the "scope" are.
 /// it tells the interpreter how many scopes there are 
 /// between the current scope and the scope where the variable is defined.
 /// it not perform any arithmatic nor looping/short-curcuit branching statement.
 /// it's only job is to "run once".
+/// misplaced `return`, `this` and `super` are collected in `errors` instead of stopping the walk.
+/// what `worng lint` reports is collected in `warnings`, nothing else looks at them.
+/// the names that are declared and where they are used end up in `declarations`.
 pub struct Resolver {
-  scopes: Vec<HashMap<String, bool>>,
+  scopes: Vec<HashMap<String, Binding>>,
+  // top level names aren't resolved, they are only kept for the warnings.
+  globals: HashMap<String, Binding>,
   current_class: Option<ClassType>,
-  current_function: Option<FunctionType>
+  current_function: Option<FunctionType>,
+  pub errors: Vec<ParsingError>,
+  pub warnings: Vec<Warning>,
+  pub declarations: Vec<Declaration>
 }
 
 impl Resolver {
   pub fn new() -> Resolver {
     Resolver{
       scopes: Vec::new(),
+      globals: HashMap::new(),
       current_class: None,
-      current_function: None
+      current_function: None,
+      errors: Vec::new(),
+      warnings: Vec::new(),
+      declarations: Vec::new()
     }
   }
 
+  /// a global the program doesn't declare itself, like the natives.
+  pub fn define_global(&mut self, name: &str, arity: Option<usize>) {
+    let mut binding = Binding::new(None, BindingKind::Implicit);
+    binding.arity = arity;
+    self.globals.insert(name.to_string(), binding);
+  }
+
   pub fn resolve(&mut self, statement: &mut Vec<Stmt>) {
+    // functions may assign globals declared further down.
+    if self.scopes.is_empty() {
+      for stmt in statement.iter() {
+        self.declare_global(stmt);
+      }
+    }
+
+    let mut after_return = statement.iter().skip_while(|stmt| !matches!(stmt, Stmt::Return(..)));
+    if let (Some(Stmt::Return(keyword, _)), Some(unreachable)) = (after_return.next(), after_return.next()) {
+      let token = first_token(unreachable).unwrap_or(keyword).clone();
+      self.warn(Rule::UnreachableCode, &token, "Unreachable code after `return`.".to_string());
+    }
+
     for ref mut stmt in statement {
-      self.resolve_statement(stmt) 
+      self.visit_stmt_mut(stmt) 
     }
   }
 
-  fn resolve_statement(&mut self, stmt: &mut Stmt) {
-    match *stmt{
-      Stmt::Block(ref mut statements) => {
-        self.begin_scope();
-        self.resolve(statements);
-        self.end_scope();
-      },
-      Stmt::Var(ref token, ref mut expr) => {
-        self.declare(token);
-        self.resolve_expression(expr);
-        self.define(token.lexeme.clone());
-      },
-      Stmt::Func(ref name, ref params, ref mut body) => {
-        self.declare(name);
-        self.define(name.lexeme.clone());
-        self.resolve_function(params, body, Some(FunctionType::FUNCTION) );
-      },
-      Stmt::Class(ref name, ref mut superclass, ref mut methods) => {
-        self.declare(name);
-        let enclosing_class_type = self.current_class.clone();
-        self.current_class = Some(ClassType::Class);
-
-        if let &mut Some(ref mut superclass) = superclass {
-          self.current_class = Some(ClassType::SubClass);
-          self.resolve_expression(superclass);
-          self.begin_scope();
-          self.define("super".to_string());
-        }
+  fn resolve_function(&mut self, params: &Vec<Token>, body: &mut Stmt, func_type: Option<FunctionType>) {
 
-        self.begin_scope();
-        self.define("this".to_string());
-
-        if let Some(ref _super ) = superclass {
-          match _super {
-            Expr::Var(ref token, _) => {
-              if name.lexeme == token.lexeme {
-                panic!("A class cannot inherit from itself.");
-              }
-            },
-            _ => {}
-          }
-        }
+    let enclosing_function = self.current_function.clone();
+    self.current_function = func_type;
 
-        for method in methods {
-          match method {
-            &mut Stmt::Func(ref token, ref params, ref mut body) => {
-                self.declare(token);
-                self.define(name.lexeme.clone());
+    self.begin_scope();
+    for param in params {
+      self.declare(param, BindingKind::Parameter);
+      self.define(param.lexeme.clone());
+    }
 
-                let function_type = match token.lexeme == "init" {
-                  true => FunctionType::INITIALIZER,
-                  false => FunctionType::METHOD
-                };
+    match body {
+      &mut Stmt::Block(ref mut stmts) => self.resolve(stmts),
+      _ => panic!("The body of a function be Stmt::Block"),
+    }
 
-                self.resolve_function(params, body, Some(function_type));
-            }
-            _ => {}
-          }
-        }
+    
+    self.end_scope();
+    self.current_function = enclosing_function;
+  }
+
+  fn resolve_local(&self, lexeme: String) -> Option<usize> {
+    for (i, scope) in self.scopes.iter().rev().enumerate() {
+      if scope.contains_key(&lexeme) {
+        return Some(i);
+      }
+    }
 
-        self.end_scope();
+    None
+  }
 
-        if superclass.is_some() {
-          self.end_scope();
-        }
+  
+  fn error(&mut self, token: &Token, message: &str) {
+    self.errors.push(ParsingError::ResolvingError(token.clone(), message.to_string()));
+  }
 
-        self.current_class = enclosing_class_type;
-        self.define(name.lexeme.clone());
-      },
-      Stmt::Expr(ref mut expr) => {
-        self.resolve_expression(expr);
-      },
-      Stmt::If(ref mut condition, ref mut then_stmt, ref mut else_stmt) => {
-        self.resolve_expression(condition);
-        self.resolve_statement(then_stmt);
-        if let Some(ref mut else_statement) = **else_stmt { 
-          self.resolve_statement(else_statement); 
-        }
-      },
-      Stmt::Print(ref mut expr) => {
-        self.resolve_expression(expr);
-      },
-      Stmt::Return(_, ref mut expr) => {
-        {
-          let function_type = self.current_function
-              .as_ref() // return None = the function is not bound to any scope, thus it is at global scope(top level).
-              .expect("UnexpectedTokenError: Cannot use `return` at the top level.");
+  fn warn(&mut self, rule: Rule, token: &Token, message: String) {
+    self.warnings.push(Warning { rule, token: token.clone(), message });
+  }
 
+  fn begin_scope(&mut self) {
+    self.scopes.push(HashMap::new());
+  }
 
-            if *function_type == FunctionType::INITIALIZER {
-              panic!("UnexpectedTokenError: Cannot use `return` on an initializer.")
-            }
-        }
+  fn end_scope(&mut self) {
+    let scope = match self.scopes.pop() {
+      Some(scope) => scope,
+      None => return,
+    };
+
+    for (name, binding) in scope {
+      let token = match binding.token {
+        Some(ref token) if !binding.used && !name.starts_with('_') => token,
+        _ => continue,
+      };
+      match binding.kind {
+        BindingKind::Parameter => {
+          self.warn(Rule::UnusedParameter, token, format!("Parameter '{}' is never used.", name));
+        },
+        BindingKind::Variable | BindingKind::Function | BindingKind::Class => {
+          let message = format!("Local {} '{}' is never used.", binding.kind.describe(), name);
+          self.warn(Rule::UnusedVariable, token, message);
+        },
+        BindingKind::Implicit => {},
+      }
+    }
+  }
 
+  fn declare(&mut self, name: &Token, kind: BindingKind) {
+    if self.scopes.is_empty() {
+      return;
+    }
 
-      self.resolve_expression(expr)
-    },
-      Stmt::While(ref mut condition, ref mut body) => {
-        self.resolve_expression(condition);
-        self.resolve_statement(body);
+    if kind != BindingKind::Implicit {
+      let outer = self.scopes[..self.scopes.len() - 1].iter().rev().chain(std::iter::once(&self.globals));
+      let shadowed = outer
+        .filter_map(|scope| scope.get(&name.lexeme))
+        .find(|binding| binding.kind != BindingKind::Implicit)
+        .and_then(|binding| binding.token.as_ref().map(|token| (binding.kind, token.line)));
+      if let Some((shadowed_kind, line)) = shadowed {
+        let message = format!("'{}' shadows the {} declared on line {}.", name.lexeme, shadowed_kind.describe(), line);
+        self.warn(Rule::Shadowing, name, message);
       }
     }
 
+    let mut binding = Binding::new(Some(name.clone()), kind);
+    if kind != BindingKind::Implicit {
+      binding.declaration = Some(self.declarations.len());
+      self.declarations.push(Declaration { name: name.clone(), kind, local: true, references: Vec::new() });
+    }
+    if let Some(scope) = self.scopes.last_mut() {
+      scope.insert(name.lexeme.clone(), binding);
+    }
   }
 
-  fn resolve_function(&mut self, params: &Vec<Token>, body: &mut Stmt, func_type: Option<FunctionType>) {
+  fn define(&mut self, name: String) {
+    if let Some(scope) = self.scopes.last_mut() {
+      scope.entry(name).or_insert_with(|| Binding::new(None, BindingKind::Implicit)).defined = true;
+    }
+  }
 
-    let enclosing_function = self.current_function.clone();
-    self.current_function = func_type;
+  fn declare_global(&mut self, stmt: &Stmt) {
+    let (name, kind, arity) = match *stmt {
+      Stmt::Var(ref name, _) => (name, BindingKind::Variable, None),
+      Stmt::Func(ref name, ref params, _) => (name, BindingKind::Function, Some(params.len())),
+      Stmt::Class(ref name, ref superclass, ref methods) => (name, BindingKind::Class, class_arity(superclass.is_some(), methods)),
+      _ => return,
+    };
+    let mut binding = Binding::new(Some(name.clone()), kind);
+    binding.defined = true;
+    binding.arity = arity;
+    binding.declaration = Some(self.declarations.len());
+    self.declarations.push(Declaration { name: name.clone(), kind, local: false, references: Vec::new() });
+    self.globals.insert(name.lexeme.clone(), binding);
+  }
+
+  fn set_arity(&mut self, name: &str, arity: Option<usize>) {
+    if let Some(binding) = self.binding(name) {
+      binding.arity = arity;
+    }
+  }
+
+  // methods and the class name inside them are in scope without a declaration, the
+  // declaration a name refers to is the closest one that has one.
+  fn refer(&mut self, token: &Token) {
+    let declaration = self.scopes
+      .iter()
+      .rev()
+      .chain(std::iter::once(&self.globals))
+      .find_map(|scope| scope.get(&token.lexeme).and_then(|binding| binding.declaration));
+    if let Some(index) = declaration {
+      self.declarations[index].references.push(token.clone());
+    }
+  }
+
+  // the binding `name` refers to from the current scope, like `resolve_local` finds it.
+  fn binding(&mut self, name: &str) -> Option<&mut Binding> {
+    let globals = &mut self.globals;
+    match self.scopes.iter_mut().rev().find(|scope| scope.contains_key(name)) {
+      Some(scope) => scope.get_mut(name),
+      None => globals.get_mut(name),
+    }
+  }
+}
+
+impl Default for Resolver {
+  fn default() -> Self {
+    Resolver::new()
+  }
+}
 
+impl StmtVisitorMut<(), ()> for Resolver {
+  fn visit_block_stmt_mut(&mut self, statements: &mut Vec<Stmt>) {
     self.begin_scope();
-    for param in params {
-      self.declare(&param);
-      self.define(param.lexeme.clone());
+    self.resolve(statements);
+    self.end_scope();
+  }
+
+  fn visit_var_stmt_mut(&mut self, token: &mut Token, expr: &mut Expr) {
+    self.declare(token, BindingKind::Variable);
+    self.visit_expr_mut(expr);
+    self.define(token.lexeme.clone());
+  }
+
+  fn visit_func_stmt_mut(&mut self, name: &mut Token, params: &mut Vec<Token>, body: &mut Stmt) {
+    self.declare(name, BindingKind::Function);
+    self.define(name.lexeme.clone());
+    self.set_arity(&name.lexeme, Some(params.len()));
+    self.resolve_function(params, body, Some(FunctionType::FUNCTION) );
+  }
+
+  fn visit_class_stmt_mut(&mut self, name: &mut Token, superclass: &mut Option<Expr>, methods: &mut Vec<Stmt>) {
+    self.declare(name, BindingKind::Class);
+    self.set_arity(&name.lexeme, class_arity(superclass.is_some(), methods));
+    let enclosing_class_type = self.current_class.clone();
+    self.current_class = Some(ClassType::Class);
+
+    if let &mut Some(ref mut superclass) = superclass {
+      self.current_class = Some(ClassType::SubClass);
+      self.visit_expr_mut(superclass);
+      self.begin_scope();
+      self.define("super".to_string());
     }
 
-    match body {
-      &mut Stmt::Block(ref mut stmts) => for stmt in stmts {
-        self.resolve_statement(stmt);
-      },
-      _ => panic!("The body of a function be Stmt::Block"),
+    self.begin_scope();
+    self.define("this".to_string());
+
+    if let Some(Expr::Var(ref token, _)) = *superclass {
+      if name.lexeme == token.lexeme {
+        self.error(token, "A class cannot inherit from itself.");
+      }
+    }
+
+    for method in methods {
+      if let Stmt::Func(ref token, ref params, ref mut body) = *method {
+        self.declare(token, BindingKind::Implicit);
+        self.define(name.lexeme.clone());
+
+        let function_type = match token.lexeme == "init" {
+          true => FunctionType::INITIALIZER,
+          false => FunctionType::METHOD
+        };
+
+        self.resolve_function(params, body, Some(function_type));
+      }
     }
 
-    
     self.end_scope();
-    self.current_function = enclosing_function;
+
+    if superclass.is_some() {
+      self.end_scope();
+    }
+
+    self.current_class = enclosing_class_type;
+    self.define(name.lexeme.clone());
   }
 
-  fn resolve_expression(&mut self, expr: &mut Expr){
-    match *expr {
-      Expr::Var(ref token, ref mut distance) => {
-        if let Some(scope) = self.scopes.last() {
-          if let Some(is_var_available) = scope.get(&token.lexeme) {
-            if !is_var_available {
-              // println!("error variable not available in scope.")
-            }
-          }
-        }
-        *distance = self.resolve_local(token.lexeme.clone());
-      },
-      Expr::Assign(ref token, ref mut expr, ref mut distance) => {
-        self.resolve_expression(expr);
-        *distance = self.resolve_local(token.lexeme.clone());
-      },
-      Expr::Binary(ref mut left , _ , ref mut right) => {
-        self.resolve_expression(left);
-        self.resolve_expression(right);
-      },
-      Expr::Call(ref mut callee, _ , ref mut arguments) => {
-        self.resolve_expression(callee);
-        for argument in arguments {
-          self.resolve_expression(argument);
-        }
-      },
-      Expr::Get(ref mut obj, ref name) => {
-        self.resolve_expression( obj )
-      },
-      Expr::Set(ref mut object, ref name, ref mut value) => {
-        self.resolve_expression(value);
-        self.resolve_expression(object);
-      },
-      Expr::Super(ref keyword, ref method, ref mut distance ) => {
-        let class_type = self.current_class
-                    .as_ref()
-                    .expect("UnexpectedTokenError: Cannot use `super` outside of a method.");
-
-        match class_type {
-          &ClassType::Class => {
-            panic!("UnexpectedTokenError: Cannot use `super` without a superclass.")
-          },
-          _ => {
-            if let Some(scope) = self.scopes.last() {
-              if let Some(is_var_available) = scope.get(&keyword.lexeme) {
-                if !is_var_available {
-                    // TODO: handle Error.
-                }
-              }
-            }
-            *distance = self.resolve_local(keyword.lexeme.to_string());
-          },
+  fn visit_return_stmt_mut(&mut self, keyword: &mut Token, expr: &mut Expr) {
+    // None = the function is not bound to any scope, thus it is at global scope(top level).
+    match self.current_function {
+      None => self.error(keyword, "Cannot use `return` at the top level."),
+      Some(FunctionType::INITIALIZER) => self.error(keyword, "Cannot use `return` on an initializer."),
+      _ => {}
+    }
+
+
+    self.visit_expr_mut(expr)
+  }
+}
+
+impl ExprVisitorMut<()> for Resolver {
+  fn visit_var_expr_mut(&mut self, token: &mut Token, distance: &mut Option<usize>) {
+    if let Some(scope) = self.scopes.last() {
+      if let Some(is_var_available) = scope.get(&token.lexeme) {
+        if !is_var_available.defined {
+          // println!("error variable not available in scope.")
         }
+      }
+    }
+    *distance = self.resolve_local(token.lexeme.clone());
+    if let Some(binding) = self.binding(&token.lexeme) {
+      binding.used = true;
+    }
+    self.refer(token);
+  }
+
+  fn visit_assign_expr_mut(&mut self, token: &mut Token, expr: &mut Expr, distance: &mut Option<usize>) {
+    self.visit_expr_mut(expr);
+    *distance = self.resolve_local(token.lexeme.clone());
+    self.refer(token);
+    match self.binding(&token.lexeme) {
+      // it may not be the function it was declared as anymore.
+      Some(binding) => binding.arity = None,
+      None => {
+        let message = format!("Assignment to '{}', which is never declared.", token.lexeme);
+        self.warn(Rule::UndeclaredAssignment, token, message);
       },
-      Expr::Grouping(ref mut expression) => {
-        self.resolve_expression(expression);
-      },
-      Expr::Literal(ref expression) => {},
-      Expr::Logical(ref mut left, _ , ref mut right) => {
-        self.resolve_expression(left);
-        self.resolve_expression(right);
+    }
+  }
+
+  fn visit_binary_expr_mut(&mut self, left: &mut Expr, operator: &mut Token, right: &mut Expr) {
+    if is_comparison(operator) && same_place(left, right) {
+      let message = format!("'{}' compares a value to itself.", operator.lexeme);
+      self.warn(Rule::SelfComparison, operator, message);
+    }
+    self.visit_expr_mut(left);
+    self.visit_expr_mut(right);
+  }
+
+  fn visit_call_expr_mut(&mut self, callee: &mut Expr, _paren: &mut Token, arguments: &mut Vec<Expr>) {
+    if let Expr::Var(ref name, _) = *callee {
+      let arity = self.binding(&name.lexeme).and_then(|binding| binding.arity);
+      if let Some(arity) = arity.filter(|&arity| arity != arguments.len()) {
+        let plural = if arity == 1 { "" } else { "s" };
+        let message = format!("'{}' takes {} argument{} but is called with {}.", name.lexeme, arity, plural, arguments.len());
+        self.warn(Rule::ArityMismatch, name, message);
+      }
+    }
+    self.visit_expr_mut(callee);
+    for argument in arguments {
+      self.visit_expr_mut(argument);
+    }
+  }
+
+  fn visit_set_expr_mut(&mut self, object: &mut Expr, _name: &mut Token, value: &mut Expr) {
+    self.visit_expr_mut(value);
+    self.visit_expr_mut(object);
+  }
+
+  fn visit_super_expr_mut(&mut self, keyword: &mut Token, _method: &mut Token, distance: &mut Option<usize>) {
+    match self.current_class {
+      None => {
+        self.error(keyword, "Cannot use `super` outside of a method.")
       },
-      Expr::Unary(_, ref mut right) => {
-        self.resolve_expression(right);
+      Some(ClassType::Class) => {
+        self.error(keyword, "Cannot use `super` without a superclass.")
       },
-      Expr::This(ref token, ref mut distance ) => {
-        if self.current_class.is_none() {
-          panic!("UnexpectedTokenError: Cannot use `this` outside of a method.");
-        }
-
+      _ => {
         if let Some(scope) = self.scopes.last() {
-          if let Some(is_var_available) = scope.get(&token.lexeme) {
-            if !is_var_available {
-              // println!("error variable not available in scope.")
+          if let Some(is_var_available) = scope.get(&keyword.lexeme) {
+            if !is_var_available.defined {
+                // TODO: handle Error.
             }
           }
         }
-        *distance =  self.resolve_local(token.lexeme.to_string());
-      }
+        *distance = self.resolve_local(keyword.lexeme.to_string());
+      },
     }
-
   }
 
-  fn resolve_local(&self, lexeme: String) -> Option<usize> {
-    for (i, scope) in self.scopes.iter().rev().enumerate() {
-      if scope.contains_key(&lexeme) {
-        return Some(i);
+  fn visit_this_expr_mut(&mut self, token: &mut Token, distance: &mut Option<usize>) {
+    if self.current_class.is_none() {
+      self.error(token, "Cannot use `this` outside of a method.");
+    }
+
+    if let Some(scope) = self.scopes.last() {
+      if let Some(is_var_available) = scope.get(&token.lexeme) {
+        if !is_var_available.defined {
+          // println!("error variable not available in scope.")
+        }
       }
     }
+    *distance =  self.resolve_local(token.lexeme.to_string());
+  }
+}
 
-    None
+impl Binding {
+  fn new(token: Option<Token>, kind: BindingKind) -> Binding {
+    Binding { defined: false, kind, token, used: false, arity: None, declaration: None }
   }
+}
 
-  
-  fn begin_scope(&mut self) {
-    self.scopes.push(HashMap::new());
+// calling a class calls its `init`, an inherited one isn't known here.
+fn class_arity(has_superclass: bool, methods: &[Stmt]) -> Option<usize> {
+  let init = methods.iter().find_map(|method| match *method {
+    Stmt::Func(ref name, ref params, _) if name.lexeme == "init" => Some(params.len()),
+    _ => None,
+  });
+  match init {
+    Some(arity) => Some(arity),
+    None if has_superclass => None,
+    None => Some(0),
   }
+}
 
-  fn end_scope(&mut self) {
-    self.scopes.pop();
+fn is_comparison(operator: &Token) -> bool {
+  matches!(
+    operator.token_type,
+    TokenType::EqualEqual | TokenType::BangEqual | TokenType::Less | TokenType::LessEqual | TokenType::Greater | TokenType::GreaterEqual
+  )
+}
+
+// the same variable or property on both sides, reading them has no side effects.
+fn same_place(left: &Expr, right: &Expr) -> bool {
+  match (left, right) {
+    (Expr::Grouping(left), right) | (right, Expr::Grouping(left)) => same_place(left, right),
+    (Expr::Var(left, _), Expr::Var(right, _)) => left.lexeme == right.lexeme,
+    (Expr::This(..), Expr::This(..)) => true,
+    (Expr::Get(left, left_name), Expr::Get(right, right_name)) => left_name.lexeme == right_name.lexeme && same_place(left, right),
+    _ => false,
   }
+}
 
-  fn declare(&mut self, name: &Token) {
-    if let Some(scope) = self.scopes.last_mut() {
-      scope.insert(name.lexeme.clone(), false);
-    }
+// where a statement starts, a literal has no token.
+pub(crate) fn first_token(stmt: &Stmt) -> Option<&Token> {
+  match *stmt {
+    Stmt::Expr(ref expr) => first_expr_token(expr),
+    Stmt::Print(ref token, _)
+    | Stmt::If(ref token, _, _, _)
+    | Stmt::While(ref token, _, _)
+    | Stmt::Var(ref token, _)
+    | Stmt::Func(ref token, _, _)
+    | Stmt::Class(ref token, _, _)
+    | Stmt::Return(ref token, _) => Some(token),
+    Stmt::Block(ref statements) => statements.first().and_then(first_token),
   }
+}
 
-  fn define(&mut self, name: String) {
-    if let Some(scope) = self.scopes.last_mut() {
-      scope.insert(name.clone(), true);
-    }
+fn first_expr_token(expr: &Expr) -> Option<&Token> {
+  match *expr {
+    Expr::Unary(ref token, _)
+    | Expr::Var(ref token, _)
+    | Expr::Assign(ref token, _, _)
+    | Expr::This(ref token, _)
+    | Expr::Super(ref token, _, _) => Some(token),
+    Expr::Binary(ref left, ref operator, _) | Expr::Logical(ref left, ref operator, _) => first_expr_token(left).or(Some(operator)),
+    Expr::Call(ref inner, _, _) | Expr::Get(ref inner, _) | Expr::Set(ref inner, _, _) | Expr::Grouping(ref inner) => first_expr_token(inner),
+    Expr::Literal(_) => None,
   }
 }
\ No newline at end of file
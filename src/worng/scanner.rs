@@ -3,10 +3,34 @@ use std::convert::TryInto;
 
 use super::token_type::*;
 use super::token::*;
+use super::error::ScanningError;
+
+pub const KEYWORDS: [(&str, TokenType); 16] = [
+  ("and", TokenType::And),
+  ("class", TokenType::Class),
+  ("else", TokenType::Else),
+  ("false", TokenType::False),
+  ("for", TokenType::For),
+  ("fun", TokenType::Func),
+  ("if", TokenType::If),
+  ("nil", TokenType::Nil),
+  ("or", TokenType::Or),
+  ("print", TokenType::Print),
+  ("return", TokenType::Return),
+  ("super", TokenType::Super),
+  ("this", TokenType::This),
+  ("true", TokenType::True),
+  ("var", TokenType::Var),
+  ("while", TokenType::While),
+];
 
 pub struct Scanner {
   source: Vec<char>,
   tokens: Vec<Token>,
+  pub errors: Vec<ScanningError>,
+  /// `//` and `/* */` comments, left out of the tokens since the parser has no use for them.
+  /// their `line` is the one they start on.
+  pub comments: Vec<Token>,
   start: usize ,
   current: usize,
   line: i32
@@ -17,6 +41,8 @@ impl Scanner{
     Scanner{
       source: source.chars().collect(),
       tokens: Vec::<Token>::new(),
+      errors: Vec::new(),
+      comments: Vec::new(),
       line: 1,
       current: 0,
       start: 0
@@ -24,25 +50,10 @@ impl Scanner{
   }
 
   pub fn is_keyword(&self, keys: &str) -> Option<TokenType>{
-    match keys {
-      "and"  => Some(TokenType::And),
-      "class"  => Some(TokenType::Class),
-      "else"  => Some(TokenType::Else),
-      "false"  => Some(TokenType::False),
-      "for"  => Some(TokenType::For),
-      "fun"  => Some(TokenType::Func),
-      "if"  => Some(TokenType::If),
-      "nil"  => Some(TokenType::Nil),
-      "or"  => Some(TokenType::Or),
-      "print"  => Some(TokenType::Print),
-      "return"  => Some(TokenType::Return),
-      "super"  => Some(TokenType::Super),
-      "this"  => Some(TokenType::This),
-      "true"  => Some(TokenType::True),
-      "var"  => Some(TokenType::Var),
-      "while"  => Some(TokenType::While),
-      other => None
-    }
+    KEYWORDS
+      .iter()
+      .find(|(keyword, _)| *keyword == keys)
+      .map(|(_, token_type)| token_type.clone())
   }
 
   pub fn scan_tokens(&mut self) -> Vec<Token> {
@@ -51,7 +62,7 @@ impl Scanner{
       self.scan_token();
     }
 
-    self.tokens.push(Token::new(TokenType::EOF, String::from(""), None, self.line));
+    self.tokens.push(Token::new(TokenType::EOF, String::from(""), None, self.line, self.current));
     self.tokens.clone()
   }
 
@@ -67,11 +78,7 @@ impl Scanner{
       '-' =>  self.add_token(TokenType::Minus, None),
       '+' =>  self.add_token(TokenType::Plus, None),
       ';' =>  self.add_token(TokenType::Semicolon, None),
-      '*' =>  {
-        if !self.is_match('/') {
-          self.add_token(TokenType::Star, None)
-        } 
-      }, 
+      '*' =>  self.add_token(TokenType::Star, None),
       '!' =>  match self.is_match('=') {
         true => self.add_token(TokenType::BangEqual, None),
         false => self.add_token(TokenType::Bang, None)
@@ -93,10 +100,9 @@ impl Scanner{
           while self.peek() != '\n' && !self.is_at_end() { 
             self.advance();
           }
+          self.add_comment(self.line);
         } else if self.is_match('*'){
-          while self.peek() != '*' && self.peek_next() != '/' && !self.is_at_end() { 
-            self.advance();
-          } 
+          self.block_comment();
         } else {
           self.add_token(TokenType::Slash, None)
         }       
@@ -112,7 +118,7 @@ impl Scanner{
         } else if is_alpha(any) {
           self.identifier();
         } else {
-          println!("Line: {} Unexpected character.", self.line);
+          self.errors.push(ScanningError::UnexpectedCharacter(self.line, any));
         }
       }  
     } 
@@ -168,7 +174,7 @@ impl Scanner{
     }
 
     if self.is_at_end() {
-      println!("Unterminated string. Line: {}", self.line);
+      self.errors.push(ScanningError::UnterminatedString(self.line));
       return;
     }
     self.advance();
@@ -176,6 +182,25 @@ impl Scanner{
     self.add_token(TokenType::String, Some(Literal::String(value)));
   }
 
+  // an unterminated one runs to the end of the source.
+  fn block_comment(&mut self) {
+    let line = self.line;
+    while !(self.peek() == '*' && self.peek_next() == '/') && !self.is_at_end() {
+      if self.peek() == '\n' { self.line += 1 };
+      self.advance();
+    }
+    if !self.is_at_end() {
+      self.advance();
+      self.advance();
+    }
+    self.add_comment(line);
+  }
+
+  fn add_comment(&mut self, line: i32) {
+    let text: String = self.source[self.start..self.current].iter().collect();
+    self.comments.push(Token::new(TokenType::Comment, text, None, line, self.start));
+  }
+
   fn is_match(&mut self, expected: char) -> bool {
     if self.is_at_end() { return false };
     if self.source[self.current] != expected {
@@ -209,7 +234,7 @@ impl Scanner{
 
   fn add_token(&mut self, kind: TokenType, literal: Option<Literal>) {
     let text: String = self.source[self.start..self.current].iter().collect();
-    self.tokens.push(Token::new(kind, text, literal, self.line));
+    self.tokens.push(Token::new(kind, text, literal, self.line, self.start));
   }
 }
 
@@ -222,5 +247,5 @@ fn is_alpha(c: char)  -> bool {
 }
 
 fn is_alpha_numeric(c: char) -> bool {
-  return is_alpha(c) || is_alpha(c);
+  return is_alpha(c) || c.is_ascii_digit();
 }
@@ -1,52 +1,417 @@
-use std::rc::Rc;
+// generated by `cargo run --bin generate_ast -- src/worng` from the spec in
+// src/tool/GenerateAst.rs, change that instead.
 
-use super::expr::Expr;
-use super::token::{ Literal, Token };
-use super::error::ParsingError;
+use std::fmt;
+
+use super::ast_printer::{list, print_names};
+use super::expr::{print_expr, Expr, ExprVisitor, ExprVisitorMut};
+use super::token::Token;
+use super::visitor::VisitResult;
 
 #[derive(Debug, Clone)]
 pub enum Stmt {
+  /// expression.
   Expr(Expr),
-  Print(Expr),
+  /// keyword, expression. the keyword gives the statement a line, like those of `If` and `While`.
+  Print(Token, Expr),
+  /// name, initializer. nil without an initializer.
   Var(Token, Expr),
+  /// statements.
   Block(Vec<Stmt>),
+  /// name, superclass, methods.
   Class(Token, Option<Expr>, Vec<Stmt>),
-  If(Expr, Box<Stmt>, Box<Option<Stmt>>),
-  While(Expr, Box<Stmt>),
+  /// keyword, condition, then_branch, else_branch.
+  If(Token, Expr, Box<Stmt>, Box<Option<Stmt>>),
+  /// keyword, condition, body. a `for` loop is a `While` with the `for` keyword, in a `Block` after its initializer.
+  While(Token, Expr, Box<Stmt>),
+  /// name, params, body.
   Func(Token, Vec<Token>, Box<Stmt>),
-  Return(Token, Box<Expr>)
+  /// keyword, value. nil without a value.
+  Return(Token, Box<Expr>),
+}
+
+/// a pass over statements giving an `R` for each. `visit_stmt` hands each kind to its own method,
+/// a pass overrides those of the kinds it looks at, the others visit the parts, giving the
+/// first break one of them gives, or `R::walked()`.
+pub trait StmtVisitor<R: VisitResult, E: VisitResult<Break = R::Break>>: ExprVisitor<E> {
+  fn visit_stmt(&mut self, stmt: &Stmt) -> R {
+    match *stmt {
+      Stmt::Expr(ref expression) => self.visit_expr_stmt(expression),
+      Stmt::Print(ref keyword, ref expression) => self.visit_print_stmt(keyword, expression),
+      Stmt::Var(ref name, ref initializer) => self.visit_var_stmt(name, initializer),
+      Stmt::Block(ref statements) => self.visit_block_stmt(statements),
+      Stmt::Class(ref name, ref superclass, ref methods) => self.visit_class_stmt(name, superclass, methods),
+      Stmt::If(ref keyword, ref condition, ref then_branch, ref else_branch) => self.visit_if_stmt(keyword, condition, then_branch, else_branch),
+      Stmt::While(ref keyword, ref condition, ref body) => self.visit_while_stmt(keyword, condition, body),
+      Stmt::Func(ref name, ref params, ref body) => self.visit_func_stmt(name, params, body),
+      Stmt::Return(ref keyword, ref value) => self.visit_return_stmt(keyword, value),
+    }
+  }
+
+  fn visit_expr_stmt(&mut self, expression: &Expr) -> R {
+    if let Some(stop) = self.visit_expr(expression).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_print_stmt(&mut self, _keyword: &Token, expression: &Expr) -> R {
+    if let Some(stop) = self.visit_expr(expression).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_var_stmt(&mut self, _name: &Token, initializer: &Expr) -> R {
+    if let Some(stop) = self.visit_expr(initializer).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_block_stmt(&mut self, statements: &[Stmt]) -> R {
+    for item in statements.iter() {
+      if let Some(stop) = self.visit_stmt(item).into_break() {
+        return R::from_break(stop);
+      }
+    }
+    R::walked()
+  }
+
+  fn visit_class_stmt(&mut self, _name: &Token, superclass: &Option<Expr>, methods: &[Stmt]) -> R {
+    if let Some(ref superclass) = *superclass {
+      if let Some(stop) = self.visit_expr(superclass).into_break() {
+        return R::from_break(stop);
+      }
+    }
+    for item in methods.iter() {
+      if let Some(stop) = self.visit_stmt(item).into_break() {
+        return R::from_break(stop);
+      }
+    }
+    R::walked()
+  }
+
+  fn visit_if_stmt(&mut self, _keyword: &Token, condition: &Expr, then_branch: &Stmt, else_branch: &Option<Stmt>) -> R {
+    if let Some(stop) = self.visit_expr(condition).into_break() {
+      return R::from_break(stop);
+    }
+    if let Some(stop) = self.visit_stmt(then_branch).into_break() {
+      return R::from_break(stop);
+    }
+    if let Some(ref else_branch) = *else_branch {
+      if let Some(stop) = self.visit_stmt(else_branch).into_break() {
+        return R::from_break(stop);
+      }
+    }
+    R::walked()
+  }
+
+  fn visit_while_stmt(&mut self, _keyword: &Token, condition: &Expr, body: &Stmt) -> R {
+    if let Some(stop) = self.visit_expr(condition).into_break() {
+      return R::from_break(stop);
+    }
+    if let Some(stop) = self.visit_stmt(body).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_func_stmt(&mut self, _name: &Token, _params: &[Token], body: &Stmt) -> R {
+    if let Some(stop) = self.visit_stmt(body).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_return_stmt(&mut self, _keyword: &Token, value: &Expr) -> R {
+    if let Some(stop) = self.visit_expr(value).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+}
+
+/// visits the nodes directly in `stmt`, in source order, giving the first break or `R::walked()`.
+pub fn walk_stmt<R: VisitResult, E: VisitResult<Break = R::Break>, V: StmtVisitor<R, E> + ?Sized>(visitor: &mut V, stmt: &Stmt) -> R {
+  match *stmt {
+    Stmt::Expr(ref expression) => {
+      if let Some(stop) = visitor.visit_expr(expression).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Stmt::Print(_, ref expression) => {
+      if let Some(stop) = visitor.visit_expr(expression).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Stmt::Var(_, ref initializer) => {
+      if let Some(stop) = visitor.visit_expr(initializer).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Stmt::Block(ref statements) => {
+      for item in statements.iter() {
+        if let Some(stop) = visitor.visit_stmt(item).into_break() {
+          return R::from_break(stop);
+        }
+      }
+    },
+    Stmt::Class(_, ref superclass, ref methods) => {
+      if let Some(ref superclass) = *superclass {
+        if let Some(stop) = visitor.visit_expr(superclass).into_break() {
+          return R::from_break(stop);
+        }
+      }
+      for item in methods.iter() {
+        if let Some(stop) = visitor.visit_stmt(item).into_break() {
+          return R::from_break(stop);
+        }
+      }
+    },
+    Stmt::If(_, ref condition, ref then_branch, ref else_branch) => {
+      if let Some(stop) = visitor.visit_expr(condition).into_break() {
+        return R::from_break(stop);
+      }
+      if let Some(stop) = visitor.visit_stmt(then_branch).into_break() {
+        return R::from_break(stop);
+      }
+      if let Some(ref else_branch) = **else_branch {
+        if let Some(stop) = visitor.visit_stmt(else_branch).into_break() {
+          return R::from_break(stop);
+        }
+      }
+    },
+    Stmt::While(_, ref condition, ref body) => {
+      if let Some(stop) = visitor.visit_expr(condition).into_break() {
+        return R::from_break(stop);
+      }
+      if let Some(stop) = visitor.visit_stmt(body).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Stmt::Func(_, _, ref body) => {
+      if let Some(stop) = visitor.visit_stmt(body).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Stmt::Return(_, ref value) => {
+      if let Some(stop) = visitor.visit_expr(value).into_break() {
+        return R::from_break(stop);
+      }
+    },
+  }
+  R::walked()
+}
+
+/// the `StmtVisitor` of passes changing the statements they visit, like the resolver
+/// filling in depths, with the same methods taking `&mut` parts.
+pub trait StmtVisitorMut<R: VisitResult, E: VisitResult<Break = R::Break>>: ExprVisitorMut<E> {
+  fn visit_stmt_mut(&mut self, stmt: &mut Stmt) -> R {
+    match *stmt {
+      Stmt::Expr(ref mut expression) => self.visit_expr_stmt_mut(expression),
+      Stmt::Print(ref mut keyword, ref mut expression) => self.visit_print_stmt_mut(keyword, expression),
+      Stmt::Var(ref mut name, ref mut initializer) => self.visit_var_stmt_mut(name, initializer),
+      Stmt::Block(ref mut statements) => self.visit_block_stmt_mut(statements),
+      Stmt::Class(ref mut name, ref mut superclass, ref mut methods) => self.visit_class_stmt_mut(name, superclass, methods),
+      Stmt::If(ref mut keyword, ref mut condition, ref mut then_branch, ref mut else_branch) => self.visit_if_stmt_mut(keyword, condition, then_branch, else_branch),
+      Stmt::While(ref mut keyword, ref mut condition, ref mut body) => self.visit_while_stmt_mut(keyword, condition, body),
+      Stmt::Func(ref mut name, ref mut params, ref mut body) => self.visit_func_stmt_mut(name, params, body),
+      Stmt::Return(ref mut keyword, ref mut value) => self.visit_return_stmt_mut(keyword, value),
+    }
+  }
+
+  fn visit_expr_stmt_mut(&mut self, expression: &mut Expr) -> R {
+    if let Some(stop) = self.visit_expr_mut(expression).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_print_stmt_mut(&mut self, _keyword: &mut Token, expression: &mut Expr) -> R {
+    if let Some(stop) = self.visit_expr_mut(expression).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_var_stmt_mut(&mut self, _name: &mut Token, initializer: &mut Expr) -> R {
+    if let Some(stop) = self.visit_expr_mut(initializer).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_block_stmt_mut(&mut self, statements: &mut Vec<Stmt>) -> R {
+    for item in statements.iter_mut() {
+      if let Some(stop) = self.visit_stmt_mut(item).into_break() {
+        return R::from_break(stop);
+      }
+    }
+    R::walked()
+  }
+
+  fn visit_class_stmt_mut(&mut self, _name: &mut Token, superclass: &mut Option<Expr>, methods: &mut Vec<Stmt>) -> R {
+    if let Some(ref mut superclass) = *superclass {
+      if let Some(stop) = self.visit_expr_mut(superclass).into_break() {
+        return R::from_break(stop);
+      }
+    }
+    for item in methods.iter_mut() {
+      if let Some(stop) = self.visit_stmt_mut(item).into_break() {
+        return R::from_break(stop);
+      }
+    }
+    R::walked()
+  }
+
+  fn visit_if_stmt_mut(&mut self, _keyword: &mut Token, condition: &mut Expr, then_branch: &mut Stmt, else_branch: &mut Option<Stmt>) -> R {
+    if let Some(stop) = self.visit_expr_mut(condition).into_break() {
+      return R::from_break(stop);
+    }
+    if let Some(stop) = self.visit_stmt_mut(then_branch).into_break() {
+      return R::from_break(stop);
+    }
+    if let Some(ref mut else_branch) = *else_branch {
+      if let Some(stop) = self.visit_stmt_mut(else_branch).into_break() {
+        return R::from_break(stop);
+      }
+    }
+    R::walked()
+  }
+
+  fn visit_while_stmt_mut(&mut self, _keyword: &mut Token, condition: &mut Expr, body: &mut Stmt) -> R {
+    if let Some(stop) = self.visit_expr_mut(condition).into_break() {
+      return R::from_break(stop);
+    }
+    if let Some(stop) = self.visit_stmt_mut(body).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_func_stmt_mut(&mut self, _name: &mut Token, _params: &mut Vec<Token>, body: &mut Stmt) -> R {
+    if let Some(stop) = self.visit_stmt_mut(body).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
+
+  fn visit_return_stmt_mut(&mut self, _keyword: &mut Token, value: &mut Expr) -> R {
+    if let Some(stop) = self.visit_expr_mut(value).into_break() {
+      return R::from_break(stop);
+    }
+    R::walked()
+  }
 }
 
-impl std::fmt::Display for Stmt {
-  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-    match *self{
-      Stmt::Expr(ref expr) => {
-        write!(f, "Stmt::Expr = {}", expr)
-      },
-      Stmt::Print(ref expr) => {
-        write!(f, "Stmt::Print = {}", expr)
-      },
-      Stmt::Var(ref token, ref expr) => {
-        write!(f, "Stmt::Var = [token: {:?}] [value: {}]",token, expr)
-      },
-      Stmt::Block(ref stmt) => {
-        write!(f, "block = {:?}", &stmt)
-      },
-      Stmt::If(ref expr, ref then_stmt, ref else_stmt) => {
-        write!(f, "if condition = {:?} then {:?} else {:?}", expr, then_stmt, else_stmt)
-      },
-      Stmt::While(ref expr, ref stmt) => {
-        write!(f, "while condition = {:?} then {:?} ", expr, stmt)
-      },
-      Stmt::Func(ref name, ref params, ref body) => {
-        write!(f, "function name: {:?}, params: {:?}, body {:?} ", name, params, body)
-      },
-      Stmt::Return(ref keyword, ref value) => {
-        write!(f, "return: keyword = {:?}, value = {:?}", keyword, value)
-      },
-      Stmt::Class(ref name,ref superclass , ref methods) => {
-        write!(f, "class: name = {:?}, methods = {:?}", name, methods)
-      }
-    }
-  }
-}
\ No newline at end of file
+/// visits the nodes directly in `stmt`, in source order, giving the first break or `R::walked()`.
+pub fn walk_stmt_mut<R: VisitResult, E: VisitResult<Break = R::Break>, V: StmtVisitorMut<R, E> + ?Sized>(visitor: &mut V, stmt: &mut Stmt) -> R {
+  match *stmt {
+    Stmt::Expr(ref mut expression) => {
+      if let Some(stop) = visitor.visit_expr_mut(expression).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Stmt::Print(_, ref mut expression) => {
+      if let Some(stop) = visitor.visit_expr_mut(expression).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Stmt::Var(_, ref mut initializer) => {
+      if let Some(stop) = visitor.visit_expr_mut(initializer).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Stmt::Block(ref mut statements) => {
+      for item in statements.iter_mut() {
+        if let Some(stop) = visitor.visit_stmt_mut(item).into_break() {
+          return R::from_break(stop);
+        }
+      }
+    },
+    Stmt::Class(_, ref mut superclass, ref mut methods) => {
+      if let Some(ref mut superclass) = *superclass {
+        if let Some(stop) = visitor.visit_expr_mut(superclass).into_break() {
+          return R::from_break(stop);
+        }
+      }
+      for item in methods.iter_mut() {
+        if let Some(stop) = visitor.visit_stmt_mut(item).into_break() {
+          return R::from_break(stop);
+        }
+      }
+    },
+    Stmt::If(_, ref mut condition, ref mut then_branch, ref mut else_branch) => {
+      if let Some(stop) = visitor.visit_expr_mut(condition).into_break() {
+        return R::from_break(stop);
+      }
+      if let Some(stop) = visitor.visit_stmt_mut(then_branch).into_break() {
+        return R::from_break(stop);
+      }
+      if let Some(ref mut else_branch) = **else_branch {
+        if let Some(stop) = visitor.visit_stmt_mut(else_branch).into_break() {
+          return R::from_break(stop);
+        }
+      }
+    },
+    Stmt::While(_, ref mut condition, ref mut body) => {
+      if let Some(stop) = visitor.visit_expr_mut(condition).into_break() {
+        return R::from_break(stop);
+      }
+      if let Some(stop) = visitor.visit_stmt_mut(body).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Stmt::Func(_, _, ref mut body) => {
+      if let Some(stop) = visitor.visit_stmt_mut(body).into_break() {
+        return R::from_break(stop);
+      }
+    },
+    Stmt::Return(_, ref mut value) => {
+      if let Some(stop) = visitor.visit_expr_mut(value).into_break() {
+        return R::from_break(stop);
+      }
+    },
+  }
+  R::walked()
+}
+
+/// `stmt` as an S-expression, see `print_program`.
+pub fn print_stmt(stmt: &Stmt) -> String {
+  match *stmt {
+    Stmt::Expr(ref expression) => list("expr", &[print_expr(expression)]),
+    Stmt::Print(_, ref expression) => list("print", &[print_expr(expression)]),
+    Stmt::Var(ref name, ref initializer) => list("var", &[name.lexeme.clone(), print_expr(initializer)]),
+    Stmt::Block(ref statements) => {
+      let mut parts = Vec::new();
+      parts.extend(statements.iter().map(print_stmt));
+      list("block", &parts)
+    },
+    Stmt::Class(ref name, ref superclass, ref methods) => {
+      let mut parts = vec![name.lexeme.clone()];
+      if let Some(ref superclass) = *superclass {
+        parts.push("<".to_string());
+        parts.push(print_expr(superclass));
+      }
+      parts.extend(methods.iter().map(print_stmt));
+      list("class", &parts)
+    },
+    Stmt::If(_, ref condition, ref then_branch, ref else_branch) => {
+      let mut parts = vec![print_expr(condition), print_stmt(then_branch)];
+      if let Some(ref else_branch) = **else_branch {
+        parts.push(print_stmt(else_branch));
+      }
+      list("if", &parts)
+    },
+    Stmt::While(ref keyword, ref condition, ref body) => list(&keyword.lexeme, &[print_expr(condition), print_stmt(body)]),
+    Stmt::Func(ref name, ref params, ref body) => list("fun", &[name.lexeme.clone(), print_names(params), print_stmt(body)]),
+    Stmt::Return(_, ref value) => list("return", &[print_expr(value)]),
+  }
+}
+
+impl fmt::Display for Stmt {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str(&print_stmt(self))
+  }
+}
@@ -0,0 +1,181 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use super::statement::Stmt;
+use super::driver::compile;
+use super::interpreter::Interpreter;
+use super::native_fs::FsAccess;
+use super::error::RuntimeError;
+use super::token::Token;
+use super::coverage::{FileCoverage, Hits};
+
+/// `worng test`: every top-level function named `test_*` in the `.worng` files under a path
+/// is a test. each one runs in a fresh interpreter (top-level code included) with its
+/// output captured, and fails when it ends with a runtime error such as a failed `assert`.
+pub struct TestRunner {
+  pub fs_access: FsAccess,
+  /// `Some` collects the coverage of each file that compiles, of all its tests together.
+  pub coverage: Option<Vec<FileCoverage>>,
+  passed: usize,
+  failures: Vec<Failure>,
+}
+
+struct Failure {
+  name: String,
+  line: Option<i32>,
+  message: String,
+  output: String,
+}
+
+impl TestRunner {
+  pub fn new(fs_access: FsAccess) -> TestRunner {
+    TestRunner { fs_access, coverage: None, passed: 0, failures: Vec::new() }
+  }
+
+  /// runs the tests under `path` and writes the report, true when all of them passed.
+  pub fn run(&mut self, path: &Path, writer: &mut dyn Write) -> io::Result<bool> {
+    let mut files = Vec::new();
+    collect_files(path, &mut files)?;
+
+    for file in files.iter() {
+      let source = fs::read_to_string(file).map_err(|err| io::Error::new(err.kind(), format!("{}: {}", file.display(), err)))?;
+      self.run_file(&file.display().to_string(), &source, writer)?;
+    }
+
+    self.report(writer)?;
+    Ok(self.failures.is_empty())
+  }
+
+  fn run_file(&mut self, file: &str, source: &str, writer: &mut dyn Write) -> io::Result<()> {
+    writeln!(writer, "{}", file)?;
+
+    let statements = match compile(source) {
+      Ok(statements) => statements,
+      Err(errors) => {
+        writeln!(writer, "  doesn't compile ... FAILED")?;
+        let message = errors.iter().map(|err| err.to_string()).collect::<Vec<String>>().join("\n");
+        self.failures.push(Failure { name: file.to_string(), line: None, message, output: String::new() });
+        return Ok(());
+      }
+    };
+
+    let tests: Vec<&Token> = statements
+      .iter()
+      .filter_map(|stmt| match *stmt {
+        Stmt::Func(ref name, _, _) if name.lexeme.starts_with("test_") => Some(name),
+        _ => None
+      })
+      .collect();
+
+    let mut hits = self.coverage.as_ref().map(|_| Hits::default());
+    for name in tests {
+      let (result, output) = self.run_test(&statements, name, &mut hits);
+      match result {
+        Ok(()) => {
+          writeln!(writer, "  {} ... ok", name.lexeme)?;
+          self.passed += 1;
+        },
+        Err(err) => {
+          writeln!(writer, "  {} ... FAILED", name.lexeme)?;
+          self.failures.push(Failure {
+            name: format!("{}::{}", file, name.lexeme),
+            line: err.token().map(|token| token.line),
+            message: err.to_string(),
+            output,
+          });
+        }
+      }
+    }
+
+    if let (Some(files), Some(hits)) = (self.coverage.as_mut(), hits) {
+      files.push(FileCoverage::new(file, &statements, &hits));
+    }
+    Ok(())
+  }
+
+  fn run_test(&self, statements: &[Stmt], name: &Token, hits: &mut Option<Hits>) -> (Result<(), RuntimeError>, String) {
+    let mut output: Vec<u8> = Vec::new();
+    let mut input = io::empty();
+
+    let result = {
+      let writer = Rc::new(RefCell::new(&mut output as &mut dyn Write));
+      let reader = Rc::new(RefCell::new(&mut input as &mut dyn io::BufRead));
+      let mut interpreter = Interpreter::new(writer, reader);
+      interpreter.fs_access = self.fs_access.clone();
+      interpreter.coverage = hits.take();
+
+      let result = match interpreter.interpret(statements.to_vec()) {
+        Some(err) => Err(err),
+        None => call_test(&mut interpreter, name),
+      };
+      *hits = interpreter.coverage.take();
+      result
+    };
+
+    (result, String::from_utf8_lossy(&output).into_owned())
+  }
+
+  fn report(&self, writer: &mut dyn Write) -> io::Result<()> {
+    if !self.failures.is_empty() {
+      writeln!(writer, "\nfailures:")?;
+    }
+
+    for failure in self.failures.iter() {
+      match failure.line {
+        Some(line) => writeln!(writer, "\n{} (line {})", failure.name, line)?,
+        None => writeln!(writer, "\n{}", failure.name)?,
+      }
+      for line in failure.message.lines() {
+        writeln!(writer, "  {}", line)?;
+      }
+      if !failure.output.is_empty() {
+        writeln!(writer, "  output:")?;
+        for line in failure.output.lines() {
+          writeln!(writer, "    {}", line)?;
+        }
+      }
+    }
+
+    let result = if self.failures.is_empty() { "ok" } else { "FAILED" };
+    writeln!(writer, "\ntest result: {}. {} passed; {} failed.", result, self.passed, self.failures.len())
+  }
+}
+
+fn call_test(interpreter: &mut Interpreter, name: &Token) -> Result<(), RuntimeError> {
+  let test = interpreter.globals.borrow().get_value(&name.lexeme).ok().and_then(|test| test.get_callable());
+  let test = match test {
+    Some(test) => test,
+    None => return Err(RuntimeError::UndefinedVariable(name.clone())),
+  };
+
+  if test.arity() != 0 {
+    return Err(RuntimeError::ArityError(name.clone(), 0, test.arity()));
+  }
+  test.call(interpreter, name, Vec::new()).map(|_| ())
+}
+
+// `.worng` files under `path` in a stable order, or `path` itself when it is a file.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+  if !path.is_dir() {
+    files.push(path.to_path_buf());
+    return Ok(());
+  }
+
+  let mut entries = fs::read_dir(path)
+    .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))?
+    .map(|entry| entry.map(|entry| entry.path()))
+    .collect::<io::Result<Vec<PathBuf>>>()?;
+  entries.sort();
+
+  for entry in entries {
+    if entry.is_dir() {
+      collect_files(&entry, files)?;
+    } else if entry.extension().is_some_and(|extension| extension == "worng") {
+      files.push(entry);
+    }
+  }
+  Ok(())
+}
@@ -28,7 +28,8 @@ pub struct Token {
   pub token_type: TokenType,
   pub lexeme: String,
   pub literal: Option<Literal>,
-  pub line: i32 
+  pub line: i32,
+  pub offset: usize // char index of the lexeme in the source.
 }
 
 // impl std::fmt::Display for Token {
@@ -44,12 +45,13 @@ pub struct Token {
 
 impl Token{
 
-  pub fn new( token_type: TokenType, lexeme: String, literal: Option<Literal> , line: i32) -> Token {
+  pub fn new( token_type: TokenType, lexeme: String, literal: Option<Literal> , line: i32, offset: usize) -> Token {
     Token {
-      token_type: token_type,
-      lexeme: lexeme,
-      literal: literal,
-      line: line
+      token_type,
+      lexeme,
+      literal,
+      line,
+      offset
     }
   }
 
@@ -46,5 +46,8 @@ pub enum TokenType {
   Var,
   While,
 
+  // only in `Scanner::comments`.
+  Comment,
+
   EOF,
 }
\ No newline at end of file
@@ -0,0 +1,41 @@
+/// what the visitors of `Expr` and `Stmt` give for a node a pass leaves to the default visit,
+/// which visits its parts: nothing, or `Ok` of an empty value. a part giving a break, an `Err`,
+/// stops the visit, which gives that break.
+pub trait VisitResult {
+  /// what a visit stops at, the error of a `Result`.
+  type Break;
+
+  fn walked() -> Self;
+
+  fn into_break(self) -> Option<Self::Break>;
+
+  fn from_break(stop: Self::Break) -> Self;
+}
+
+impl VisitResult for () {
+  type Break = ();
+
+  fn walked() {}
+
+  fn into_break(self) -> Option<()> {
+    None
+  }
+
+  fn from_break(_stop: ()) {}
+}
+
+impl<T: Default, E> VisitResult for Result<T, E> {
+  type Break = E;
+
+  fn walked() -> Self {
+    Ok(T::default())
+  }
+
+  fn into_break(self) -> Option<E> {
+    self.err()
+  }
+
+  fn from_break(stop: E) -> Self {
+    Err(stop)
+  }
+}
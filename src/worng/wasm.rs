@@ -2,7 +2,7 @@ use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_void};
 use std::mem;
 
-use super::worng_value::{run_string};
+use super::driver::{run_string, run_string_with_input};
 
 #[no_mangle]
 pub fn run_from_wasm(data: *const c_char) -> *const c_char {
@@ -15,6 +15,18 @@ pub fn run_from_wasm(data: *const c_char) -> *const c_char {
   CString::new(run_string(incoming_str)).unwrap().into_raw()
 }
 
+/// # Safety
+///
+/// `data` and `input` have to point to nul-terminated UTF-8 strings, like the ones `alloc` gives
+/// room for, that stay valid until this returns.
+#[no_mangle]
+pub unsafe fn run_from_wasm_with_input(data: *const c_char, input: *const c_char) -> *const c_char {
+  let incoming_str = CStr::from_ptr(data).to_str().unwrap().to_owned();
+  let incoming_input = CStr::from_ptr(input).to_str().unwrap().to_owned();
+
+  CString::new(run_string_with_input(incoming_str, incoming_input)).unwrap().into_raw()
+}
+
 #[no_mangle]
 pub fn alloc(size: usize) -> *const c_void {
   let buf = Vec::with_capacity(size);
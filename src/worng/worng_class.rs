@@ -2,6 +2,7 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::any::Any;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use super::callable::Callable;
 use super::interpreter::Interpreter;
@@ -11,8 +12,12 @@ use super::token::Token;
 use super::worng_function::WorngFunction;
 use super::worng_instance::WorngInstance;
 
+// instances keep their own copy of the class, so it is told apart by id.
+static NEXT_CLASS_ID: AtomicUsize = AtomicUsize::new(0);
+
 #[derive(Debug, Clone)]
 pub struct WorngClass {
+  id: usize,
   name: String,
   methods: HashMap<String, WorngValue>,
   superclass: Option<Rc<WorngClass>>
@@ -21,6 +26,7 @@ pub struct WorngClass {
 impl WorngClass {
   pub fn new(name: String, methods: HashMap<String, WorngValue>, superclass: Option<Rc<WorngClass>>,) -> WorngClass {
     WorngClass {
+      id: NEXT_CLASS_ID.fetch_add(1, Ordering::Relaxed),
       name: name,
       methods: methods,
       superclass: superclass 
@@ -47,6 +53,20 @@ impl WorngClass {
           }
       })
   }
+
+  // a class inherits from itself too.
+  pub fn inherits(&self, other: &WorngClass) -> bool {
+    self.id == other.id || self.superclass.as_ref().is_some_and(|superclass| superclass.inherits(other))
+  }
+
+  // including the inherited ones.
+  pub fn method_names(&self) -> Vec<String> {
+    let mut names: Vec<String> = self.methods.keys().cloned().collect();
+    if let Some(ref superclass) = self.superclass {
+      names.extend(superclass.method_names());
+    }
+    names
+  }
 }
 
 impl std::fmt::Display for WorngClass {
@@ -60,13 +80,14 @@ impl Callable for WorngClass {
   fn call(
     &self, 
     interpreter: &mut Interpreter, 
+    paren: &Token,
     args: Vec<WorngValue>) 
     -> Result<WorngValue, RuntimeError>{
 
-    let instance = WorngInstance::new(self.clone());
-    match self.find_method("init", Rc::new(RefCell::new(instance.clone()))) {
-      Some( _initializer ) => return _initializer.call(interpreter, args),
-      None => return Ok(WorngValue::Instance(Rc::new( RefCell::new( instance )) ))
+    let instance = Rc::new(RefCell::new(WorngInstance::new(self.clone())));
+    match self.find_method("init", instance.clone()) {
+      Some( _initializer ) => return _initializer.call(interpreter, paren, args),
+      None => return Ok(WorngValue::Instance(instance))
     };
   }
 
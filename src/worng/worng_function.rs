@@ -9,6 +9,7 @@ use super::environment::Environment;
 use super::worng_value::WorngValue;
 use super::worng_instance::WorngInstance;
 use super::error::RuntimeError;
+use super::token::Token;
 
 #[derive(Debug, Clone)]
 pub struct WorngFunction{
@@ -45,15 +46,15 @@ impl WorngFunction{
 
 
 impl Callable for WorngFunction{
-  fn call(&self, interpreter: &mut Interpreter, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError>{
+  fn call(&self, interpreter: &mut Interpreter, paren: &Token, args: Vec<WorngValue>) -> Result<WorngValue, RuntimeError>{
 
     // each function has it's own environment
     // eg. recursive function has to have it's "enclosed" environment, 
     // just to collect it's own params, at certain stage of calling function.
     let mut environment = Environment::enclose(self.closure.clone());
 
-    let (params, body)  = match self.declaration {
-      Stmt::Func(ref name, ref params, ref body) => (params, body),
+    let (name, params, body)  = match self.declaration {
+      Stmt::Func(ref name, ref params, ref body) => (name, params, body),
       _ => panic!("Cannot build a function statement other than Stmt::Func")
     };
 
@@ -71,7 +72,11 @@ impl Callable for WorngFunction{
       );
     }
 
-    let result = match interpreter.interpret_block(body, RefCell::new(environment))? {
+    interpreter.push_call(name, paren);
+    let result = interpreter.interpret_block(body, RefCell::new(environment));
+    interpreter.pop_call();
+
+    let result = match result? {
       Some(res) => Ok(res),
       None => Ok(WorngValue::Nil)
     };
@@ -97,9 +102,11 @@ impl Callable for WorngFunction{
     return params;
   } 
 
-  // TODO: "<fn " + declaration.name.lexeme + ">";
   fn func_to_string(&self) -> String{
-    String::from("<function>")
+    match self.declaration {
+      Stmt::Func(ref name, _, _) => format!("<fn {}>", name.lexeme),
+      _ => String::from("<function>")
+    }
   }
 
   fn as_any(&self) -> &dyn Any{
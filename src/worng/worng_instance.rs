@@ -22,12 +22,13 @@ impl WorngInstance {
     }
   }
 
-  pub fn get(&self, name: &Token ) -> Result<WorngValue, RuntimeError>{
-    if let Some(value) =  self.fields.get(&name.lexeme) {
+  // methods are bound to the instance itself, not a copy, so they can change its fields.
+  pub fn get(instance: &Rc<RefCell<WorngInstance>>, name: &Token ) -> Result<WorngValue, RuntimeError>{
+    if let Some(value) = instance.borrow().fields.get(&name.lexeme) {
       return Ok(value.clone());
     }
 
-    match self.klass.find_method(&name.lexeme, Rc::new(RefCell::new(self.clone()))){
+    match instance.borrow().klass.find_method(&name.lexeme, instance.clone()){
       Some(method) => Ok(WorngValue::Func(Rc::new(method))),
       None => Err(RuntimeError::UndefinedProperty(name.clone()))
     }
@@ -36,6 +37,18 @@ impl WorngInstance {
   pub fn set(&mut self, name: Token, value: WorngValue) {
     self.fields.insert(name.lexeme, value);
   }
+
+  pub fn field(&self, name: &str) -> Option<WorngValue> {
+    self.fields.get(name).cloned()
+  }
+
+  pub fn field_names(&self) -> Vec<String> {
+    self.fields.keys().cloned().collect()
+  }
+
+  pub fn class(&self) -> &WorngClass {
+    &self.klass
+  }
 }
 
 impl std::fmt::Display for WorngInstance {
@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+use super::worng_value::WorngValue;
+use super::token::Token;
+use super::error::RuntimeError;
+
+/// a namespace of natives, eg. `os.args()`.
+/// members are fixed once the module is built, scripts can only read them.
+#[derive(Debug)]
+pub struct WorngModule {
+  name: String,
+  members: HashMap<String, WorngValue>
+}
+
+impl WorngModule {
+  pub fn new(name: &str) -> WorngModule {
+    WorngModule {
+      name: name.to_string(),
+      members: HashMap::new()
+    }
+  }
+
+  pub fn define(&mut self, name: &str, value: WorngValue) {
+    self.members.insert(name.to_string(), value);
+  }
+
+  pub fn get(&self, name: &Token) -> Result<WorngValue, RuntimeError> {
+    match self.members.get(&name.lexeme) {
+      Some(value) => Ok(value.clone()),
+      None => Err(RuntimeError::UndefinedProperty(name.clone()))
+    }
+  }
+
+  pub fn member_names(&self) -> Vec<String> {
+    self.members.keys().cloned().collect()
+  }
+}
+
+impl std::fmt::Display for WorngModule {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "<module {}>", self.name)
+  }
+}
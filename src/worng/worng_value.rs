@@ -1,38 +1,18 @@
-extern crate rustyline;
-
-use std::io::{ self, Read, Write};
-use std::fs;
-use std::path::Path;
-use std::error::{Error};
 use std::ops::Sub;
-use std::io::Cursor;
 use std::rc::Rc;
 use std::cell::RefCell;
-use std::fs::File;
+use std::collections::BTreeMap;
 
-use self::rustyline::error::ReadlineError;
-use self::rustyline::Editor;
+use regex::Regex;
 
-use super::scanner::Scanner;
-use super::statement::Stmt;
-use super::token::Token;
-use super::token_type::TokenType;
 use super::callable::Callable;
-use super::parser::Parser;
-use super::interpreter::Interpreter;
 use super::expr::Expr;
-use super::resolver::Resolver;
 use super::worng_class::WorngClass;
 use super::worng_instance::WorngInstance;
+use super::worng_module::WorngModule;
 use super::error::{ WorngError, ValueError, RuntimeError };
 
-#[derive(Debug)]
-pub struct Worng {
-  pub had_error: bool,
-  pub had_runtime_error: bool,
-}
-
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub enum WorngValue {
   Number(f64),
   String(String),
@@ -40,11 +20,24 @@ pub enum WorngValue {
   Func(Rc<dyn Callable>),
   Class(Rc<WorngClass>),
   Instance(Rc<RefCell<WorngInstance>>),
+  List(Rc<RefCell<Vec<WorngValue>>>),
+  Map(Rc<RefCell<BTreeMap<String, WorngValue>>>),
+  Regex(Rc<Regex>),
+  Module(Rc<WorngModule>),
+  #[default]
   Nil,
 }
 
 impl std::fmt::Display for WorngValue {
   fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    self.write(f, &mut Vec::new())
+  }
+}
+
+impl WorngValue {
+  // `printing` holds the lists and maps being written, one met again inside itself is written
+  // `[...]` or `{...}`.
+  fn write(&self, f: &mut std::fmt::Formatter, printing: &mut Vec<*const ()>) -> std::fmt::Result {
     match *self {
       WorngValue::Number(number) => write!(f, "{}", number),
       WorngValue::String(ref string) => write!(f, "{}", string),
@@ -52,6 +45,37 @@ impl std::fmt::Display for WorngValue {
       WorngValue::Func(_) => f.write_str("func"),
       WorngValue::Class(ref name) => write!(f,"{}", name),
       WorngValue::Instance(ref klass) => write!(f,"Instance: {}", klass.borrow()),
+      WorngValue::List(ref list) => {
+        let pointer = Rc::as_ptr(list) as *const ();
+        if printing.contains(&pointer) {
+          return f.write_str("[...]");
+        }
+        printing.push(pointer);
+        f.write_str("[")?;
+        for (i, item) in list.borrow().iter().enumerate() {
+          if i > 0 { f.write_str(", ")? }
+          item.write(f, printing)?;
+        }
+        printing.pop();
+        f.write_str("]")
+      },
+      WorngValue::Map(ref map) => {
+        let pointer = Rc::as_ptr(map) as *const ();
+        if printing.contains(&pointer) {
+          return f.write_str("{...}");
+        }
+        printing.push(pointer);
+        f.write_str("{")?;
+        for (i, (key, value)) in map.borrow().iter().enumerate() {
+          if i > 0 { f.write_str(", ")? }
+          write!(f, "{}: ", key)?;
+          value.write(f, printing)?;
+        }
+        printing.pop();
+        f.write_str("}")
+      },
+      WorngValue::Regex(ref regex) => write!(f, "<regex {}>", regex),
+      WorngValue::Module(ref module) => write!(f, "{}", module),
       WorngValue::Nil => f.write_str("nil"),
     }
   }
@@ -67,124 +91,15 @@ impl std::clone::Clone for WorngValue {
       WorngValue::Func(ref func) => WorngValue::Func(func.clone()),
       WorngValue::Class(ref class) => WorngValue::Class(class.clone()),
       WorngValue::Instance(ref klass) => WorngValue::Instance(klass.clone()),
+      WorngValue::List(ref list) => WorngValue::List(list.clone()),
+      WorngValue::Map(ref map) => WorngValue::Map(map.clone()),
+      WorngValue::Regex(ref regex) => WorngValue::Regex(regex.clone()),
+      WorngValue::Module(ref module) => WorngValue::Module(module.clone()),
     }
   }
 }
 
 
-impl Worng {
-  
-  pub fn main(&mut self, args: Vec<&str> ) {
-    
-    if args.len() > 1 {
-
-      writeln!(io::stdout(), "Usage: worng [script]");
-      std::process::exit(64)
-    } else if let Some(filename) = args.first() {
-      if let Err(errors) = self.run_file(filename.to_string(), &mut io::stdout()) {
-        println!("Error running file {}\n", filename);
-
-        for err in errors {
-          println!("{}", err);
-        }
-    }
-    } else {
-      self.run_prompt(&mut io::stdout()).unwrap();
-    }
-  }
-  
-  fn run_file(&mut self, path: String, writer: &mut io::Write) ->  Result<(), Vec<RuntimeError>> {
-
-    let mut f = File::open(path).expect("file not found");
-    let mut contents = String::new();
-    f.read_to_string(&mut contents).expect("something went wrong reading the file");
-
-    let writer = Rc::new(RefCell::new(writer));
-    let mut interpreter = Interpreter::new(writer);
-    run(&mut interpreter, contents)
-  }
-  
-  fn run_prompt(&mut self, writer: &mut io::Write) -> Result<(), Box<Error>>{
-    let writer = Rc::new(RefCell::new(writer));
-    let mut interpreter = Interpreter::new(writer.clone());
-
-    let mut rl = Editor::<()>::new();
-    if rl.load_history("history.txt").is_err() {
-      println!("No previous history.");
-    }
-  
-    loop {
-      let readline = rl.readline(">> ");
-      let mut l = match readline {
-        Ok(line) => { rl.add_history_entry(line.as_str()); line },
-        Err(ReadlineError::Interrupted) => { println!("exit"); break },
-        Err(err) => { println!("Error: {:?}", err); break }
-      };
-      run(&mut interpreter, l);
-    }
-  
-    rl.save_history("history.txt").unwrap();
-  
-    Ok(())
-  }
-
-  fn report(&mut self, line: i32, pos: String, message: &str) {
-    eprintln!("[Line {}] Error {}: {}", line,pos,message);
-    self.had_error = true;
-  }
-  
-  pub fn error(&mut self, token: Token, message: &str) {
-    if token.token_type == TokenType::EOF {
-      self.report(token.line, String::from(" at end"), message);
-    } else {
-      self.report(token.line, String::from(format!(" at '{}'",token.lexeme )), message);
-    }
-  }
-}
-
-fn run(interpreter: &mut Interpreter, source: String) -> Result<(), Vec<RuntimeError>> {
-  let mut scanner = Scanner::new(&source);
-  let tokens: Vec<Token> = scanner.scan_tokens();
-  
-  let mut parser = Parser::new(tokens);
-
-  let mut expression: Vec<Stmt> = parser.parse().unwrap();
-  // let mut interpreter = Interpreter::new();
-
-  // if self.had_error { return };
-
-  let mut resolver = Resolver::new();
-  resolver.resolve(&mut expression);
-
-  match interpreter.interpret(expression) {
-    Some(err) => Err(vec![err]),
-    None => Ok(()),
-  }
-}
-
-pub fn run_string(code: String) -> String {
-  let output: Vec<u8> = Vec::new();
-  let mut cursor = Cursor::new(output);
-
-  let result = {
-    let mut writer = Rc::new(RefCell::new(&mut cursor as &mut io::Write));
-    let mut interpreter = Interpreter::new(writer.clone());
-    run(&mut interpreter, code)
-  };
-
-  match result {
-    Ok(_) => {
-        let output = cursor.get_ref().clone();
-        String::from_utf8(output).unwrap()
-    }
-    Err(errors) => errors
-        .iter()
-        .map(|error| error.to_string())
-        .collect::<Vec<String>>()
-        .join("\n"),
-  }
-}
-
 impl WorngValue {
   pub fn subtract(&self, other: WorngValue) -> Result<WorngValue, ValueError> {
     match (self, other) {
@@ -200,12 +115,17 @@ impl WorngValue {
       (&WorngValue::Number(left), WorngValue::Number(right)) => {
         Ok(WorngValue::Number(left + right))
       },
-      // NOTE: should be possible to concat string with number.
       (&WorngValue::String(ref left), WorngValue::String(ref right)) => {
         let mut s = String::from(left);
         s.push_str(right);
         Ok(WorngValue::String(s))
       },
+      (WorngValue::String(left), WorngValue::Number(right)) => {
+        Ok(WorngValue::String(format!("{}{}", left, right)))
+      },
+      (WorngValue::Number(left), WorngValue::String(right)) => {
+        Ok(WorngValue::String(format!("{}{}", left, right)))
+      },
       _ => Err(ValueError::TypeError) 
     }
   }
@@ -294,6 +214,10 @@ impl WorngValue {
       (&WorngValue::String(ref string), &WorngValue::String(ref other)) => string == other,
       (&WorngValue::Number(num), &WorngValue::Number(other)) => num == other,
       (&WorngValue::Func(ref f), &WorngValue::Func(ref other)) => Rc::ptr_eq(f, other),
+      (&WorngValue::List(ref list), &WorngValue::List(ref other)) => Rc::ptr_eq(list, other),
+      (&WorngValue::Map(ref map), &WorngValue::Map(ref other)) => Rc::ptr_eq(map, other),
+      (&WorngValue::Regex(ref regex), &WorngValue::Regex(ref other)) => Rc::ptr_eq(regex, other),
+      (&WorngValue::Module(ref module), &WorngValue::Module(ref other)) => Rc::ptr_eq(module, other),
       _ => false,
     };
 
@@ -320,6 +244,22 @@ impl WorngValue {
     }
   }
 
+  pub fn type_name(&self) -> &'static str {
+    match *self {
+      WorngValue::Number(_) => "number",
+      WorngValue::String(_) => "string",
+      WorngValue::Bool(_) => "bool",
+      WorngValue::Func(_) => "func",
+      WorngValue::Class(_) => "class",
+      WorngValue::Instance(_) => "instance",
+      WorngValue::List(_) => "list",
+      WorngValue::Map(_) => "map",
+      WorngValue::Regex(_) => "regex",
+      WorngValue::Module(_) => "module",
+      WorngValue::Nil => "nil",
+    }
+  }
+
   pub fn get_callable(&self) -> Option<Rc<dyn Callable>> {
     match *self {
       WorngValue::Func(ref func) => Some(func.clone()),
@@ -0,0 +1,95 @@
+//! `worng tokens --json` and `worng ast --json`.
+
+extern crate worng;
+
+use std::fs;
+use std::process::Command;
+
+use worng::{AstJson, Json, Parser, Resolver, Scanner};
+
+const PROGRAM: &str = "\
+var a = 1;
+{
+  var b = \"é\";
+  print a + b;
+}
+";
+
+fn statements(source: &str) -> Json {
+  let tokens = Scanner::new(source).scan_tokens();
+  let mut statements = Parser::new(tokens).parse().unwrap();
+  Resolver::new().resolve(&mut statements);
+  AstJson::new(source).statements(&statements)
+}
+
+fn at<'a>(json: &'a Json, path: &[&str]) -> &'a Json {
+  path.iter().fold(json, |json, key| match (json, key.parse::<usize>()) {
+    (Json::Array(items), Ok(index)) => &items[index],
+    _ => json.get(key).unwrap_or_else(|| panic!("no {} in {}", key, json)),
+  })
+}
+
+#[test]
+fn tokens_with_spans() {
+  let tokens = Scanner::new(PROGRAM).scan_tokens();
+  let json = AstJson::new(PROGRAM).tokens(&tokens);
+  assert_eq!(
+    at(&json, &["5"]).to_string(),
+    r#"{"type":"LeftBrace","lexeme":"{","literal":null,"span":{"line":2,"column":1,"start":11,"end":12}}"#
+  );
+  // columns and offsets count chars.
+  assert_eq!(at(&json, &["9", "literal"]), &Json::String("é".to_string()));
+  assert_eq!(at(&json, &["10", "span"]).to_string(), r#"{"line":3,"column":14,"start":26,"end":27}"#);
+  assert_eq!(at(&json, &["17", "type"]).as_str(), Some("EOF"));
+}
+
+#[test]
+fn statements_with_depths() {
+  let json = statements(PROGRAM);
+  assert_eq!(at(&json, &["0"]).to_string(), r#"{"kind":"Var","name":{"type":"Identifier","lexeme":"a","literal":null,"span":{"line":1,"column":5,"start":4,"end":5}},"initializer":{"kind":"Literal","value":1}}"#);
+
+  let sum = at(&json, &["1", "statements", "1", "expression"]);
+  assert_eq!(at(sum, &["kind"]).as_str(), Some("Binary"));
+  assert_eq!(at(sum, &["left", "depth"]), &Json::Null);
+  assert_eq!(at(sum, &["right", "depth"]), &Json::Number(0.0));
+  assert_eq!(at(sum, &["operator", "span", "line"]), &Json::Number(4.0));
+}
+
+#[test]
+fn every_kind_of_node() {
+  let json = statements(
+    "class A { f() { return this; } }\nclass B < A { g(x) { x = super.f(); x.y = -x; return (x.y and nil) or x; } }\nif (true) print 1; else while (false) {}\n",
+  );
+  let text = json.to_string();
+  for kind in &["Class", "Func", "Return", "This", "Assign", "Super", "Call", "Set", "Unary", "Get", "Grouping", "Logical", "If", "While", "Block", "Print", "Expr"] {
+    assert!(text.contains(&format!(r#""kind":"{}""#, kind)), "no {} in {}", kind, text);
+  }
+  assert_eq!(at(&json, &["1", "superclass", "name", "lexeme"]).as_str(), Some("A"));
+  assert_eq!(at(&json, &["0", "superclass"]), &Json::Null);
+  assert_eq!(at(&json, &["1", "methods", "0", "params", "0", "lexeme"]).as_str(), Some("x"));
+  assert_eq!(at(&json, &["2", "else", "kind"]).as_str(), Some("While"));
+}
+
+#[test]
+fn json_options() {
+  let path = std::env::temp_dir().join(format!("worng-ast-json-{}.worng", std::process::id()));
+  fs::write(&path, PROGRAM).unwrap();
+
+  let ast = Command::new(env!("CARGO_BIN_EXE_worng")).args(&["ast", "--json"]).arg(&path).output().unwrap();
+  assert_eq!(ast.status.code(), Some(0));
+  assert_eq!(Json::parse(&String::from_utf8_lossy(&ast.stdout)).unwrap(), statements(PROGRAM));
+
+  let tokens = Command::new(env!("CARGO_BIN_EXE_worng")).args(&["tokens", "--json"]).arg(&path).output().unwrap();
+  assert_eq!(tokens.status.code(), Some(0));
+  match Json::parse(&String::from_utf8_lossy(&tokens.stdout)).unwrap() {
+    Json::Array(tokens) => assert_eq!(tokens.len(), 18),
+    other => panic!("not an array: {}", other),
+  }
+
+  fs::write(&path, "print ;").unwrap();
+  let ast = Command::new(env!("CARGO_BIN_EXE_worng")).args(&["ast", "--json"]).arg(&path).output().unwrap();
+  assert_eq!(ast.status.code(), Some(65));
+  assert!(ast.stdout.is_empty());
+
+  fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,93 @@
+//! the parser, checked against the trees it should build written as S-expressions, and the
+//! reader giving back what the printer wrote.
+
+extern crate worng;
+
+use worng::{print_expr, print_program, read_expr, read_program, Expr, Literal, Parser, Scanner};
+
+fn parse(source: &str) -> String {
+  let tokens = Scanner::new(source).scan_tokens();
+  match Parser::new(tokens).parse() {
+    Ok(statements) => print_program(&statements),
+    Err(errors) => panic!("{} doesn't parse: {:?}", source, errors),
+  }
+}
+
+fn round_trip(tree: &str) {
+  match read_program(tree) {
+    Ok(statements) => assert_eq!(print_program(&statements), tree),
+    Err(errors) => panic!("{} doesn't read: {}", tree, errors.iter().map(|err| err.to_string()).collect::<Vec<_>>().join(", ")),
+  }
+}
+
+#[test]
+fn precedence_and_grouping() {
+  assert_eq!(parse("print 1 + 2 * -3 == (4 - 5) / 6;"), "(print (== (+ 1 (* 2 (- 3))) (/ (group (- 4 5)) 6)))\n");
+  assert_eq!(parse("a = b or c and !d;"), "(expr (assign a (or b (and c (! d)))))\n");
+  assert_eq!(parse("x.y.z = f(1)(2, \"s\");"), "(expr (set (get x y) z (call (call f 1) 2 \"s\")))\n");
+}
+
+#[test]
+fn declarations() {
+  assert_eq!(parse("var a; var b = true;"), "(var a nil)\n(var b true)\n");
+  assert_eq!(parse("fun f(a, b) { return a; }"), "(fun f (a b) (block (return a)))\n");
+  assert_eq!(parse("fun g() { return; }"), "(fun g () (block (return nil)))\n");
+  assert_eq!(
+    parse("class B < A { init() { this.x = super.init(); } }"),
+    "(class B < A (fun init () (block (expr (set this x (call (super init)))))))\n"
+  );
+}
+
+#[test]
+fn control_flow() {
+  assert_eq!(parse("if (a) print 1; else { print 2; }"), "(if a (print 1) (block (print 2)))\n");
+  assert_eq!(parse("while (i < 3) i = i + 1;"), "(while (< i 3) (expr (assign i (+ i 1))))\n");
+  assert_eq!(
+    parse("for (var i = 0; i < 3; i = i + 1) print i;"),
+    "(block (var i 0) (for (< i 3) (block (print i) (expr (assign i (+ i 1))))))\n"
+  );
+  assert_eq!(parse("for (;;) {}"), "(block (expr nil) (for true (block (block) (expr nil))))\n");
+}
+
+#[test]
+fn printed_trees_read_back() {
+  round_trip(&parse(
+    "class A { f(x) { if (x >= 1.5) return -x; else return !x; } }\nclass B < A { g() { return super.f(this.y); } }\nvar b = B();\nb.y = \"a b\";\nprint b.g() != nil or false;\nfor (var i = 0; i <= 2; i = i + 1) { print i; }\n",
+  ));
+  round_trip("(if (> a 1) (block))\n(while false (print 1))\n");
+}
+
+#[test]
+fn expressions_read_back() {
+  let expr = read_expr("(- (- 1) (call f (group a)))").unwrap_or_else(|_| panic!("doesn't read"));
+  assert_eq!(print_expr(&expr), "(- (- 1) (call f (group a)))");
+  assert_eq!(expr.to_string(), "(- (- 1) (call f (group a)))");
+}
+
+// the parser never makes a negative literal, a pass folding `(- 2)` into one does.
+#[test]
+fn negative_numbers_read_back() {
+  let negative = Expr::Literal(Literal::Number(-2.5));
+  assert_eq!(print_expr(&negative), "-2.5");
+  match read_expr(&print_expr(&negative)) {
+    Ok(Expr::Literal(Literal::Number(value))) => assert_eq!(value, -2.5),
+    Ok(other) => panic!("-2.5 reads as {}", other),
+    Err(_) => panic!("-2.5 doesn't read"),
+  }
+  round_trip("(print (- -1 -2))\n(var a (- -3))\n(expr (call f -0.5))\n");
+  assert!(read_expr("- 2").is_err());
+}
+
+#[test]
+fn reader_errors() {
+  let error = |tree: &str| match read_program(tree) {
+    Ok(statements) => panic!("{} reads as {}", tree, print_program(&statements)),
+    Err(errors) => errors.iter().map(|err| err.to_string()).collect::<Vec<_>>().join(", "),
+  };
+  assert!(error("(print (% 1 2))").contains("Unexpected character"));
+  assert!(error("(print (? 1 2))").contains("Unexpected character"));
+  assert!(error("(print 1").contains("Expect ')' after a statement."));
+  assert!(error("(frobnicate 1)").contains("Expect a statement."));
+  assert!(error("(print (! 1 2))").contains("Expect ')' after the operand."));
+  assert!(read_expr("1 2").is_err());
+}
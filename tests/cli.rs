@@ -0,0 +1,134 @@
+//! the `worng` binary: exit codes, what a script gets from the process (`os.args`, `os.env`,
+//! `os.exit`) and reading stdin with `input`, `read_line` and `read_all`.
+
+extern crate worng;
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+use worng::run_string_with_input;
+
+fn worng(args: &[&str], stdin: &str) -> Output {
+  let mut child = Command::new(env!("CARGO_BIN_EXE_worng"))
+    .args(args)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .unwrap();
+  child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+  child.wait_with_output().unwrap()
+}
+
+fn eval(code: &str) -> Output {
+  worng(&["-e", code], "")
+}
+
+// a script in its own temp file, they can't live under `tests` as some of them don't compile.
+fn script(name: &str, source: &str) -> PathBuf {
+  let dir = std::env::temp_dir().join(format!("worng-cli-{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  let file = dir.join(name);
+  fs::write(&file, source).unwrap();
+  file
+}
+
+fn stdout(output: &Output) -> String {
+  String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+  String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn exit_codes() {
+  let ok = eval("print 1;");
+  assert_eq!((ok.status.code(), stdout(&ok)), (Some(0), "1\n".to_string()));
+
+  let compile_error = eval("print 1 +;");
+  assert_eq!(compile_error.status.code(), Some(65));
+  assert!(compile_error.stdout.is_empty());
+  assert_eq!(stderr(&compile_error), "[Line: 1] Unexpected token error: Expect expression. ;\n");
+
+  let runtime_error = eval("print \"before\"; print nil + 1;");
+  assert_eq!(runtime_error.status.code(), Some(70));
+  assert_eq!(stdout(&runtime_error), "before\n");
+  assert_eq!(stderr(&runtime_error), "[Line: 1] add non-number: +\n");
+
+  let missing = worng(&["run", "does/not/exist.worng"], "");
+  assert_eq!(missing.status.code(), Some(66));
+  assert!(stderr(&missing).starts_with("worng: does/not/exist.worng: "), "{}", stderr(&missing));
+}
+
+#[test]
+fn check_command() {
+  let broken = script("broken.worng", "print 1 +;\n");
+  let fine = script("fine.worng", "print nil + 1;\n");
+
+  let checked = worng(&["check", fine.to_str().unwrap()], "");
+  assert_eq!(checked.status.code(), Some(0));
+  assert!(checked.stdout.is_empty() && checked.stderr.is_empty());
+
+  let failed = worng(&["check", fine.to_str().unwrap(), broken.to_str().unwrap()], "");
+  assert_eq!(failed.status.code(), Some(65));
+  assert!(failed.stdout.is_empty());
+
+  let from_stdin = worng(&["check"], "var a = ;");
+  assert_eq!(from_stdin.status.code(), Some(65));
+}
+
+#[test]
+fn os_args_and_env() {
+  let file = script("args.worng", "print os.args();\nprint os.env(\"WORNG_CLI_TEST\");\nprint os.env(\"WORNG_CLI_UNSET\");\n");
+  let output = Command::new(env!("CARGO_BIN_EXE_worng"))
+    .args(["run", file.to_str().unwrap(), "--", "one", "two three"])
+    .env("WORNG_CLI_TEST", "set")
+    .env_remove("WORNG_CLI_UNSET")
+    .output()
+    .unwrap();
+  assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+  assert_eq!(stdout(&output), "[one, two three]\nset\nnil\n");
+
+  let no_args = eval("print os.args();");
+  assert_eq!(stdout(&no_args), "[]\n");
+}
+
+#[test]
+fn os_exit() {
+  let output = eval("print \"before\"; os.exit(3); print \"after\";");
+  assert_eq!(output.status.code(), Some(3));
+  assert_eq!(stdout(&output), "before\n");
+  assert!(output.stderr.is_empty());
+
+  // unwinds out of calls too.
+  let nested = eval("fun quit() { os.exit(0); } quit(); print \"after\";");
+  assert_eq!((nested.status.code(), stdout(&nested)), (Some(0), String::new()));
+
+  let not_a_number = eval("os.exit(\"3\");");
+  assert_eq!(not_a_number.status.code(), Some(70));
+}
+
+#[test]
+fn reads_stdin() {
+  let code = "var name = input(\"name? \"); print \"hi \" + name; print read_line(); print read_all(); print read_line();";
+  let output = worng(&["-e", code], "ann\r\nb\nc\nd");
+  assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+  assert_eq!(stdout(&output), "name? hi ann\nb\nc\nd\nnil\n");
+
+  let script = script("echo.worng", "var line = read_line();\nwhile (line != nil) {\n  print \"> \" + line;\n  line = read_line();\n}\n");
+  let piped = worng(&["run", script.to_str().unwrap()], "one\ntwo\n");
+  assert_eq!(stdout(&piped), "> one\n> two\n");
+
+  let program = worng(&["run", "-"], "print read_all();");
+  assert_eq!((program.status.code(), stdout(&program)), (Some(0), "\n".to_string()));
+}
+
+#[test]
+fn run_string_with_input_reads_the_input() {
+  let code = "print input(\"? \"); print read_line(); print read_all(); print input(\"! \");";
+  assert_eq!(run_string_with_input(code.to_string(), "a\nb\nc\nd\n".to_string()), "? a\nb\nc\nd\n\n! nil\n");
+  assert_eq!(run_string_with_input("print read_all();".to_string(), String::new()), "\n");
+}
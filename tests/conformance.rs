@@ -0,0 +1,94 @@
+//! runs every `.worng` file under `tests/worng` through `run_string_output` and checks it
+//! against its annotations:
+//!
+//!   print 1 + 2; // expect: 3
+//!   print nope; // expect runtime error: [line 2] Undefined variable -> nope
+//!
+//! the output is checked whether or not the file ends in an error, the lines printed before a
+//! runtime error are expected like any others.
+//! `RuntimeError::RuntimeError` has no case, nothing a script does reaches it.
+
+extern crate worng;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use worng::run_string_output;
+
+const EXPECT: &str = "// expect: ";
+const EXPECT_RUNTIME_ERROR: &str = "// expect runtime error: ";
+
+struct Expectations {
+  output: Vec<String>,
+  runtime_error: Option<String>,
+}
+
+fn expectations(source: &str) -> Expectations {
+  let mut expectations = Expectations { output: Vec::new(), runtime_error: None };
+  for line in source.lines() {
+    if let Some(index) = line.find(EXPECT) {
+      expectations.output.push(line[index + EXPECT.len()..].to_string());
+    } else if let Some(index) = line.find(EXPECT_RUNTIME_ERROR) {
+      expectations.runtime_error = Some(line[index + EXPECT_RUNTIME_ERROR.len()..].to_string());
+    }
+  }
+  expectations
+}
+
+// the mismatch, if any.
+fn check(source: &str) -> Result<(), String> {
+  let expectations = expectations(source);
+  let (output, error) = run_string_output(source.to_string(), String::new());
+
+  let lines: Vec<&str> = output.lines().collect();
+  if lines != expectations.output {
+    return Err(format!(
+      "expected output:\n  {}\ngot:\n  {}",
+      expectations.output.join("\n  "),
+      lines.join("\n  ")
+    ));
+  }
+
+  match (expectations.runtime_error, error) {
+    (Some(ref expected), Some(ref actual)) if actual.trim() == expected.trim() => Ok(()),
+    (None, None) => Ok(()),
+    (expected, actual) => Err(format!(
+      "expected runtime error:\n  {}\ngot:\n  {}",
+      expected.as_deref().unwrap_or("none"),
+      actual.as_deref().map_or("none".to_string(), |actual| actual.trim_end().replace('\n', "\n  "))
+    )),
+  }
+}
+
+fn worng_files(dir: &Path, files: &mut Vec<PathBuf>) {
+  let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+    .unwrap_or_else(|err| panic!("{}: {}", dir.display(), err))
+    .map(|entry| entry.unwrap().path())
+    .collect();
+  entries.sort();
+
+  for entry in entries {
+    if entry.is_dir() {
+      worng_files(&entry, files);
+    } else if entry.extension().is_some_and(|extension| extension == "worng") {
+      files.push(entry);
+    }
+  }
+}
+
+#[test]
+fn conformance() {
+  let mut files = Vec::new();
+  worng_files(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/worng"), &mut files);
+  assert!(!files.is_empty(), "no .worng files found");
+
+  let failures: Vec<String> = files
+    .iter()
+    .filter_map(|file| {
+      let source = fs::read_to_string(file).unwrap();
+      check(&source).err().map(|mismatch| format!("{}: {}", file.display(), mismatch))
+    })
+    .collect();
+
+  assert!(failures.is_empty(), "\n{}\n\n{} of {} files failed", failures.join("\n\n"), failures.len(), files.len());
+}
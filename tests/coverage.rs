@@ -0,0 +1,120 @@
+//! `--coverage`: the lines and branches a script or its tests ran, the summary and the lcov
+//! tracefile.
+
+extern crate worng;
+
+use std::fs;
+use std::io::Cursor;
+use std::process::Command;
+
+use worng::{Branch, FileCoverage, Worng};
+
+const PROGRAM: &str = "\
+fun sign(n) {
+  if (n < 0) {
+    return -1;
+  }
+  return 1;
+}
+
+var i = 0;
+while (i < 2 and true) i = i + 1;
+print sign(i) or nil;
+
+fun unused() {
+  print 1;
+  print 2;
+}
+";
+
+fn cover(source: &str) -> (FileCoverage, String) {
+  let mut output = Vec::new();
+  let coverage = Worng::new().cover_source("sign.worng", source.to_string(), &mut Cursor::new(Vec::new()), &mut output);
+  (coverage.unwrap(), String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn counts_statements_on_each_line() {
+  let (coverage, output) = cover(PROGRAM);
+  assert_eq!(output, "1\n");
+  let lines: Vec<(i32, usize)> = coverage.lines.iter().map(|(&line, &hits)| (line, hits)).collect();
+  assert_eq!(lines, vec![(1, 1), (2, 1), (3, 0), (5, 1), (8, 1), (9, 3), (10, 1), (12, 1), (13, 0), (14, 0)]);
+  assert_eq!(coverage.lines_hit(), 7);
+  assert_eq!(coverage.missed_lines(), "3, 13-14");
+}
+
+#[test]
+fn counts_both_ways_of_each_branch() {
+  let (coverage, _) = cover(PROGRAM);
+  assert_eq!(
+    coverage.branches,
+    vec![
+      Branch { line: 2, taken: [0, 1] },
+      Branch { line: 9, taken: [2, 1] },
+      Branch { line: 9, taken: [1, 2] },
+      Branch { line: 10, taken: [1, 0] },
+    ]
+  );
+  assert_eq!((coverage.branches_hit(), coverage.branches_found()), (6, 8));
+}
+
+#[test]
+fn compile_errors_cover_nothing() {
+  let mut output = Vec::new();
+  let coverage = Worng::new().cover_source("bad.worng", "print ;".to_string(), &mut Cursor::new(Vec::new()), &mut output);
+  assert!(coverage.is_none());
+}
+
+#[test]
+fn summary_and_lcov() {
+  let (coverage, _) = cover(PROGRAM);
+  let files = vec![coverage];
+  assert_eq!(
+    FileCoverage::summary(&files),
+    "\
+file                  lines         branches  missed lines
+sign.worng      7/10  70.0%       6/8  75.0%  3, 13-14
+"
+  );
+
+  let lcov = FileCoverage::lcov(&files);
+  assert!(lcov.starts_with("TN:\nSF:sign.worng\nBRDA:2,0,0,0\nBRDA:2,0,1,1\n"), "{}", lcov);
+  assert!(lcov.contains("BRF:8\nBRH:6\n"), "{}", lcov);
+  assert!(lcov.contains("DA:3,0\n"), "{}", lcov);
+  assert!(lcov.ends_with("LF:10\nLH:7\nend_of_record\n"), "{}", lcov);
+}
+
+#[test]
+fn branches_never_reached_are_dashes() {
+  let (coverage, _) = cover("fun never(a) { return a and a; }\n");
+  assert_eq!(coverage.branches, vec![Branch { line: 1, taken: [0, 0] }]);
+  assert!(FileCoverage::lcov(&[coverage]).contains("BRDA:1,0,0,-\nBRDA:1,0,1,-\n"));
+}
+
+#[test]
+fn coverage_options() {
+  let dir = std::env::temp_dir().join(format!("worng-coverage-{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  let script = dir.join("sign.worng");
+  let lcov = dir.join("run.info");
+  fs::write(&script, PROGRAM).unwrap();
+
+  let output = Command::new(env!("CARGO_BIN_EXE_worng")).arg("run").arg("--coverage").arg("--lcov").arg(&lcov).arg(&script).output().unwrap();
+  assert_eq!(output.status.code(), Some(0));
+  assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n");
+  let summary = String::from_utf8_lossy(&output.stderr);
+  assert!(summary.contains("7/10  70.0%"), "{}", summary);
+  assert!(fs::read_to_string(&lcov).unwrap().contains("LH:7\n"));
+
+  fs::write(dir.join("sign_test.worng"), "fun sign(n) {\n  if (n < 0) return -1;\n  return 1;\n}\n\nfun test_sign() {\n  assert_eq(sign(2), 1);\n}\n").unwrap();
+  fs::remove_file(&script).unwrap();
+  let lcov = dir.join("test.info");
+  let output = Command::new(env!("CARGO_BIN_EXE_worng")).arg("test").arg("--coverage").arg("--lcov").arg(&lcov).arg(&dir).output().unwrap();
+  assert_eq!(output.status.code(), Some(0));
+  let report = String::from_utf8_lossy(&output.stdout);
+  assert!(report.contains("test result: ok. 1 passed; 0 failed."), "{}", report);
+  assert!(report.lines().any(|line| line.contains("sign_test.worng") && line.ends_with(" 5/5 100.0%       1/2  50.0%")), "{}", report);
+  assert!(fs::read_to_string(&lcov).unwrap().contains("DA:2,1\n"));
+
+  fs::remove_dir_all(&dir).unwrap();
+}
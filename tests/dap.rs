@@ -0,0 +1,136 @@
+//! `worng dap`: every `tests/dap/<name>.transcript` is a session with the debug adapter,
+//! laid out like the `worng lsp` ones: `-->` starts a request and `<--` a message the
+//! adapter has to send, in that order. `"<program>"` stands for the path of `<name>.worng`.
+
+extern crate worng;
+
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use worng::{Json, Worng};
+
+const PROGRAM: &str = "\"<program>\"";
+
+fn dap_dir() -> PathBuf {
+  Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("dap")
+}
+
+fn frame(message: &str) -> String {
+  format!("Content-Length: {}\r\n\r\n{}", message.len(), message)
+}
+
+// (sent, expected) messages of a transcript.
+fn read_transcript(path: &Path) -> (Vec<String>, Vec<String>) {
+  let program = Json::String(path.with_extension("worng").display().to_string()).to_string();
+
+  let mut messages: Vec<(bool, String)> = Vec::new();
+  for line in fs::read_to_string(path).unwrap().lines() {
+    if line.trim().is_empty() || line.trim_start().starts_with("//") {
+      continue;
+    }
+    if let Some(message) = line.strip_prefix("-->") {
+      messages.push((true, message.to_string()));
+    } else if let Some(message) = line.strip_prefix("<--") {
+      messages.push((false, message.to_string()));
+    } else {
+      let last = messages.last_mut().unwrap_or_else(|| panic!("{}: a message has to start with --> or <--", path.display()));
+      last.1.push('\n');
+      last.1.push_str(line);
+    }
+  }
+
+  let messages: Vec<(bool, String)> = messages.into_iter().map(|(sent, message)| (sent, message.replace(PROGRAM, &program))).collect();
+  let sent = messages.iter().filter(|(sent, _)| *sent).map(|(_, message)| message.clone()).collect();
+  let expected = messages.iter().filter(|(sent, _)| !*sent).map(|(_, message)| message.clone()).collect();
+  (sent, expected)
+}
+
+fn parse(text: &str, path: &Path) -> Json {
+  Json::parse(text).unwrap_or_else(|err| panic!("{}: {} in {}", path.display(), err, text))
+}
+
+// the adapter's messages, without their headers.
+fn split_frames(output: &str) -> Vec<&str> {
+  let mut messages = Vec::new();
+  let mut rest = output;
+  while let Some(start) = rest.find("\r\n\r\n") {
+    let length: usize = rest[..start].trim_start_matches("Content-Length:").trim().parse().unwrap();
+    messages.push(&rest[start + 4..start + 4 + length]);
+    rest = &rest[start + 4 + length..];
+  }
+  messages
+}
+
+// objects are equal whatever order their keys are in.
+fn same(left: &Json, right: &Json) -> bool {
+  match (left, right) {
+    (Json::Object(left), Json::Object(right)) => {
+      left.len() == right.len() && left.iter().all(|(key, value)| {
+        right.iter().any(|(other_key, other_value)| key == other_key && same(value, other_value))
+      })
+    },
+    (Json::Array(left), Json::Array(right)) => left.len() == right.len() && left.iter().zip(right).all(|(left, right)| same(left, right)),
+    _ => left == right,
+  }
+}
+
+#[test]
+fn dap_transcripts() {
+  let mut files: Vec<PathBuf> = fs::read_dir(dap_dir())
+    .unwrap()
+    .map(|entry| entry.unwrap().path())
+    .filter(|path| path.extension().is_some_and(|extension| extension == "transcript"))
+    .collect();
+  files.sort();
+  assert!(!files.is_empty(), "no .transcript files found");
+
+  for file in files {
+    let (sent, expected) = read_transcript(&file);
+    let input: String = sent.iter().map(|message| frame(message)).collect();
+    let mut output = Vec::new();
+    Worng::new().serve_dap(&mut Cursor::new(input.into_bytes()), &mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    let received = split_frames(&output);
+    for (i, expected) in expected.iter().enumerate() {
+      let received = received.get(i).unwrap_or_else(|| panic!("{}: no message #{}, expected {}", file.display(), i + 1, expected));
+      assert!(
+        same(&parse(received, &file), &parse(expected, &file)),
+        "{}: message #{}\nexpected {}\nreceived {}", file.display(), i + 1, expected, received
+      );
+    }
+    assert_eq!(received.len(), expected.len(), "{}: {:?}", file.display(), &received[expected.len().min(received.len())..]);
+  }
+}
+
+#[test]
+fn launch_reports_compile_errors() {
+  let dir = std::env::temp_dir().join(format!("worng-dap-{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  let program = dir.join("broken.worng");
+  fs::write(&program, "print (1;\n").unwrap();
+
+  let launch = format!(
+    r#"{{"seq":1,"type":"request","command":"launch","arguments":{{"program":{}}}}}"#,
+    Json::String(program.display().to_string())
+  );
+  let mut output = Vec::new();
+  Worng::new().serve_dap(&mut Cursor::new(frame(&launch).into_bytes()), &mut output).unwrap();
+
+  let output = String::from_utf8(output).unwrap();
+  let response = Json::parse(split_frames(&output)[0]).unwrap();
+  assert_eq!(response.get("success"), Some(&Json::Bool(false)));
+  let message = response.get("message").and_then(Json::as_str).unwrap();
+  assert!(message.contains("Expect ')'"), "{}", message);
+
+  fs::remove_dir_all(&dir).unwrap();
+}
+
+// framed like LSP messages, with the same limit on their length.
+#[test]
+fn refuses_oversized_messages() {
+  let input = "Content-Length: 99999999999999\r\n\r\n{}";
+  let error = Worng::new().serve_dap(&mut Cursor::new(input.as_bytes().to_vec()), &mut Vec::new()).unwrap_err();
+  assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,113 @@
+//! `worng debug`: the commands are read from the same input as the program's, so a
+//! session is a list of commands and everything the debugger and the program wrote.
+
+extern crate worng;
+
+use std::io::{Cursor, Write};
+use std::process::{Command, Stdio};
+
+use worng::Worng;
+
+const PROGRAM: &str = "\
+fun add(a, b) {
+  var sum = a + b;
+  return sum;
+}
+
+var total = 0;
+for (var i = 0; i < 2; i = i + 1) {
+  total = add(total, i);
+}
+print total;
+";
+
+fn debug(commands: &str) -> String {
+  let mut output = Vec::new();
+  Worng::new().debug_source(PROGRAM.to_string(), &mut Cursor::new(commands.as_bytes().to_vec()), &mut output);
+  String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn breakpoints_frames_and_variables() {
+  let commands = "b 2\nc\nbt\nv\np a * 10 + b\np sum\nn\nv\nf 1\np total = 100\nl\no\nd 2\nc\n";
+  let expected = r#"paused at line 1 in <script> (entry)
+->   1 | fun add(a, b) {
+(worng) breakpoint at line 2
+(worng) paused at line 2 in add (breakpoint)
+->   2 |   var sum = a + b;
+(worng) -> #0 add at line 2
+   #1 <script> at line 8
+(worng) locals:
+  a = 0
+  b = 0
+globals:
+  add = <fn add>
+  total = 0
+(worng) 0
+(worng) error: [line 1] Undefined variable -> sum
+(worng) paused at line 3 in add (step)
+->   3 |   return sum;
+(worng) locals:
+  a = 0
+  b = 0
+  sum = 0
+globals:
+  add = <fn add>
+  total = 0
+(worng) #1 <script> at line 8
+->   8 |   total = add(total, i);
+(worng) 100
+(worng)      6 | var total = 0;
+     7 | for (var i = 0; i < 2; i = i + 1) {
+->   8 |   total = add(total, i);
+     9 | }
+    10 | print total;
+(worng) paused at line 7 in <script> (step)
+->   7 | for (var i = 0; i < 2; i = i + 1) {
+(worng) deleted the breakpoint at line 2
+(worng) 1
+"#;
+  assert_eq!(debug(commands), expected);
+}
+
+#[test]
+fn breakpoints_move_to_the_next_statement() {
+  let output = debug("b 5\nb 40\nbreakpoints\nbogus\n");
+  assert!(output.contains("breakpoint at line 6\n"), "{}", output);
+  assert!(output.contains("no statement on or after line '40'\n"), "{}", output);
+  assert!(output.contains("breakpoints at lines 6\n"), "{}", output);
+  assert!(output.contains("unknown command 'bogus'"), "{}", output);
+}
+
+#[test]
+fn runs_to_the_end_without_commands() {
+  assert_eq!(debug(""), "paused at line 1 in <script> (entry)\n->   1 | fun add(a, b) {\n(worng) 1\n");
+}
+
+#[test]
+fn debug_command() {
+  let worng = |commands: &str, file: &str| {
+    let dir = std::env::temp_dir().join(format!("worng-debug-{}-{}", std::process::id(), file));
+    std::fs::write(&dir, PROGRAM.replace("print total;", file)).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_worng"))
+      .arg("debug")
+      .arg(&dir)
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped())
+      .spawn()
+      .unwrap();
+    child.stdin.take().unwrap().write_all(commands.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    std::fs::remove_file(&dir).unwrap();
+    output
+  };
+
+  let quit = worng("n\nq\n", "print total;");
+  assert_eq!(quit.status.code(), Some(0));
+  assert!(!String::from_utf8_lossy(&quit.stdout).ends_with("1\n"));
+
+  let failed = worng("c\n", "print nothing;");
+  assert_eq!(failed.status.code(), Some(70));
+  assert!(String::from_utf8_lossy(&failed.stderr).contains("nothing"));
+}
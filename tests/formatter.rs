@@ -0,0 +1,121 @@
+//! `worng fmt`: every `tests/fmt/<name>.worng` has to format to `<name>.expected`, and
+//! formatting any `.worng` file under `tests` a second time must not change it again.
+
+extern crate worng;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use worng::{format_source, run_string};
+
+// as much stack as the `worng` binary gives the interpreter, for the deep recursion cases.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+fn tests_dir() -> PathBuf {
+  Path::new(env!("CARGO_MANIFEST_DIR")).join("tests")
+}
+
+fn files_with_extension(dir: &Path, extension: &str, files: &mut Vec<PathBuf>) {
+  let mut entries: Vec<PathBuf> = fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+  entries.sort();
+
+  for entry in entries {
+    if entry.is_dir() {
+      files_with_extension(&entry, extension, files);
+    } else if entry.extension().is_some_and(|found| found == extension) {
+      files.push(entry);
+    }
+  }
+}
+
+fn format(source: &str) -> String {
+  format_source(source).unwrap_or_else(|errors| {
+    let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+    panic!("{}", messages.join("\n"))
+  })
+}
+
+#[test]
+fn formats_to_expected() {
+  let mut files = Vec::new();
+  files_with_extension(&tests_dir().join("fmt"), "worng", &mut files);
+  assert!(!files.is_empty(), "no .worng files found");
+
+  for file in files {
+    let expected = fs::read_to_string(file.with_extension("expected")).unwrap();
+    let formatted = format(&fs::read_to_string(&file).unwrap());
+    assert_eq!(formatted, expected, "{}", file.display());
+  }
+}
+
+#[test]
+fn formatting_is_idempotent() {
+  let mut files = Vec::new();
+  files_with_extension(&tests_dir(), "worng", &mut files);
+  files_with_extension(&tests_dir(), "expected", &mut files);
+
+  for file in files {
+    let once = format(&fs::read_to_string(&file).unwrap());
+    assert_eq!(format(&once), once, "{}", file.display());
+  }
+}
+
+// runtime errors name lines, which formatting moves, so only programs running to the end are compared.
+#[test]
+fn formatting_keeps_behaviour() {
+  let mut files = Vec::new();
+  files_with_extension(&tests_dir().join("worng"), "worng", &mut files);
+
+  let sources: Vec<(PathBuf, String)> = files
+    .into_iter()
+    .filter(|file| !file.components().any(|component| component.as_os_str() == "runtime_errors"))
+    .map(|file| { let source = fs::read_to_string(&file).unwrap(); (file, source) })
+    .collect();
+
+  std::thread::Builder::new()
+    .stack_size(STACK_SIZE)
+    .spawn(move || {
+      for (file, source) in sources {
+        let formatted = format(&source);
+        assert_eq!(run_string(formatted), run_string(source), "{}", file.display());
+      }
+    })
+    .unwrap()
+    .join()
+    .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+}
+
+#[test]
+fn reports_syntax_errors() {
+  let errors = format_source("print (1;").err().expect("an error");
+  assert_eq!(errors.len(), 1);
+  assert!(errors[0].to_string().contains("Expect ')'"), "{}", errors[0]);
+}
+
+#[test]
+fn fmt_command() {
+  let dir = std::env::temp_dir().join(format!("worng-fmt-{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  let messy = dir.join("messy.worng");
+  let tidy = dir.join("tidy.worng");
+  fs::write(&messy, "var a=1;\n").unwrap();
+  fs::write(&tidy, "var a = 1;\n").unwrap();
+
+  let worng = |args: &[&Path]| Command::new(env!("CARGO_BIN_EXE_worng")).arg("fmt").args(args).output().unwrap();
+
+  let check = worng(&[Path::new("--check"), &messy, &tidy]);
+  assert_eq!(check.status.code(), Some(1));
+  assert_eq!(String::from_utf8_lossy(&check.stdout), format!("{}\n", messy.display()));
+  assert_eq!(fs::read_to_string(&messy).unwrap(), "var a=1;\n");
+
+  let rewrite = worng(&[&messy, &tidy]);
+  assert_eq!(rewrite.status.code(), Some(0));
+  assert_eq!(fs::read_to_string(&messy).unwrap(), "var a = 1;\n");
+
+  let check = worng(&[Path::new("--check"), &messy, &tidy]);
+  assert_eq!(check.status.code(), Some(0));
+  assert!(check.stdout.is_empty());
+
+  fs::remove_dir_all(&dir).unwrap();
+}
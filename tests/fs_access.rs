@@ -0,0 +1,127 @@
+//! the `fs` natives under each `FsAccess`: `Within` keeps every path, `..`, absolute or going
+//! through a symlink, inside its root.
+
+extern crate worng;
+
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use worng::{FsAccess, Worng};
+
+struct Dirs {
+  base: PathBuf,
+  root: PathBuf,
+  outside: PathBuf,
+}
+
+impl Dirs {
+  fn new(name: &str) -> Dirs {
+    let base = std::env::temp_dir().join(format!("worng-fs-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&base);
+    let root = base.join("root");
+    let outside = base.join("outside");
+    fs::create_dir_all(root.join("sub")).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+    fs::write(root.join("inside.txt"), "inside").unwrap();
+    fs::write(outside.join("secret.txt"), "secret").unwrap();
+    Dirs { base, root, outside }
+  }
+}
+
+impl Drop for Dirs {
+  fn drop(&mut self) {
+    let _ = fs::remove_dir_all(&self.base);
+  }
+}
+
+// what the script printed and whether it stopped with a runtime error.
+fn run(access: FsAccess, source: &str) -> (String, bool) {
+  let mut worng = Worng::new();
+  worng.fs_access = access;
+  let mut output = Vec::new();
+  worng.run_source(source.to_string(), &mut Cursor::new(Vec::new()), &mut output);
+  (String::from_utf8(output).unwrap(), worng.had_runtime_error)
+}
+
+fn within(dirs: &Dirs, source: &str) -> (String, bool) {
+  run(FsAccess::Within(dirs.root.clone()), source)
+}
+
+#[test]
+fn denied_refuses_everything() {
+  let dirs = Dirs::new("denied");
+  let path = dirs.root.join("inside.txt");
+  for call in &["print fs.read_file(\"{}\");", "print fs.exists(\"{}\");", "fs.write_file(\"{}\", \"x\");"] {
+    let (output, failed) = run(FsAccess::Denied, &call.replace("{}", &path.display().to_string()));
+    assert_eq!((output.as_str(), failed), ("", true), "{}", call);
+  }
+  assert_eq!(fs::read_to_string(&path).unwrap(), "inside");
+}
+
+#[test]
+fn within_reads_and_writes_inside_the_root() {
+  let dirs = Dirs::new("inside");
+  let source = "print fs.read_file(\"inside.txt\"); fs.write_file(\"sub/new.txt\", \"new\"); print fs.read_file(\"sub/../sub/./new.txt\"); print fs.list_dir(\".\");";
+  assert_eq!(within(&dirs, source), ("inside\nnew\n[inside.txt, sub]\n".to_string(), false));
+  assert_eq!(fs::read_to_string(dirs.root.join("sub/new.txt")).unwrap(), "new");
+}
+
+#[test]
+fn within_refuses_parent_and_absolute_paths() {
+  let dirs = Dirs::new("escape");
+  let secret = dirs.outside.join("secret.txt");
+  for path in &["../outside/secret.txt".to_string(), "sub/../../outside/secret.txt".to_string(), secret.display().to_string()] {
+    assert_eq!(within(&dirs, &format!("print fs.read_file(\"{}\");", path)), (String::new(), true), "{}", path);
+    assert_eq!(within(&dirs, &format!("fs.write_file(\"{}\", \"x\");", path)), (String::new(), true), "{}", path);
+  }
+  assert_eq!(within(&dirs, "fs.write_file(\"../new.txt\", \"x\");"), (String::new(), true));
+  assert_eq!(fs::read_to_string(&secret).unwrap(), "secret");
+  assert!(!dirs.base.join("new.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn within_refuses_symlinks_out_of_the_root() {
+  use std::os::unix::fs::symlink;
+
+  let dirs = Dirs::new("symlinks");
+  symlink(dirs.outside.join("secret.txt"), dirs.root.join("file_link")).unwrap();
+  symlink(&dirs.outside, dirs.root.join("dir_link")).unwrap();
+  // dangling: their targets don't exist yet.
+  symlink(dirs.outside.join("created.txt"), dirs.root.join("dangling")).unwrap();
+  symlink("../outside/relative.txt", dirs.root.join("relative_dangling")).unwrap();
+  symlink(dirs.outside.join("missing_dir"), dirs.root.join("dangling_dir")).unwrap();
+
+  for source in &[
+    "print fs.read_file(\"file_link\");",
+    "fs.write_file(\"file_link\", \"x\");",
+    "print fs.read_file(\"dir_link/secret.txt\");",
+    "fs.write_file(\"dangling\", \"x\");",
+    "fs.append(\"dangling\", \"x\");",
+    "fs.write_file(\"relative_dangling\", \"x\");",
+    "fs.write_file(\"dangling_dir/new.txt\", \"x\");",
+    "print fs.exists(\"dangling\");",
+  ] {
+    assert_eq!(within(&dirs, source), (String::new(), true), "{}", source);
+  }
+  assert_eq!(fs::read_to_string(dirs.outside.join("secret.txt")).unwrap(), "secret");
+  assert!(!dirs.outside.join("created.txt").exists());
+  assert!(!dirs.outside.join("relative.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn within_follows_symlinks_that_stay_inside() {
+  use std::os::unix::fs::symlink;
+
+  let dirs = Dirs::new("inner-links");
+  symlink("inside.txt", dirs.root.join("alias")).unwrap();
+  symlink("sub/later.txt", dirs.root.join("later")).unwrap();
+  symlink("loop_b", dirs.root.join("loop_a")).unwrap();
+  symlink("loop_a", dirs.root.join("loop_b")).unwrap();
+
+  assert_eq!(within(&dirs, "print fs.read_file(\"alias\"); fs.write_file(\"later\", \"later\");"), ("inside\n".to_string(), false));
+  assert_eq!(fs::read_to_string(dirs.root.join("sub/later.txt")).unwrap(), "later");
+  assert_eq!(within(&dirs, "print fs.read_file(\"loop_a\");"), (String::new(), true));
+}
@@ -0,0 +1,121 @@
+//! inputs the fuzz targets in `fuzz/` found crashing the process, they have to end in an error
+//! instead. most are too long to write down, so they are built here. they run on the test's
+//! own thread, like the fuzz targets `run_string` gives the interpreter a stack of its own.
+
+extern crate worng;
+
+use worng::{run_string, Json};
+
+const TOO_DEEP: &str = "[Line: 1] Too deeply nested, at most 200 levels are allowed.";
+const TOO_LONG: &str = "[Line: 1] Too deeply nested, at most 5000 levels are allowed.";
+const STACK_OVERFLOW: &str = "Stack overflow, more than 5000 levels of nested calls and expressions.";
+
+// parsing goes on after the error, the rest of a deep program may report it again.
+fn assert_too_deep(source: String) {
+  let output = run_string(source);
+  assert_eq!(output.lines().next(), Some(TOO_DEEP), "{}", output);
+}
+
+fn assert_too_long(source: String) {
+  let output = run_string(source);
+  assert_eq!(output.lines().next(), Some(TOO_LONG), "{}", output);
+}
+
+fn nested(open: &str, inner: &str, close: &str, levels: usize) -> String {
+  format!("{}{}{}", open.repeat(levels), inner, close.repeat(levels))
+}
+
+#[test]
+fn deep_grouping() {
+  assert_too_deep(format!("print {};", nested("(", "1", ")", 1000)));
+}
+
+#[test]
+fn deep_unary() {
+  assert_too_deep(format!("print {}1;", "-".repeat(1000)));
+  assert_too_deep(format!("print {}true;", "!".repeat(1000)));
+}
+
+#[test]
+fn deep_blocks() {
+  assert_too_deep(nested("{", "", "}", 5000));
+}
+
+#[test]
+fn deep_control_flow() {
+  assert_too_deep(nested("if (true) {", "", "}", 1000));
+  assert_too_deep(nested("while (false) {", "", "}", 1000));
+  assert_too_deep(nested("fun f() {", "", "}", 1000));
+}
+
+// a chain nests the tree without nesting the parser, it has a limit of its own per statement.
+#[test]
+fn long_binary_chains() {
+  assert_too_long(format!("print 1{};", " + 1".repeat(10_000)));
+  assert_too_long(format!("print true{};", " and true".repeat(10_000)));
+  assert_too_long(format!("print 1{};", " < 1 == true".repeat(5000)));
+}
+
+#[test]
+fn long_call_and_get_chains() {
+  assert_too_long(format!("print f{};", "()".repeat(10_000)));
+  assert_too_long(format!("print a{};", ".b".repeat(10_000)));
+}
+
+#[test]
+fn long_flat_chains_still_run() {
+  assert_eq!(run_string(format!("print 0{};", " + 1".repeat(250))), "250\n");
+  assert_eq!(run_string(format!("print 1{};", " * 2 / 2".repeat(250))), "1\n");
+  assert_eq!(run_string(format!("print true{};", " and true".repeat(250))), "true\n");
+  assert_eq!(run_string(format!("print false{};", " or false".repeat(250))), "false\n");
+  assert_eq!(run_string(format!("print true{};", " == true".repeat(250))), "true\n");
+  assert_eq!(run_string(format!("print 1 < 2{};", " != false".repeat(250))), "true\n");
+  assert_eq!(run_string(format!("fun f() {{ return f; }}\nprint f{} == f;", "()".repeat(250))), "true\n");
+  // each statement gets the whole limit.
+  assert_eq!(run_string(format!("var a = 0{0};\nprint a{0};", " + 1".repeat(4000))), "8000\n");
+  assert_eq!(run_string(format!("print 0{};", " + 1".repeat(4900))), "4900\n");
+}
+
+#[test]
+fn long_assignment_chains() {
+  assert_too_deep(format!("var a; {}1;", "a = ".repeat(5000)));
+}
+
+#[test]
+fn nesting_below_the_limit_still_runs() {
+  assert_eq!(run_string(format!("print {};", nested("(", "1", ")", 150))), "1\n");
+  assert_eq!(run_string(format!("print {}1;", "-".repeat(150))), "1\n");
+  assert_eq!(run_string(format!("print 0{};", " + 1".repeat(150))), "150\n");
+}
+
+#[test]
+fn unbounded_recursion() {
+  let error = run_string("fun f() { f(); }\nf();".to_string());
+  assert_eq!(error, format!("[line 1] {}", STACK_OVERFLOW));
+
+  let error = run_string("fun even(n) { return odd(n + 1); }\nfun odd(n) { return even(n + 1); }\neven(0);".to_string());
+  assert!(error.ends_with(STACK_OVERFLOW), "{}", error);
+}
+
+// deep expressions in every frame take the most stack per call.
+#[test]
+fn recursion_through_deep_expressions() {
+  let source = format!("fun f() {{ return {}; }}\nf();", nested("(", "-f()", ")", 190));
+  assert_eq!(run_string(source), format!("[line 1] {}", STACK_OVERFLOW));
+}
+
+// on the test's own stack, deep JSON used to overflow it long before the interpreter's limits.
+#[test]
+fn deep_json() {
+  let error = run_string(format!("json.parse(\"{}\");", "[".repeat(100_000)));
+  assert_eq!(error, "[line 1] parse() got invalid JSON: Nested too deeply at line 1 column 513.");
+  let error = Json::parse(&"{\"a\": ".repeat(100_000)).unwrap_err();
+  assert_eq!(error.message, "Nested too deeply");
+}
+
+// a `to_json()` giving a new instance each time used to be serialized until the stack ran out.
+#[test]
+fn self_returning_to_json() {
+  let error = run_string("class Loop { to_json() { return Loop(); } } json.stringify(Loop(), nil);".to_string());
+  assert_eq!(error, "[line 1] stringify() can't serialize values nested more than 512 levels deep.");
+}
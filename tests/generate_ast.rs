@@ -0,0 +1,185 @@
+//! the generated `Expr` and `Stmt` are what `generate_ast` writes, and their walks visit
+//! every node, changing them with the `Mut` visitors.
+
+extern crate worng;
+
+use std::fs;
+use std::process::Command;
+
+use worng::{print_program, read_program, walk_expr, walk_expr_mut, walk_stmt, Expr, ExprVisitor, ExprVisitorMut, Literal, Stmt, StmtVisitor, StmtVisitorMut, Token};
+
+#[test]
+fn generated_files_are_up_to_date() {
+  let dir = std::env::temp_dir().join(format!("worng-generate-ast-{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+
+  let status = Command::new(env!("CARGO_BIN_EXE_generate_ast")).arg(&dir).status().unwrap();
+  assert!(status.success());
+  for file in &["expr.rs", "statement.rs"] {
+    let generated = fs::read_to_string(dir.join(file)).unwrap();
+    let checked_in = fs::read_to_string(format!("{}/src/worng/{}", env!("CARGO_MANIFEST_DIR"), file)).unwrap();
+    assert!(generated == checked_in, "src/worng/{} is out of date, run `cargo run --bin generate_ast -- src/worng`", file);
+  }
+
+  fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn usage() {
+  let output = Command::new(env!("CARGO_BIN_EXE_generate_ast")).output().unwrap();
+  assert_eq!(output.status.code(), Some(64));
+  assert_eq!(String::from_utf8_lossy(&output.stderr), "Usage: generate_ast <output directory>\n");
+}
+
+// the names read, in the order the walks get to them. only `Var` gets its own visit.
+#[derive(Default)]
+struct Names {
+  names: Vec<String>,
+  statements: usize,
+}
+
+impl ExprVisitor<()> for Names {
+  fn visit_var_expr(&mut self, name: &Token, _depth: &Option<usize>) {
+    self.names.push(name.lexeme.clone());
+  }
+}
+
+impl StmtVisitor<(), ()> for Names {
+  fn visit_stmt(&mut self, stmt: &Stmt) {
+    self.statements += 1;
+    walk_stmt(self, stmt);
+  }
+}
+
+#[test]
+fn walks_visit_every_node() {
+  let program = read_program(
+    "(class B < A (fun f (x) (block (if (and a b) (return (call c (get d e) (group f))) (while (! g) (expr (assign h (set i j (- k)))))))))\n(print l)\n",
+  )
+  .unwrap_or_else(|_| panic!("doesn't read"));
+
+  let mut names = Names::default();
+  for statement in program.iter() {
+    names.visit_stmt(statement);
+  }
+  assert_eq!(names.names, vec!["A", "a", "b", "c", "d", "f", "g", "i", "k", "l"]);
+  assert_eq!(names.statements, 8);
+}
+
+// turns `-n` into the literal and drops `print` statements' groupings.
+struct Fold;
+
+impl ExprVisitorMut<()> for Fold {
+  fn visit_expr_mut(&mut self, expr: &mut Expr) {
+    walk_expr_mut(self, expr);
+    let folded = match *expr {
+      Expr::Unary(ref operator, ref right) if operator.lexeme == "-" => match **right {
+        Expr::Literal(Literal::Number(number)) => Some(Expr::Literal(Literal::Number(-number))),
+        _ => None,
+      },
+      _ => None,
+    };
+    if let Some(folded) = folded {
+      *expr = folded;
+    }
+  }
+}
+
+impl StmtVisitorMut<(), ()> for Fold {
+  fn visit_print_stmt_mut(&mut self, _keyword: &mut Token, expression: &mut Expr) {
+    if let Expr::Grouping(inner) = expression.clone() {
+      *expression = *inner;
+    }
+    self.visit_expr_mut(expression);
+  }
+}
+
+#[test]
+fn mut_walks_change_nodes() {
+  let mut program = read_program("(fun f () (block (print (group (- 2))) (while (< x (- 1)) (expr (- x)))))\n")
+    .unwrap_or_else(|_| panic!("doesn't read"));
+  for statement in program.iter_mut() {
+    Fold.visit_stmt_mut(statement);
+  }
+  assert_eq!(print_program(&program), "(fun f () (block (print -2) (while (< x -1) (expr (- x)))))\n");
+}
+
+// the numbers the `print`s show. its own visits pass errors on with `?`, the kinds left to the
+// walk pass on the first error of their parts and give `Ok` of nothing without one.
+struct Printed;
+
+impl ExprVisitor<Result<f64, String>> for Printed {
+  fn visit_literal_expr(&mut self, value: &Literal) -> Result<f64, String> {
+    match *value {
+      Literal::Number(number) => Ok(number),
+      ref other => Err(format!("{} isn't a number", other)),
+    }
+  }
+
+  fn visit_binary_expr(&mut self, left: &Expr, operator: &Token, right: &Expr) -> Result<f64, String> {
+    let (left, right) = (self.visit_expr(left)?, self.visit_expr(right)?);
+    match operator.lexeme.as_str() {
+      "+" => Ok(left + right),
+      "-" => Ok(left - right),
+      _ => Err(format!("can't {}", operator.lexeme)),
+    }
+  }
+}
+
+impl StmtVisitor<Result<Vec<f64>, String>, Result<f64, String>> for Printed {
+  fn visit_print_stmt(&mut self, _keyword: &Token, expression: &Expr) -> Result<Vec<f64>, String> {
+    Ok(vec![self.visit_expr(expression)?])
+  }
+
+  fn visit_block_stmt(&mut self, statements: &[Stmt]) -> Result<Vec<f64>, String> {
+    let mut printed = Vec::new();
+    for statement in statements {
+      printed.extend(self.visit_stmt(statement)?);
+    }
+    Ok(printed)
+  }
+}
+
+#[test]
+fn visits_default_to_the_walk() {
+  let program = read_program("(block (print (+ 1 2)) (expr (call f)) (print (- 5 (group 1))) (var a 3))\n")
+    .unwrap_or_else(|_| panic!("doesn't read"));
+  // a group is left to the walk, which visits its expression and gives 0.
+  assert_eq!(Printed.visit_stmt(&program[0]), Ok(vec![3.0, 5.0]));
+
+  let program = read_program("(block (print 1) (print (* 2 3)) (print \"a\"))\n").unwrap_or_else(|_| panic!("doesn't read"));
+  assert_eq!(Printed.visit_stmt(&program[0]), Err("can't *".to_string()));
+}
+
+// the first name read that isn't declared, only `Var` gets its own visit.
+struct Undeclared;
+
+impl ExprVisitor<Result<(), String>> for Undeclared {
+  fn visit_var_expr(&mut self, name: &Token, _depth: &Option<usize>) -> Result<(), String> {
+    match name.lexeme.as_str() {
+      "a" | "b" => Ok(()),
+      other => Err(format!("{} isn't declared", other)),
+    }
+  }
+}
+
+impl StmtVisitor<Result<(), String>, Result<(), String>> for Undeclared {}
+
+#[test]
+fn walks_stop_at_the_first_break() {
+  let program = read_program("(expr (+ (group a) (call b 1 (- (get c d)) e)))
+(while a (block (print b) (if (! f) (print g))))
+")
+    .unwrap_or_else(|_| panic!("doesn't read"));
+  let expression = match program[0] {
+    Stmt::Expr(ref expression) => expression,
+    _ => panic!("not an expression statement"),
+  };
+  assert_eq!(Undeclared.visit_expr(expression), Err("c isn't declared".to_string()));
+  assert_eq!(walk_expr(&mut Undeclared, expression), Err("c isn't declared".to_string()));
+  assert_eq!(Undeclared.visit_stmt(&program[1]), Err("f isn't declared".to_string()));
+
+  let declared = read_program("(print (+ a (group b)))
+").unwrap_or_else(|_| panic!("doesn't read"));
+  assert_eq!(Undeclared.visit_stmt(&declared[0]), Ok(()));
+}
@@ -0,0 +1,81 @@
+//! `worng lint`: every line of a `tests/lint/*.worng` file ending in
+//! `// expect warning: rule` has to get exactly that warning, and no other line any.
+
+extern crate worng;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use worng::{lint, Rule};
+
+const EXPECT: &str = "// expect warning: ";
+
+fn lint_dir() -> PathBuf {
+  Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("lint")
+}
+
+// (line, rule) pairs, in the order they are in the file.
+fn expected_warnings(source: &str) -> Vec<(i32, String)> {
+  source
+    .lines()
+    .enumerate()
+    .filter_map(|(index, line)| line.find(EXPECT).map(|at| (index as i32 + 1, line[at + EXPECT.len()..].trim().to_string())))
+    .collect()
+}
+
+fn found_warnings(source: &str, allowed: &[Rule]) -> Vec<(i32, String)> {
+  let warnings = lint(source, allowed).unwrap_or_else(|errors| {
+    let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+    panic!("{}", messages.join("\n"))
+  });
+  warnings.iter().map(|warning| (warning.token.line, warning.rule.name().to_string())).collect()
+}
+
+#[test]
+fn lint_fixtures() {
+  let mut files: Vec<PathBuf> = fs::read_dir(lint_dir()).unwrap().map(|entry| entry.unwrap().path()).collect();
+  files.sort();
+  assert!(!files.is_empty(), "no .worng files found");
+
+  for file in files {
+    let source = fs::read_to_string(&file).unwrap();
+    assert_eq!(found_warnings(&source, &[]), expected_warnings(&source), "{}", file.display());
+  }
+}
+
+#[test]
+fn allowed_rules_are_skipped() {
+  let source = fs::read_to_string(lint_dir().join("unused.worng")).unwrap();
+  let found = found_warnings(&source, &[Rule::UnusedVariable]);
+  assert!(!found.is_empty());
+  assert!(found.iter().all(|(_, rule)| rule == "unused-parameter"), "{:?}", found);
+}
+
+#[test]
+fn reports_syntax_errors() {
+  let errors = lint("print (1;", &[]).err().expect("an error");
+  assert_eq!(errors.len(), 1);
+  assert!(errors[0].to_string().contains("Expect ')'"), "{}", errors[0]);
+}
+
+#[test]
+fn lint_command() {
+  let worng = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_worng")).arg("lint").args(args).current_dir(lint_dir()).output().unwrap();
+
+  let warned = worng(&["self_comparison.worng"]);
+  assert_eq!(warned.status.code(), Some(1));
+  let stdout = String::from_utf8_lossy(&warned.stdout);
+  assert_eq!(
+    stdout.lines().next(),
+    Some("self_comparison.worng:2:9: warning: '==' compares a value to itself. [self-comparison]")
+  );
+
+  let allowed = worng(&["--allow", "self-comparison", "self_comparison.worng", "clean.worng"]);
+  assert_eq!(allowed.status.code(), Some(0));
+  assert!(allowed.stdout.is_empty());
+
+  let unknown = worng(&["--allow", "everything", "clean.worng"]);
+  assert_ne!(unknown.status.code(), Some(0));
+  assert!(String::from_utf8_lossy(&unknown.stderr).contains("unknown lint rule 'everything'"));
+}
@@ -0,0 +1,140 @@
+//! `worng lsp`: every `tests/lsp/<name>.transcript` is a conversation with the server.
+//! `-->` starts a message from the client and `<--` one the server has to answer with, in
+//! that order. a message may go on over the following lines, `//` lines are comments.
+//! the string `"<source>"` in a client message stands for the text of `<name>.worng`.
+
+extern crate worng;
+
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use worng::{Json, Worng};
+
+const SOURCE: &str = "\"<source>\"";
+
+fn lsp_dir() -> PathBuf {
+  Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("lsp")
+}
+
+fn frame(message: &str) -> String {
+  format!("Content-Length: {}\r\n\r\n{}", message.len(), message)
+}
+
+// (sent, expected) messages of a transcript.
+fn read_transcript(path: &Path) -> (Vec<String>, Vec<String>) {
+  let source = fs::read_to_string(path.with_extension("worng")).unwrap_or_default();
+  let source = Json::String(source).to_string();
+
+  let mut messages: Vec<(bool, String)> = Vec::new();
+  for line in fs::read_to_string(path).unwrap().lines() {
+    if line.trim().is_empty() || line.trim_start().starts_with("//") {
+      continue;
+    }
+    if let Some(message) = line.strip_prefix("-->") {
+      messages.push((true, message.replace(SOURCE, &source)));
+    } else if let Some(message) = line.strip_prefix("<--") {
+      messages.push((false, message.to_string()));
+    } else {
+      let last = messages.last_mut().unwrap_or_else(|| panic!("{}: a message has to start with --> or <--", path.display()));
+      last.1.push('\n');
+      last.1.push_str(line);
+    }
+  }
+
+  let sent = messages.iter().filter(|(sent, _)| *sent).map(|(_, message)| message.clone()).collect();
+  let expected = messages.iter().filter(|(sent, _)| !*sent).map(|(_, message)| message.clone()).collect();
+  (sent, expected)
+}
+
+fn parse(text: &str, path: &Path) -> Json {
+  Json::parse(text).unwrap_or_else(|err| panic!("{}: {} in {}", path.display(), err, text))
+}
+
+// the server's messages, without their headers.
+fn split_frames(output: &str) -> Vec<&str> {
+  let mut messages = Vec::new();
+  let mut rest = output;
+  while let Some(start) = rest.find("\r\n\r\n") {
+    let length: usize = rest[..start].trim_start_matches("Content-Length:").trim().parse().unwrap();
+    messages.push(&rest[start + 4..start + 4 + length]);
+    rest = &rest[start + 4 + length..];
+  }
+  messages
+}
+
+// objects are equal whatever order their keys are in.
+fn same(left: &Json, right: &Json) -> bool {
+  match (left, right) {
+    (Json::Object(left), Json::Object(right)) => {
+      left.len() == right.len() && left.iter().all(|(key, value)| {
+        right.iter().any(|(other_key, other_value)| key == other_key && same(value, other_value))
+      })
+    },
+    (Json::Array(left), Json::Array(right)) => left.len() == right.len() && left.iter().zip(right).all(|(left, right)| same(left, right)),
+    _ => left == right,
+  }
+}
+
+#[test]
+fn lsp_transcripts() {
+  let mut files: Vec<PathBuf> = fs::read_dir(lsp_dir())
+    .unwrap()
+    .map(|entry| entry.unwrap().path())
+    .filter(|path| path.extension().is_some_and(|extension| extension == "transcript"))
+    .collect();
+  files.sort();
+  assert!(!files.is_empty(), "no .transcript files found");
+
+  for file in files {
+    let (sent, expected) = read_transcript(&file);
+    let input: String = sent.iter().map(|message| frame(message)).collect();
+    let mut output = Vec::new();
+    Worng::new().serve_lsp(&mut Cursor::new(input.into_bytes()), &mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    let received = split_frames(&output);
+    for (i, expected) in expected.iter().enumerate() {
+      let received = received.get(i).unwrap_or_else(|| panic!("{}: no message #{}, expected {}", file.display(), i + 1, expected));
+      assert!(
+        same(&parse(received, &file), &parse(expected, &file)),
+        "{}: message #{}\nexpected {}\nreceived {}", file.display(), i + 1, expected, received
+      );
+    }
+    assert_eq!(received.len(), expected.len(), "{}: {:?}", file.display(), &received[expected.len().min(received.len())..]);
+  }
+}
+
+#[test]
+fn lsp_command_exit_status() {
+  let run = |messages: &[&str]| {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_worng"))
+      .arg("lsp")
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .spawn()
+      .unwrap();
+    let input: String = messages.iter().map(|message| frame(message)).collect();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap().status.code()
+  };
+
+  let shutdown = r#"{"jsonrpc":"2.0","id":1,"method":"shutdown"}"#;
+  let exit = r#"{"jsonrpc":"2.0","method":"exit"}"#;
+  assert_eq!(run(&[shutdown, exit]), Some(0));
+  assert_eq!(run(&[exit]), Some(1));
+}
+
+// the length comes from the client, a huge one is refused instead of allocated.
+#[test]
+fn refuses_oversized_messages() {
+  let serve = |input: &str| Worng::new().serve_lsp(&mut Cursor::new(input.as_bytes().to_vec()), &mut Vec::new()).unwrap_err();
+
+  let error = serve("Content-Length: 99999999999999\r\n\r\n{}");
+  assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+  assert!(error.to_string().starts_with("Content-Length of 99999999999999 bytes is over the limit"), "{}", error);
+
+  let error = serve("Content-Length: 100\r\n\r\n{}");
+  assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+}
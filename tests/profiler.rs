@@ -0,0 +1,117 @@
+//! `worng run --profile`: the times differ from run to run, so only the calls, the line hits
+//! and the shape of the stacks are compared.
+
+extern crate worng;
+
+use std::fs;
+use std::io::Cursor;
+use std::process::Command;
+
+use worng::{Profiler, Worng};
+
+const PROGRAM: &str = "\
+fun fib(n) {
+  if (n < 2) return n;
+  return fib(n - 1) + fib(n - 2);
+}
+
+class Counter {
+  init() { this.count = 0; }
+  add() { this.count = this.count + 1; }
+}
+
+var counter = Counter();
+for (var i = 0; i < 3; i = i + 1) counter.add();
+print fib(5);
+";
+
+fn profile(source: &str) -> (Profiler, String) {
+  let mut output = Vec::new();
+  let profiler = Worng::new().profile_source(source.to_string(), &mut Cursor::new(Vec::new()), &mut output);
+  (profiler, String::from_utf8(output).unwrap())
+}
+
+fn calls(profiler: &Profiler) -> Vec<(String, usize)> {
+  profiler.functions.iter().map(|function| (function.label(), function.calls)).collect()
+}
+
+#[test]
+fn counts_calls_by_declaration() {
+  let (profiler, output) = profile(PROGRAM);
+  assert_eq!(output, "5\n");
+  assert_eq!(
+    calls(&profiler),
+    vec![("<script>".to_string(), 1), ("init:7".to_string(), 1), ("add:8".to_string(), 3), ("fib:1".to_string(), 15)]
+  );
+
+  for function in &profiler.functions {
+    assert!(function.exclusive <= function.inclusive, "{:?}", function);
+  }
+  let script = &profiler.functions[0];
+  assert!(profiler.functions[1..].iter().all(|function| function.inclusive <= script.inclusive));
+}
+
+#[test]
+fn counts_statements_on_each_line() {
+  let (profiler, _) = profile(PROGRAM);
+  let hits: Vec<(i32, usize)> = profiler.lines.iter().map(|(&line, &hits)| (line, hits)).collect();
+  // line 2 runs the `if` of every call and the `return` of the 8 that end there,
+  // line 12 the `var`, the loop, 3 calls and 3 increments.
+  assert_eq!(hits, vec![(1, 1), (2, 23), (3, 7), (6, 1), (7, 1), (8, 3), (11, 1), (12, 8), (13, 1)]);
+}
+
+#[test]
+fn collapses_stacks() {
+  let (profiler, _) = profile(PROGRAM);
+  let folded = profiler.folded();
+  let stacks: Vec<&str> = folded.lines().map(|line| line.rsplit_once(' ').unwrap().0).collect();
+  assert_eq!(
+    stacks,
+    vec![
+      "<script>",
+      "<script>;add:8",
+      "<script>;fib:1",
+      "<script>;fib:1;fib:1",
+      "<script>;fib:1;fib:1;fib:1",
+      "<script>;fib:1;fib:1;fib:1;fib:1",
+      "<script>;fib:1;fib:1;fib:1;fib:1;fib:1",
+      "<script>;init:7",
+    ]
+  );
+  assert!(folded.lines().all(|line| line.rsplit_once(' ').unwrap().1.parse::<u64>().is_ok()), "{}", folded);
+}
+
+#[test]
+fn calls_cut_short_by_an_error_still_count() {
+  let (profiler, _) = profile("fun fail() { return nothing; }\nfail();\n");
+  assert_eq!(calls(&profiler), vec![("<script>".to_string(), 1), ("fail:1".to_string(), 1)]);
+  assert_eq!(profiler.folded().lines().count(), 2);
+}
+
+#[test]
+fn profile_option() {
+  let dir = std::env::temp_dir().join(format!("worng-profile-{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  let script = dir.join("fib.worng");
+  let stacks = dir.join("fib.folded");
+  fs::write(&script, PROGRAM).unwrap();
+
+  let output = Command::new(env!("CARGO_BIN_EXE_worng"))
+    .arg("run")
+    .arg("--profile")
+    .arg("--profile-stacks")
+    .arg(&stacks)
+    .arg(&script)
+    .output()
+    .unwrap();
+  assert_eq!(output.status.code(), Some(0));
+  assert_eq!(String::from_utf8_lossy(&output.stdout), "5\n");
+
+  let summary = String::from_utf8_lossy(&output.stderr);
+  assert!(summary.starts_with("function "), "{}", summary);
+  assert!(summary.lines().any(|line| line.starts_with("fib:1 ") && line.contains(" 15 ")), "{}", summary);
+  assert!(summary.contains("return fib(n - 1) + fib(n - 2);"), "{}", summary);
+  assert!(fs::read_to_string(&stacks).unwrap().contains("<script>;fib:1;fib:1 "));
+
+  fs::remove_dir_all(&dir).unwrap();
+}
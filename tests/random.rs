@@ -0,0 +1,46 @@
+//! `random`: every interpreter owns its generator, seeding one never moves the numbers of another.
+
+extern crate worng;
+
+use std::io::{self, BufRead, Cursor, Read};
+
+use worng::{run_string, Worng};
+
+const DRAW: &str = "random.seed(3); print random.random(); read_line(); print random.random_int(1, 1000000);";
+
+// stdin of the first interpreter, each read runs a second one that seeds and draws.
+struct Meddler;
+
+impl Read for Meddler {
+  fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+    Ok(0)
+  }
+}
+
+impl BufRead for Meddler {
+  fn fill_buf(&mut self) -> io::Result<&[u8]> {
+    run_string("random.seed(99); random.random(); random.random_int(1, 10);".to_string());
+    Ok(&[])
+  }
+
+  fn consume(&mut self, _amount: usize) {}
+}
+
+fn draw(reader: &mut dyn BufRead) -> String {
+  let mut output = Vec::new();
+  Worng::new().run_source(DRAW.to_string(), reader, &mut output);
+  String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn seeded_runs_repeat() {
+  let once = run_string(DRAW.to_string());
+  assert_eq!(once.lines().count(), 2, "{}", once);
+  assert_eq!(run_string(DRAW.to_string()), once);
+}
+
+#[test]
+fn interpreters_do_not_share_a_generator() {
+  let alone = draw(&mut Cursor::new(Vec::new()));
+  assert_eq!(draw(&mut Meddler), alone);
+}
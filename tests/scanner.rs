@@ -0,0 +1,12 @@
+//! the scanner: how the source is split into tokens.
+
+extern crate worng;
+
+use worng::run_string;
+
+// a digit after the first letter continues the identifier instead of starting a number.
+#[test]
+fn identifiers_with_digits() {
+  assert_eq!(run_string("var x1 = 1; var a2b3 = 2; print x1 + a2b3;".to_string()), "3\n");
+  assert_eq!(run_string("fun log10(n) { return n; } print log10(7);".to_string()), "7\n");
+}
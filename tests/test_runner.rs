@@ -0,0 +1,101 @@
+//! `worng test`: the report of the `tests/test_runner` fixtures, one directory whose tests all
+//! pass and one with failing tests.
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn worng_test(dir: &Path, path: &str) -> Output {
+  Command::new(env!("CARGO_BIN_EXE_worng")).arg("test").arg(path).current_dir(dir).output().unwrap()
+}
+
+fn fixtures(path: &str) -> Output {
+  worng_test(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("test_runner"), path)
+}
+
+#[test]
+fn passing_tests() {
+  let output = fixtures("passing");
+  let report = String::from_utf8_lossy(&output.stdout);
+  assert_eq!(output.status.code(), Some(0), "{}", report);
+  assert_eq!(
+    report,
+    "\
+passing/math.worng
+  test_add ... ok
+  test_join ... ok
+
+test result: ok. 2 passed; 0 failed.
+"
+  );
+}
+
+#[test]
+fn failing_tests() {
+  let output = fixtures("failing");
+  let report = String::from_utf8_lossy(&output.stdout);
+  assert_eq!(output.status.code(), Some(1), "{}", report);
+  assert_eq!(
+    report,
+    "\
+failing/asserts.worng
+  test_passes ... ok
+  test_assert ... FAILED
+  test_assert_eq ... FAILED
+  test_runtime_error ... FAILED
+
+failures:
+
+failing/asserts.worng::test_assert (line 8)
+  [line 8] Assertion failed: one is not two
+  output:
+    before
+    the assert
+
+failing/asserts.worng::test_assert_eq (line 12)
+  [line 12] Assertion failed: 1 (number) is not equal to 1 (string).
+
+failing/asserts.worng::test_runtime_error (line 16)
+  [Line: 16] add non-number: +
+
+test result: FAILED. 1 passed; 3 failed.
+"
+  );
+}
+
+#[test]
+fn single_file() {
+  let output = fixtures("passing/math.worng");
+  assert_eq!(output.status.code(), Some(0));
+  assert!(String::from_utf8_lossy(&output.stdout).ends_with("\ntest result: ok. 2 passed; 0 failed.\n"));
+}
+
+// every `.worng` file under `tests` has to format, so this one is written out here.
+#[test]
+fn file_that_does_not_compile() {
+  let dir = std::env::temp_dir().join(format!("worng-test-{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(dir.join("broken.worng"), "fun test_broken( {\n}\n").unwrap();
+  fs::write(dir.join("fine.worng"), "fun test_fine() {}\n").unwrap();
+
+  let output = worng_test(&dir, ".");
+  fs::remove_dir_all(&dir).unwrap();
+  let report = String::from_utf8_lossy(&output.stdout);
+  assert_eq!(output.status.code(), Some(1), "{}", report);
+  assert_eq!(
+    report,
+    "\
+./broken.worng
+  doesn't compile ... FAILED
+./fine.worng
+  test_fine ... ok
+
+failures:
+
+./broken.worng
+  [Line: 1] Unexpected token error: Expect parameter name. {
+
+test result: FAILED. 1 passed; 1 failed.
+"
+  );
+}